@@ -12,11 +12,45 @@ use {
         runtool::args::RunToolCommand,
     },
     argh::FromArgs,
+    std::str::FromStr,
 };
 
+/// When to colorize the output.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorChoice {
+    /// Colorize when writing to a terminal and NO_COLOR isn't set.
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => {
+                Err(format!("Unrecognized color choice \"{}\". Expected auto, always or never", s))
+            }
+        }
+    }
+}
+
 #[derive(FromArgs, Debug, PartialEq)]
 #[argh(name = "driver", description = "Support driver development workflows")]
 pub struct DriverCommand {
+    /// suppress all output except for errors.
+    #[argh(switch, short = 'q', long = "quiet")]
+    pub quiet: bool,
+
+    /// when to colorize the output: auto, always or never. Defaults to auto,
+    /// which colorizes only when writing to a terminal and NO_COLOR isn't set.
+    #[argh(option, long = "color", default = "ColorChoice::Auto")]
+    pub color: ColorChoice,
+
     #[argh(subcommand)]
     pub subcommand: DriverSubcommand,
 }
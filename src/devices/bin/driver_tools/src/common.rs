@@ -3,10 +3,120 @@
 // found in the LICENSE file.
 
 use {
+    crate::args::ColorChoice,
     anyhow::{Context, Result},
     fidl_fuchsia_driver_development as fdd,
+    std::io::Write,
 };
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Color {
+    Green,
+    Red,
+}
+
+impl Color {
+    fn ansi_code(&self) -> &'static str {
+        match self {
+            Color::Green => "32",
+            Color::Red => "31",
+        }
+    }
+}
+
+/// Decides whether the subcommands colorize their output, so that colored
+/// output looks the same everywhere and is never written into a pipe unless
+/// asked for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorPolicy {
+    enabled: bool,
+}
+
+impl ColorPolicy {
+    /// `is_terminal` is whether the output goes to a terminal and `no_color`
+    /// is whether the NO_COLOR environment variable is set.
+    pub fn new(choice: ColorChoice, is_terminal: bool, no_color: bool) -> Self {
+        let enabled = match choice {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => is_terminal && !no_color,
+        };
+        ColorPolicy { enabled }
+    }
+
+    /// Decides for output written to stdout, honoring NO_COLOR.
+    pub fn for_stdout(choice: ColorChoice) -> Self {
+        Self::new(choice, is_terminal(libc::STDOUT_FILENO), Self::no_color_from_env())
+    }
+
+    /// Returns whether NO_COLOR is set to a non-empty value.
+    fn no_color_from_env() -> bool {
+        std::env::var_os("NO_COLOR").map_or(false, |value| !value.is_empty())
+    }
+
+    pub fn paint(&self, text: &str, color: Color) -> String {
+        if self.enabled {
+            format!("\x1b[{}m{}\x1b[0m", color.ansi_code(), text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Returns whether the file descriptor refers to a terminal.
+pub fn is_terminal(fd: libc::c_int) -> bool {
+    // isatty only inspects the descriptor.
+    unsafe { libc::isatty(fd) == 1 }
+}
+
+/// Receives updates while a paginated query is in flight.
+pub trait Progress {
+    /// Called after each page is received with the total number of entries
+    /// received so far.
+    fn on_page(&mut self, received: usize);
+
+    /// Called once all pages have been received.
+    fn finish(&mut self) {}
+}
+
+/// Reports nothing, for when there is nowhere to show progress.
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn on_page(&mut self, _received: usize) {}
+}
+
+/// Shows a spinner followed by a count of the entries received so far,
+/// rewriting the same line of a terminal on every update.
+pub struct SpinnerProgress<W: Write> {
+    writer: W,
+    noun: &'static str,
+    frame: usize,
+}
+
+impl<W: Write> SpinnerProgress<W> {
+    const FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+
+    pub fn new(writer: W, noun: &'static str) -> Self {
+        SpinnerProgress { writer, noun, frame: 0 }
+    }
+}
+
+impl<W: Write> Progress for SpinnerProgress<W> {
+    fn on_page(&mut self, received: usize) {
+        let frame = Self::FRAMES[self.frame % Self::FRAMES.len()];
+        self.frame += 1;
+        // Progress is best effort, so failing to show it isn't an error.
+        let _ = write!(self.writer, "\r{} Received {} {}", frame, received, self.noun);
+        let _ = self.writer.flush();
+    }
+
+    fn finish(&mut self) {
+        let _ = write!(self.writer, "\r\x1b[K");
+        let _ = self.writer.flush();
+    }
+}
+
 #[derive(Debug)]
 pub struct DFv1Device(pub fdd::DeviceInfo);
 
@@ -84,6 +194,16 @@ pub async fn get_device_info(
 pub async fn get_driver_info(
     service: &fdd::DriverDevelopmentProxy,
     driver_filter: &[String],
+) -> Result<Vec<fdd::DriverInfo>> {
+    get_driver_info_with_progress(service, driver_filter, &mut NoProgress).await
+}
+
+/// Combines pagination results into a single vector, reporting to `progress`
+/// as each page arrives.
+pub async fn get_driver_info_with_progress(
+    service: &fdd::DriverDevelopmentProxy,
+    driver_filter: &[String],
+    progress: &mut (dyn Progress + Send),
 ) -> Result<Vec<fdd::DriverInfo>> {
     let (iterator, iterator_server) =
         fidl::endpoints::create_proxy::<fdd::DriverInfoIteratorMarker>()?;
@@ -99,7 +219,99 @@ pub async fn get_driver_info(
         if driver_info.len() == 0 {
             break;
         }
-        info_result.append(&mut driver_info)
+        info_result.append(&mut driver_info);
+        progress.on_page(info_result.len());
     }
+    progress.finish();
     Ok(info_result)
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        fuchsia_async::{self as fasync, Task},
+        futures::TryStreamExt,
+    };
+
+    /// Records every update it receives.
+    #[derive(Default)]
+    struct RecordingProgress {
+        pages: Vec<usize>,
+        finished: bool,
+    }
+
+    impl Progress for RecordingProgress {
+        fn on_page(&mut self, received: usize) {
+            self.pages.push(received);
+        }
+
+        fn finish(&mut self) {
+            self.finished = true;
+        }
+    }
+
+    fn driver(name: &str) -> fdd::DriverInfo {
+        fdd::DriverInfo { name: Some(name.to_string()), ..fdd::DriverInfo::EMPTY }
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn test_driver_info_progress() -> Result<()> {
+        let (proxy, mut stream) =
+            fidl::endpoints::create_proxy_and_stream::<fdd::DriverDevelopmentMarker>()?;
+        Task::spawn(async move {
+            while let Some(request) = stream.try_next().await.unwrap() {
+                match request {
+                    fdd::DriverDevelopmentRequest::GetDriverInfo { iterator, .. } => {
+                        let mut pages =
+                            vec![vec![driver("a"), driver("b")], vec![driver("c")], vec![]]
+                                .into_iter();
+                        let mut iterator = iterator.into_stream().unwrap();
+                        while let Some(fdd::DriverInfoIteratorRequest::GetNext { responder }) =
+                            iterator.try_next().await.unwrap()
+                        {
+                            let page = pages.next().unwrap_or_default();
+                            responder.send(&mut page.into_iter()).unwrap();
+                        }
+                    }
+                    _ => panic!("Unexpected driver development request"),
+                }
+            }
+        })
+        .detach();
+
+        let mut progress = RecordingProgress::default();
+        let driver_info = get_driver_info_with_progress(&proxy, &[], &mut progress).await?;
+        assert_eq!(driver_info.len(), 3);
+        assert_eq!(progress.pages, vec![2, 3]);
+        assert!(progress.finished);
+        Ok(())
+    }
+
+    #[test]
+    fn test_spinner_progress() {
+        let mut output = vec![];
+        let mut progress = SpinnerProgress::new(&mut output, "drivers");
+        progress.on_page(2);
+        progress.on_page(3);
+        progress.finish();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\r| Received 2 drivers\r/ Received 3 drivers\r\x1b[K"
+        );
+    }
+
+    #[test]
+    fn test_color_policy() {
+        let paint = |policy: ColorPolicy| policy.paint("text", Color::Green);
+
+        assert_eq!(paint(ColorPolicy::new(ColorChoice::Auto, true, false)), "\x1b[32mtext\x1b[0m");
+        assert_eq!(paint(ColorPolicy::new(ColorChoice::Auto, false, false)), "text");
+        assert_eq!(paint(ColorPolicy::new(ColorChoice::Auto, true, true)), "text");
+        assert_eq!(
+            paint(ColorPolicy::new(ColorChoice::Always, false, true)),
+            "\x1b[32mtext\x1b[0m"
+        );
+        assert_eq!(paint(ColorPolicy::new(ColorChoice::Never, true, false)), "text");
+    }
+}
@@ -12,13 +12,19 @@ mod subcommands;
 use {
     anyhow::{Context, Result},
     args::{DriverCommand, DriverSubcommand},
+    common::{is_terminal, ColorPolicy, NoProgress, Progress, SpinnerProgress},
     fidl_fuchsia_device_manager as fdm, fidl_fuchsia_driver_development as fdd,
     fidl_fuchsia_driver_playground as fdp, fidl_fuchsia_driver_registrar as fdr,
     fidl_fuchsia_io as fio,
     futures::lock::Mutex,
-    std::{io, sync::Arc},
+    std::{
+        io::{self, Write},
+        sync::Arc,
+    },
 };
 
+pub use subcommands::list::ListError;
+
 #[async_trait::async_trait]
 pub trait DriverConnector {
     async fn get_driver_development_proxy(
@@ -32,6 +38,21 @@ pub trait DriverConnector {
 }
 
 pub async fn driver(cmd: DriverCommand, driver_connector: impl DriverConnector) -> Result<()> {
+    let color = ColorPolicy::for_stdout(cmd.color);
+    driver_with_writer(cmd, driver_connector, io::stdout(), color).await
+}
+
+/// Runs the command, writing its normal output to `writer` and colorizing it according to
+/// `color`. Errors are returned rather than written, so nothing at all is written when `--quiet`
+/// is passed.
+async fn driver_with_writer(
+    cmd: DriverCommand,
+    driver_connector: impl DriverConnector,
+    writer: impl Write + Send + Sync + 'static,
+    color: ColorPolicy,
+) -> Result<()> {
+    let mut writer: Box<dyn Write + Send + Sync> =
+        if cmd.quiet { Box::new(io::sink()) } else { Box::new(writer) };
     match cmd.subcommand {
         DriverSubcommand::DebugBind(subcmd) => {
             let driver_development_proxy = driver_connector
@@ -40,7 +61,8 @@ pub async fn driver(cmd: DriverCommand, driver_connector: impl DriverConnector)
                 .context("Failed to get driver development proxy")?;
             subcommands::debug_bind::debug_bind(
                 subcmd,
-                &mut io::stdout(),
+                &mut writer,
+                color,
                 driver_development_proxy,
             )
             .await
@@ -51,14 +73,16 @@ pub async fn driver(cmd: DriverCommand, driver_connector: impl DriverConnector)
                 .get_dev_proxy(subcmd.select)
                 .await
                 .context("Failed to get dev proxy")?;
-            subcommands::device::device(subcmd, dev).await.context("Device subcommand failed")?;
+            subcommands::device::device(subcmd, &mut writer, dev)
+                .await
+                .context("Device subcommand failed")?;
         }
         DriverSubcommand::Dump(subcmd) => {
             let driver_development_proxy = driver_connector
                 .get_driver_development_proxy(subcmd.select)
                 .await
                 .context("Failed to get driver development proxy")?;
-            subcommands::dump::dump(subcmd, driver_development_proxy)
+            subcommands::dump::dump(subcmd, &mut writer, driver_development_proxy)
                 .await
                 .context("Dump subcommand failed")?;
         }
@@ -67,16 +91,29 @@ pub async fn driver(cmd: DriverCommand, driver_connector: impl DriverConnector)
                 .get_driver_development_proxy(subcmd.select)
                 .await
                 .context("Failed to get driver development proxy")?;
-            subcommands::list::list(subcmd, driver_development_proxy)
-                .await
-                .context("List subcommand failed")?;
+            // Progress goes to stderr so it never mixes with the listing itself.
+            let show_progress = !cmd.quiet && is_terminal(libc::STDERR_FILENO);
+            let mut progress: Box<dyn Progress + Send> = if show_progress {
+                Box::new(SpinnerProgress::new(io::stderr(), "drivers"))
+            } else {
+                Box::new(NoProgress)
+            };
+            subcommands::list::list(
+                subcmd,
+                &mut writer,
+                color,
+                &mut *progress,
+                driver_development_proxy,
+            )
+            .await
+            .context("List subcommand failed")?;
         }
         DriverSubcommand::ListDevices(subcmd) => {
             let driver_development_proxy = driver_connector
                 .get_driver_development_proxy(subcmd.select)
                 .await
                 .context("Failed to get driver development proxy")?;
-            subcommands::list_devices::list_devices(subcmd, driver_development_proxy)
+            subcommands::list_devices::list_devices(subcmd, &mut writer, driver_development_proxy)
                 .await
                 .context("List-devices subcommand failed")?;
         }
@@ -85,7 +122,7 @@ pub async fn driver(cmd: DriverCommand, driver_connector: impl DriverConnector)
                 .get_driver_development_proxy(subcmd.select)
                 .await
                 .context("Failed to get driver development proxy")?;
-            subcommands::list_hosts::list_hosts(subcmd, driver_development_proxy)
+            subcommands::list_hosts::list_hosts(subcmd, &mut writer, driver_development_proxy)
                 .await
                 .context("List-hosts subcommand failed")?;
         }
@@ -94,26 +131,30 @@ pub async fn driver(cmd: DriverCommand, driver_connector: impl DriverConnector)
                 .get_dev_proxy(subcmd.select)
                 .await
                 .context("Failed to get dev proxy")?;
-            subcommands::lsblk::lsblk(subcmd, dev).await.context("Lsblk subcommand failed")?;
+            subcommands::lsblk::lsblk(subcmd, &mut writer, dev)
+                .await
+                .context("Lsblk subcommand failed")?;
         }
         DriverSubcommand::Lspci(subcmd) => {
             let dev = driver_connector
                 .get_dev_proxy(subcmd.select)
                 .await
                 .context("Failed to get dev proxy")?;
-            subcommands::lspci::lspci(subcmd, dev).await.context("Lspci subcommand failed")?;
+            subcommands::lspci::lspci(subcmd, &mut writer, dev)
+                .await
+                .context("Lspci subcommand failed")?;
         }
         DriverSubcommand::Lsusb(subcmd) => {
             let device_watcher_proxy = driver_connector
                 .get_device_watcher_proxy()
                 .await
                 .context("Failed to get device watcher proxy")?;
-            subcommands::lsusb::lsusb(subcmd, device_watcher_proxy)
+            subcommands::lsusb::lsusb(subcmd, &mut writer, device_watcher_proxy)
                 .await
                 .context("Lsusb subcommand failed")?;
         }
         DriverSubcommand::PrintInputReport(ref subcmd) => {
-            let writer = Arc::new(Mutex::new(io::stdout()));
+            let writer = Arc::new(Mutex::new(writer));
             let dev =
                 driver_connector.get_dev_proxy(false).await.context("Failed to get dev proxy")?;
             subcommands::print_input_report::print_input_report(subcmd, writer, dev)
@@ -131,7 +172,7 @@ pub async fn driver(cmd: DriverCommand, driver_connector: impl DriverConnector)
                 .context("Failed to get driver development proxy")?;
             subcommands::register::register(
                 subcmd,
-                &mut io::stdout(),
+                &mut writer,
                 driver_registrar_proxy,
                 driver_development_proxy,
             )
@@ -143,7 +184,7 @@ pub async fn driver(cmd: DriverCommand, driver_connector: impl DriverConnector)
                 .get_driver_development_proxy(subcmd.select)
                 .await
                 .context("Failed to get driver development proxy")?;
-            subcommands::restart::restart(subcmd, &mut io::stdout(), driver_development_proxy)
+            subcommands::restart::restart(subcmd, &mut writer, driver_development_proxy)
                 .await
                 .context("Restart subcommand failed")?;
         }
@@ -152,10 +193,395 @@ pub async fn driver(cmd: DriverCommand, driver_connector: impl DriverConnector)
                 .get_tool_runner_proxy(false)
                 .await
                 .context("Failed to get tool runner proxy")?;
-            subcommands::runtool::run_tool(subcmd, &mut io::stdout(), tool_runner_proxy)
+            subcommands::runtool::run_tool(subcmd, &mut writer, tool_runner_proxy)
                 .await
                 .context("RunTool subcommand failed")?;
         }
     };
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        anyhow::anyhow,
+        argh::FromArgs,
+        fuchsia_async::{self as fasync, Task},
+        futures::TryStreamExt,
+    };
+
+    /// A writer whose contents can still be read after it has been moved into
+    /// `driver_with_writer`.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Provides a driver development service that reports the given drivers and devices.
+    struct FakeDriverConnector {
+        driver_info: Vec<fdd::DriverInfo>,
+        device_info: Vec<fdd::DeviceInfo>,
+    }
+
+    impl Default for FakeDriverConnector {
+        fn default() -> Self {
+            FakeDriverConnector {
+                driver_info: vec![fdd::DriverInfo {
+                    name: Some("fake".to_string()),
+                    url: Some("fuchsia-boot:///#meta/fake.cm".to_string()),
+                    ..fdd::DriverInfo::EMPTY
+                }],
+                device_info: vec![],
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DriverConnector for FakeDriverConnector {
+        async fn get_driver_development_proxy(
+            &self,
+            _select: bool,
+        ) -> Result<fdd::DriverDevelopmentProxy> {
+            let (proxy, mut stream) =
+                fidl::endpoints::create_proxy_and_stream::<fdd::DriverDevelopmentMarker>()
+                    .context("Failed to create FIDL proxy")?;
+            let driver_info = self.driver_info.clone();
+            let device_info = self.device_info.clone();
+            Task::spawn(async move {
+                while let Some(request) = stream.try_next().await.unwrap() {
+                    match request {
+                        fdd::DriverDevelopmentRequest::GetDriverInfo { iterator, .. } => {
+                            let mut driver_info = driver_info.clone();
+                            let mut iterator = iterator.into_stream().unwrap();
+                            while let Some(fdd::DriverInfoIteratorRequest::GetNext { responder }) =
+                                iterator.try_next().await.unwrap()
+                            {
+                                responder.send(&mut driver_info.split_off(0).into_iter()).unwrap();
+                            }
+                        }
+                        fdd::DriverDevelopmentRequest::GetDeviceInfo { iterator, .. } => {
+                            let mut device_info = device_info.clone();
+                            let mut iterator = iterator.into_stream().unwrap();
+                            while let Some(fdd::DeviceInfoIteratorRequest::GetNext { responder }) =
+                                iterator.try_next().await.unwrap()
+                            {
+                                responder.send(&mut device_info.split_off(0).into_iter()).unwrap();
+                            }
+                        }
+                        _ => panic!("Unexpected driver development request"),
+                    }
+                }
+            })
+            .detach();
+            Ok(proxy)
+        }
+
+        async fn get_dev_proxy(&self, _select: bool) -> Result<fio::DirectoryProxy> {
+            Err(anyhow!("not supported by fake"))
+        }
+
+        async fn get_device_watcher_proxy(&self) -> Result<fdm::DeviceWatcherProxy> {
+            Err(anyhow!("not supported by fake"))
+        }
+
+        async fn get_driver_registrar_proxy(
+            &self,
+            _select: bool,
+        ) -> Result<fdr::DriverRegistrarProxy> {
+            Err(anyhow!("not supported by fake"))
+        }
+
+        async fn get_tool_runner_proxy(&self, _select: bool) -> Result<fdp::ToolRunnerProxy> {
+            Err(anyhow!("not supported by fake"))
+        }
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn test_list() -> Result<()> {
+        let cmd = DriverCommand::from_args(&["driver"], &["list"]).unwrap();
+        let buffer = SharedBuffer::default();
+        let color = ColorPolicy::new(cmd.color, false, false);
+        driver_with_writer(cmd, FakeDriverConnector::default(), buffer.clone(), color).await?;
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone())?,
+            "fake                : fuchsia-boot:///#meta/fake.cm\n"
+        );
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn test_list_quiet() -> Result<()> {
+        let cmd = DriverCommand::from_args(&["driver"], &["--quiet", "list"]).unwrap();
+        let buffer = SharedBuffer::default();
+        let color = ColorPolicy::new(cmd.color, false, false);
+        driver_with_writer(cmd, FakeDriverConnector::default(), buffer.clone(), color).await?;
+        assert!(buffer.0.lock().unwrap().is_empty());
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn test_list_color_always() -> Result<()> {
+        let cmd = DriverCommand::from_args(&["driver"], &["--color", "always", "list"]).unwrap();
+        let buffer = SharedBuffer::default();
+        let color = ColorPolicy::new(cmd.color, false, true);
+        driver_with_writer(cmd, FakeDriverConnector::default(), buffer.clone(), color).await?;
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone())?,
+            "\x1b[32mfake                \x1b[0m: fuchsia-boot:///#meta/fake.cm\n"
+        );
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn test_list_no_color() -> Result<()> {
+        let cmd = DriverCommand::from_args(&["driver"], &["list"]).unwrap();
+        let buffer = SharedBuffer::default();
+        let color = ColorPolicy::new(cmd.color, true, true);
+        driver_with_writer(cmd, FakeDriverConnector::default(), buffer.clone(), color).await?;
+        assert!(!String::from_utf8(buffer.0.lock().unwrap().clone())?.contains('\x1b'));
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn test_list_filtered_by_rules() -> Result<()> {
+        let connector = || FakeDriverConnector {
+            driver_info: vec![
+                fdd::DriverInfo {
+                    name: Some("bound".to_string()),
+                    url: Some("fuchsia-boot:///#meta/bound.cm".to_string()),
+                    bind_rules: Some(fdd::BindRulesBytecode::BytecodeV2(vec![])),
+                    ..fdd::DriverInfo::EMPTY
+                },
+                fdd::DriverInfo {
+                    name: Some("unbound".to_string()),
+                    url: Some("fuchsia-boot:///#meta/unbound.cm".to_string()),
+                    ..fdd::DriverInfo::EMPTY
+                },
+            ],
+            device_info: vec![],
+        };
+
+        let cmd = DriverCommand::from_args(&["driver"], &["list", "--with-rules"]).unwrap();
+        let buffer = SharedBuffer::default();
+        let color = ColorPolicy::new(cmd.color, false, false);
+        driver_with_writer(cmd, connector(), buffer.clone(), color).await?;
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone())?,
+            "bound               : fuchsia-boot:///#meta/bound.cm\n"
+        );
+
+        let cmd = DriverCommand::from_args(&["driver"], &["list", "--without-rules"]).unwrap();
+        let buffer = SharedBuffer::default();
+        let color = ColorPolicy::new(cmd.color, false, false);
+        driver_with_writer(cmd, connector(), buffer.clone(), color).await?;
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone())?,
+            "unbound             : fuchsia-boot:///#meta/unbound.cm\n"
+        );
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn test_list_sorted() -> Result<()> {
+        let driver = |name: &str, url: Option<&str>, libname: &str| fdd::DriverInfo {
+            name: Some(name.to_string()),
+            url: url.map(str::to_string),
+            libname: Some(libname.to_string()),
+            ..fdd::DriverInfo::EMPTY
+        };
+        let connector = || FakeDriverConnector {
+            driver_info: vec![
+                driver("b", None, "/boot/driver/a.so"),
+                driver("c", Some("fuchsia-boot:///#meta/a.cm"), "/boot/driver/c.so"),
+                driver("a", Some("fuchsia-boot:///#meta/b.cm"), "/boot/driver/b.so"),
+            ],
+            device_info: vec![],
+        };
+
+        for (sort, expected) in [("name", "abc"), ("url", "cab"), ("libname", "bac")] {
+            let cmd = DriverCommand::from_args(&["driver"], &["list", "--sort", sort]).unwrap();
+            let buffer = SharedBuffer::default();
+            let color = ColorPolicy::new(cmd.color, false, false);
+            driver_with_writer(cmd, connector(), buffer.clone(), color).await?;
+            let names = String::from_utf8(buffer.0.lock().unwrap().clone())?
+                .lines()
+                .map(|line| line.chars().next().unwrap())
+                .collect::<String>();
+            assert_eq!(names, expected, "sorted by {}", sort);
+        }
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn test_list_hosts_quiet() -> Result<()> {
+        let connector = || FakeDriverConnector {
+            device_info: vec![fdd::DeviceInfo {
+                driver_host_koid: Some(1),
+                bound_driver_url: Some("fuchsia-boot:///#meta/fake.cm".to_string()),
+                ..fdd::DeviceInfo::EMPTY
+            }],
+            ..FakeDriverConnector::default()
+        };
+
+        let cmd = DriverCommand::from_args(&["driver"], &["list-hosts"]).unwrap();
+        let buffer = SharedBuffer::default();
+        let color = ColorPolicy::new(cmd.color, false, false);
+        driver_with_writer(cmd, connector(), buffer.clone(), color).await?;
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone())?,
+            "Driver Host: 1\n    fuchsia-boot:///#meta/fake.cm\n\n"
+        );
+
+        let cmd = DriverCommand::from_args(&["driver"], &["--quiet", "list-hosts"]).unwrap();
+        let buffer = SharedBuffer::default();
+        let color = ColorPolicy::new(cmd.color, false, false);
+        driver_with_writer(cmd, connector(), buffer.clone(), color).await?;
+        assert!(buffer.0.lock().unwrap().is_empty());
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn test_list_transport_error() -> Result<()> {
+        // Dropping the server end makes every request fail.
+        let (proxy, stream) =
+            fidl::endpoints::create_proxy_and_stream::<fdd::DriverDevelopmentMarker>()?;
+        drop(stream);
+
+        let cmd = match DriverCommand::from_args(&["driver"], &["list"]).unwrap().subcommand {
+            DriverSubcommand::List(cmd) => cmd,
+            _ => unreachable!(),
+        };
+        let result = subcommands::list::list(
+            cmd,
+            &mut io::sink(),
+            ColorPolicy::new(args::ColorChoice::Never, false, false),
+            &mut NoProgress,
+            proxy,
+        )
+        .await;
+        assert!(matches!(result, Err(ListError::Transport(_))));
+        assert_eq!(result.unwrap_err().exit_code(), 1);
+        Ok(())
+    }
+
+    /// Version 2 bind rules that require BIND_PROTOCOL == `protocol`.
+    fn bind_protocol_bytecode(protocol: u32) -> Vec<u8> {
+        use bind::{
+            bytecode_constants::*,
+            test_util::{append_section_header, BIND_HEADER},
+        };
+
+        let mut instructions = vec![RawOp::EqualCondition as u8, RawValueType::NumberValue as u8];
+        instructions.extend_from_slice(&bind::ddk_bind_constants::BIND_PROTOCOL.to_le_bytes());
+        instructions.push(RawValueType::NumberValue as u8);
+        instructions.extend_from_slice(&protocol.to_le_bytes());
+
+        let mut bytecode = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, 0);
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(&instructions);
+        bytecode
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn test_list_json() -> Result<()> {
+        let connector = FakeDriverConnector {
+            driver_info: vec![
+                fdd::DriverInfo {
+                    name: Some("v1".to_string()),
+                    libname: Some("/boot/driver/v1.so".to_string()),
+                    bind_rules: Some(fdd::BindRulesBytecode::BytecodeV1(vec![1, 2, 3])),
+                    ..fdd::DriverInfo::EMPTY
+                },
+                fdd::DriverInfo {
+                    name: Some("v2".to_string()),
+                    url: Some("fuchsia-boot:///#meta/v2.cm".to_string()),
+                    bind_rules: Some(fdd::BindRulesBytecode::BytecodeV2(bind_protocol_bytecode(2))),
+                    ..fdd::DriverInfo::EMPTY
+                },
+            ],
+            device_info: vec![],
+        };
+
+        let cmd = DriverCommand::from_args(&["driver"], &["list", "--json"]).unwrap();
+        let buffer = SharedBuffer::default();
+        let color = ColorPolicy::new(cmd.color, false, false);
+        driver_with_writer(cmd, connector, buffer.clone(), color).await?;
+        let output: serde_json::Value = serde_json::from_slice(&buffer.0.lock().unwrap())?;
+        assert_eq!(
+            output,
+            serde_json::json!([
+                {
+                    "name": "v1",
+                    "url": null,
+                    "libname": "/boot/driver/v1.so",
+                    "bytecode_version": 1,
+                    "bind_rules": [1, 2, 3],
+                },
+                {
+                    "name": "v2",
+                    "url": "fuchsia-boot:///#meta/v2.cm",
+                    "libname": null,
+                    "bytecode_version": 2,
+                    "bind_rules":
+                        bind::debugger::debug_dump::dump_bind_rules(bind_protocol_bytecode(2))?,
+                },
+            ])
+        );
+        Ok(())
+    }
+
+    #[fasync::run_singlethreaded(test)]
+    async fn test_debug_bind_v2() -> Result<()> {
+        let bytecode = bind_protocol_bytecode(2);
+        let connector = |protocol| FakeDriverConnector {
+            driver_info: vec![fdd::DriverInfo {
+                name: Some("fake".to_string()),
+                bind_rules: Some(fdd::BindRulesBytecode::BytecodeV2(bytecode.clone())),
+                ..fdd::DriverInfo::EMPTY
+            }],
+            device_info: vec![fdd::DeviceInfo {
+                topological_path: Some("/dev/sys/fake".to_string()),
+                property_list: Some(fdm::DevicePropertyList {
+                    props: vec![fdm::DeviceProperty {
+                        id: bind::ddk_bind_constants::BIND_PROTOCOL as u16,
+                        reserved: 0,
+                        value: protocol,
+                    }],
+                    str_props: vec![],
+                }),
+                ..fdd::DeviceInfo::EMPTY
+            }],
+        };
+
+        let args = ["debug-bind", "/boot/driver/fake.so", "sys/fake"];
+        let cmd = DriverCommand::from_args(&["driver"], &args).unwrap();
+        let buffer = SharedBuffer::default();
+        let color = ColorPolicy::new(cmd.color, false, false);
+        driver_with_writer(cmd, connector(2), buffer.clone(), color).await?;
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone())?,
+            "Driver binds to the device.\n"
+        );
+
+        let cmd = DriverCommand::from_args(&["driver"], &args).unwrap();
+        let buffer = SharedBuffer::default();
+        let color = ColorPolicy::new(cmd.color, false, false);
+        driver_with_writer(cmd, connector(3), buffer.clone(), color).await?;
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone())?,
+            "Rejected by `0x0001 == 0x2 (2)` at instruction offset 0.\n\
+             Driver doesn't bind to the device.\n"
+        );
+        Ok(())
+    }
+}
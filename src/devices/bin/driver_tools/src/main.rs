@@ -4,7 +4,7 @@
 
 use {
     anyhow::{Context, Result},
-    driver_tools::args::DriverCommand,
+    driver_tools::{args::DriverCommand, ListError},
     fidl::endpoints::{self, Proxy},
     fidl_fuchsia_device_manager as fdm, fidl_fuchsia_driver_development as fdd,
     fidl_fuchsia_driver_playground as fdp, fidl_fuchsia_driver_registrar as fdr,
@@ -66,7 +66,12 @@ impl driver_tools::DriverConnector for DriverConnector {
 }
 
 #[fasync::run_singlethreaded]
-async fn main() -> Result<()> {
+async fn main() {
     let cmd: DriverCommand = argh::from_env();
-    driver_tools::driver(cmd, DriverConnector::new()).await
+    if let Err(err) = driver_tools::driver(cmd, DriverConnector::new()).await {
+        eprintln!("Error: {:?}", err);
+        // Failures of subcommands with their own error type get a distinct exit code per class.
+        let code = err.downcast_ref::<ListError>().map_or(1, ListError::exit_code);
+        std::process::exit(code);
+    }
 }
@@ -5,24 +5,33 @@
 pub mod args;
 
 use {
-    crate::common,
+    crate::common::{self, Color, ColorPolicy},
     anyhow::{format_err, Result},
     args::DebugBindCommand,
     bind::{
-        bytecode_encoder::encode_v1::RawInstruction, compiler::instruction::DeviceProperty,
+        bytecode_encoder::encode_v1::RawInstruction,
+        compiler::{get_deprecated_key_identifiers, instruction::DeviceProperty, Symbol},
         debugger,
+        interpreter::{
+            describe::describe_requirements,
+            match_bind::{
+                format_properties, match_bytecode_verbose, DeviceProperties, KeyResolver,
+                PropertyKey,
+            },
+        },
     },
-    fidl_fuchsia_driver_development as fdd,
+    fidl_fuchsia_device_manager as fdm, fidl_fuchsia_driver_development as fdd,
     std::io::Write,
 };
 
 pub async fn debug_bind(
     cmd: DebugBindCommand,
     writer: &mut impl Write,
+    color: ColorPolicy,
     driver_development_proxy: fdd::DriverDevelopmentProxy,
 ) -> Result<()> {
     let driver_info =
-        common::get_driver_info(&driver_development_proxy, &[cmd.driver_path]).await?;
+        common::get_driver_info(&driver_development_proxy, &[cmd.driver_path.clone()]).await?;
 
     if driver_info.len() != 1 {
         return Err(format_err!(
@@ -31,16 +40,8 @@ pub async fn debug_bind(
         ));
     }
 
-    let bind_rules =
-        match driver_info[0].bind_rules.as_ref().ok_or(format_err!("missing bind rules"))? {
-            fdd::BindRulesBytecode::BytecodeV1(rules) => rules,
-            fdd::BindRulesBytecode::BytecodeV2(_) => {
-                return Err(format_err!("Currently the debugger only supports the old bytecode"));
-            }
-        };
-
     let mut device_info =
-        common::get_device_info(&driver_development_proxy, &[cmd.device_path]).await?;
+        common::get_device_info(&driver_development_proxy, &[cmd.device_path.clone()]).await?;
 
     if device_info.len() != 1 {
         return Err(format_err!(
@@ -49,19 +50,39 @@ pub async fn debug_bind(
         ));
     }
 
+    let property_list =
+        device_info.remove(0).property_list.ok_or(format_err!("missing property_list"))?;
+
+    let binds = match driver_info[0].bind_rules.as_ref().ok_or(format_err!("missing bind rules"))? {
+        fdd::BindRulesBytecode::BytecodeV1(rules) => {
+            debug_bind_v1(&cmd, writer, rules, property_list)?
+        }
+        fdd::BindRulesBytecode::BytecodeV2(bytecode) => {
+            debug_bind_v2(&cmd, writer, bytecode, &property_list)?
+        }
+    };
+
+    if binds {
+        writeln!(writer, "{}", color.paint("Driver binds to the device.", Color::Green))?;
+    } else {
+        writeln!(writer, "{}", color.paint("Driver doesn't bind to the device.", Color::Red))?;
+    }
+    Ok(())
+}
+
+fn debug_bind_v1(
+    cmd: &DebugBindCommand,
+    writer: &mut impl Write,
+    bind_rules: &[fdm::BindInstruction],
+    property_list: fdm::DevicePropertyList,
+) -> Result<bool> {
     let raw_instructions = bind_rules
-        .into_iter()
+        .iter()
         .map(|instruction| RawInstruction([instruction.op, instruction.arg, instruction.debug]))
         .collect::<Vec<RawInstruction<[u32; 3]>>>();
 
-    let device_properties = device_info
-        .remove(0)
-        .property_list
-        .ok_or(format_err!("missing property_list"))?
-        .props
-        .into_iter()
-        .map(DeviceProperty::from)
-        .collect::<Vec<DeviceProperty>>();
+    let device_properties =
+        property_list.props.into_iter().map(DeviceProperty::from).collect::<Vec<DeviceProperty>>();
 
     if cmd.print_instructions {
         writeln!(writer, "Bind program:")?;
@@ -79,14 +100,61 @@ pub async fn debug_bind(
         writeln!(writer)?;
     }
 
-    let binds = debugger::debug(&raw_instructions, &device_properties)
+    Ok(debugger::debug(&raw_instructions, &device_properties)
         .map_err(|err| format_err!("{}", err))?
-        .is_some();
+        .is_some())
+}
 
-    if binds {
-        writeln!(writer, "Driver binds to the device.")?;
-    } else {
-        writeln!(writer, "Driver doesn't bind to the device.")?;
+fn debug_bind_v2(
+    cmd: &DebugBindCommand,
+    writer: &mut impl Write,
+    bytecode: &[u8],
+    property_list: &fdm::DevicePropertyList,
+) -> Result<bool> {
+    let device_properties = device_properties_from_fidl(property_list);
+
+    if cmd.print_instructions {
+        writeln!(writer, "Bind rules:")?;
+        writeln!(
+            writer,
+            "{}",
+            describe_requirements(bytecode).map_err(|err| format_err!("{}", err))?
+        )?;
+        writeln!(writer)?;
     }
-    Ok(())
+
+    if cmd.print_properties {
+        let resolver = get_deprecated_key_identifiers()
+            .into_iter()
+            .map(|(key, name)| (key as u64, name))
+            .collect::<KeyResolver>();
+        writeln!(writer, "Device properties:")?;
+        writeln!(writer, "{}", format_properties(&device_properties, Some(&resolver)))?;
+        writeln!(writer)?;
+    }
+
+    let result = match_bytecode_verbose(bytecode.to_vec(), &device_properties)
+        .map_err(|err| format_err!("{}", err))?;
+    if let (Some(offset), Some(instruction)) = (result.rejected_at, result.rejected_instruction) {
+        writeln!(writer, "Rejected by `{}` at instruction offset {}.", instruction, offset)?;
+    }
+    Ok(result.matched)
+}
+
+/// Converts the properties the driver development service reports for a device into the form the
+/// bind rules matcher takes.
+fn device_properties_from_fidl(property_list: &fdm::DevicePropertyList) -> DeviceProperties {
+    let props = property_list.props.iter().map(|prop| {
+        (PropertyKey::NumberKey(prop.id as u64), Symbol::NumberValue(prop.value as u64))
+    });
+    let str_props = property_list.str_props.iter().map(|prop| {
+        let value = match &prop.value {
+            fdm::PropertyValue::IntValue(value) => Symbol::NumberValue(*value as u64),
+            fdm::PropertyValue::StrValue(value) => Symbol::StringValue(value.clone()),
+            fdm::PropertyValue::BoolValue(value) => Symbol::BoolValue(*value),
+            fdm::PropertyValue::EnumValue(value) => Symbol::EnumValue(value.clone()),
+        };
+        (PropertyKey::StringKey(prop.key.clone()), value)
+    });
+    props.chain(str_props).collect()
 }
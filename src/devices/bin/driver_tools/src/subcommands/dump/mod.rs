@@ -9,19 +9,23 @@ use {
     anyhow::{format_err, Result},
     args::DumpCommand,
     fidl_fuchsia_driver_development as fdd,
-    std::collections::{BTreeMap, VecDeque},
+    std::{
+        collections::{BTreeMap, VecDeque},
+        io::Write,
+    },
 };
 
 trait DeviceInfoPrinter {
-    fn print(&self, tabs: usize) -> Result<()>;
+    fn print(&self, writer: &mut impl Write, tabs: usize) -> Result<()>;
 
-    fn print_graph_node(&self) -> Result<()>;
-    fn print_graph_edge(&self, child: &fdd::DeviceInfo) -> Result<()>;
+    fn print_graph_node(&self, writer: &mut impl Write) -> Result<()>;
+    fn print_graph_edge(&self, writer: &mut impl Write, child: &fdd::DeviceInfo) -> Result<()>;
 }
 
 impl DeviceInfoPrinter for DFv1Device {
-    fn print(&self, tabs: usize) -> Result<()> {
-        println!(
+    fn print(&self, writer: &mut impl Write, tabs: usize) -> Result<()> {
+        writeln!(
+            writer,
             "{:indent$}[{}] pid={} {}",
             "",
             Self::extract_name(
@@ -30,12 +34,13 @@ impl DeviceInfoPrinter for DFv1Device {
             self.0.driver_host_koid.as_ref().ok_or(format_err!("Missing driver host KOID"))?,
             self.0.bound_driver_libname.as_ref().ok_or(format_err!("Missing driver libname"))?,
             indent = tabs * 3,
-        );
+        )?;
         Ok(())
     }
 
-    fn print_graph_node(&self) -> Result<()> {
-        println!(
+    fn print_graph_node(&self, writer: &mut impl Write) -> Result<()> {
+        writeln!(
+            writer,
             "     \"{}\" [label=\"{}\"]",
             self.0.id.as_ref().ok_or(format_err!("Device missing id"))?,
             Self::extract_name(
@@ -45,82 +50,90 @@ impl DeviceInfoPrinter for DFv1Device {
                     .as_ref()
                     .ok_or(format_err!("Device missing topological path"))?
             )
-        );
+        )?;
         Ok(())
     }
 
-    fn print_graph_edge(&self, child: &fdd::DeviceInfo) -> Result<()> {
-        println!(
+    fn print_graph_edge(&self, writer: &mut impl Write, child: &fdd::DeviceInfo) -> Result<()> {
+        writeln!(
+            writer,
             "     \"{}\" -> \"{}\"",
             self.0.id.as_ref().ok_or(format_err!("Device missing id"))?,
             child.id.as_ref().ok_or(format_err!("Child device missing id"))?,
-        );
+        )?;
         Ok(())
     }
 }
 
 impl DeviceInfoPrinter for DFv2Node {
-    fn print(&self, tabs: usize) -> Result<()> {
-        println!(
+    fn print(&self, writer: &mut impl Write, tabs: usize) -> Result<()> {
+        writeln!(
+            writer,
             "{:indent$}[{}] pid={} {}",
             "",
             Self::extract_name(self.0.moniker.as_ref().ok_or(format_err!("Missing moniker"))?),
             self.0.driver_host_koid.as_ref().ok_or(format_err!("Missing driver host KOID"))?,
             self.0.bound_driver_url.as_deref().unwrap_or(""),
             indent = tabs * 3,
-        );
+        )?;
         Ok(())
     }
 
-    fn print_graph_node(&self) -> Result<()> {
-        println!(
+    fn print_graph_node(&self, writer: &mut impl Write) -> Result<()> {
+        writeln!(
+            writer,
             "     \"{}\" [label=\"{}\"]",
             self.0.id.as_ref().ok_or(format_err!("Node missing id"))?,
             Self::extract_name(
                 &self.0.moniker.as_ref().ok_or(format_err!("Node missing moniker"))?
             )
-        );
+        )?;
         Ok(())
     }
 
-    fn print_graph_edge(&self, child: &fdd::DeviceInfo) -> Result<()> {
-        println!(
+    fn print_graph_edge(&self, writer: &mut impl Write, child: &fdd::DeviceInfo) -> Result<()> {
+        writeln!(
+            writer,
             "     \"{}\" -> \"{}\"",
             self.0.id.as_ref().ok_or(format_err!("Node missing id"))?,
             child.id.as_ref().ok_or(format_err!("Child node missing id"))?
-        );
+        )?;
         Ok(())
     }
 }
 
 impl DeviceInfoPrinter for Device {
-    fn print(&self, tabs: usize) -> Result<()> {
+    fn print(&self, writer: &mut impl Write, tabs: usize) -> Result<()> {
         match self {
-            Device::V1(device) => device.print(tabs),
-            Device::V2(device) => device.print(tabs),
+            Device::V1(device) => device.print(writer, tabs),
+            Device::V2(device) => device.print(writer, tabs),
         }
     }
 
-    fn print_graph_node(&self) -> Result<()> {
+    fn print_graph_node(&self, writer: &mut impl Write) -> Result<()> {
         match self {
-            Device::V1(device) => device.print_graph_node(),
-            Device::V2(node) => node.print_graph_node(),
+            Device::V1(device) => device.print_graph_node(writer),
+            Device::V2(node) => node.print_graph_node(writer),
         }
     }
 
-    fn print_graph_edge(&self, child: &fdd::DeviceInfo) -> Result<()> {
+    fn print_graph_edge(&self, writer: &mut impl Write, child: &fdd::DeviceInfo) -> Result<()> {
         match self {
-            Device::V1(device) => device.print_graph_edge(child),
-            Device::V2(node) => node.print_graph_edge(child),
+            Device::V1(device) => device.print_graph_edge(writer, child),
+            Device::V2(node) => node.print_graph_edge(writer, child),
         }
     }
 }
 
-fn print_tree(root: &Device, device_map: &BTreeMap<u64, &Device>) -> Result<()> {
+fn print_tree(
+    writer: &mut impl Write,
+    root: &Device,
+    device_map: &BTreeMap<u64, &Device>,
+) -> Result<()> {
     let mut stack = VecDeque::new();
     stack.push_front((root, 0));
     while let Some((device, tabs)) = stack.pop_front() {
-        device.print(tabs)?;
+        device.print(writer, tabs)?;
         if let Some(child_ids) = &device.get_device_info().child_ids {
             for id in child_ids.iter().rev() {
                 if let Some(child) = device_map.get(id) {
@@ -134,6 +147,7 @@ fn print_tree(root: &Device, device_map: &BTreeMap<u64, &Device>) -> Result<()>
 
 pub async fn dump(
     cmd: DumpCommand,
+    writer: &mut impl Write,
     driver_development_proxy: fdd::DriverDevelopmentProxy,
 ) -> Result<()> {
     let devices: Vec<Device> = common::get_device_info(&driver_development_proxy, &[])
@@ -155,24 +169,27 @@ pub async fn dump(
         .collect::<Result<BTreeMap<_, _>>>()?;
 
     if cmd.graph {
-        println!("digraph {{");
-        println!("     forcelabels = true; splines=\"ortho\"; ranksep = 1.2; nodesep = 0.5;");
-        println!("     node [ shape = \"box\" color = \"#2a5b4f\" penwidth = 2.25 fontname = \"prompt medium\" fontsize = 10 margin = 0.22 ];");
-        println!("     edge [ color = \"#37474f\" penwidth = 1 style = dashed fontname = \"roboto mono\" fontsize = 10 ];");
+        writeln!(writer, "digraph {{")?;
+        writeln!(
+            writer,
+            "     forcelabels = true; splines=\"ortho\"; ranksep = 1.2; nodesep = 0.5;"
+        )?;
+        writeln!(writer, "     node [ shape = \"box\" color = \"#2a5b4f\" penwidth = 2.25 fontname = \"prompt medium\" fontsize = 10 margin = 0.22 ];")?;
+        writeln!(writer, "     edge [ color = \"#37474f\" penwidth = 1 style = dashed fontname = \"roboto mono\" fontsize = 10 ];")?;
         for device in devices.iter() {
-            device.print_graph_node()?;
+            device.print_graph_node(writer)?;
         }
 
         for device in devices.iter() {
             if let Some(child_ids) = &device.get_device_info().child_ids {
                 for id in child_ids.iter().rev() {
                     let child = &device_map[&id];
-                    device.print_graph_edge(child.get_device_info())?;
+                    device.print_graph_edge(writer, child.get_device_info())?;
                 }
             }
         }
 
-        println!("}}");
+        writeln!(writer, "}}")?;
     } else {
         let roots = devices.iter().filter(|device| {
             let device_info = device.get_device_info();
@@ -189,7 +206,7 @@ pub async fn dump(
         });
 
         for root in roots {
-            print_tree(root, &device_map)?;
+            print_tree(writer, root, &device_map)?;
         }
     }
     Ok(())
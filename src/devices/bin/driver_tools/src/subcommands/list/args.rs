@@ -2,7 +2,28 @@
 // Use of this source code is governed by a BSD-style license that can be
 // found in the LICENSE file.
 
-use argh::FromArgs;
+use {argh::FromArgs, std::str::FromStr};
+
+/// The field to sort the listed drivers by.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortKey {
+    Name,
+    Url,
+    Libname,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(SortKey::Name),
+            "url" => Ok(SortKey::Url),
+            "libname" => Ok(SortKey::Libname),
+            _ => Err(format!("Unrecognized sort key \"{}\". Expected name, url or libname", s)),
+        }
+    }
+}
 
 #[derive(FromArgs, Debug, PartialEq)]
 #[argh(
@@ -15,8 +36,22 @@ use argh::FromArgs;
 
 To list only loaded drivers:
 
-    $ driver list --loaded",
-    error_code(1, "Failed to connect to the driver development service")
+    $ driver list --loaded
+
+To list only drivers shipped without bind rules:
+
+    $ driver list --without-rules
+
+To list all drivers sorted by URL:
+
+    $ driver list --sort url
+
+To list drivers as JSON for scripts:
+
+    $ driver list --json",
+    error_code(1, "Failed to connect to the driver development service"),
+    error_code(2, "Failed to decode the bind rules of a driver"),
+    error_code(3, "Invalid combination of filters")
 )]
 pub struct ListCommand {
     /// list all driver properties
@@ -27,6 +62,23 @@ pub struct ListCommand {
     #[argh(switch, long = "loaded")]
     pub loaded: bool,
 
+    /// only list drivers that have bind rules
+    #[argh(switch, long = "with-rules")]
+    pub with_rules: bool,
+
+    /// only list drivers that don't have bind rules
+    #[argh(switch, long = "without-rules")]
+    pub without_rules: bool,
+
+    /// sort the drivers by name, url or libname. Drivers without the field
+    /// are listed last.
+    #[argh(option, long = "sort")]
+    pub sort: Option<SortKey>,
+
+    /// print the drivers as a JSON array, including their bind rules
+    #[argh(switch, long = "json")]
+    pub json: bool,
+
     /// if this exists, the user will be prompted for a component to select.
     #[argh(switch, short = 's', long = "select")]
     pub select: bool,
@@ -5,21 +5,66 @@
 pub mod args;
 
 use {
-    crate::common::{self, Device},
-    anyhow::Result,
-    args::ListCommand,
+    crate::common::{self, Color, ColorPolicy, Device, Progress},
+    anyhow::{anyhow, Context},
+    args::{ListCommand, SortKey},
     bind::debugger::debug_dump::dump_bind_rules,
     fidl_fuchsia_driver_development as fdd,
     futures::join,
-    std::{collections::HashSet, iter::FromIterator},
+    serde_json::json,
+    std::{
+        collections::HashSet,
+        io::{self, Write},
+        iter::FromIterator,
+    },
+    thiserror::Error,
 };
 
+/// The ways in which listing drivers can fail, so that callers can tell them
+/// apart.
+#[derive(Debug, Error)]
+pub enum ListError {
+    /// Querying the driver development service failed.
+    #[error("Failed to query the driver development service")]
+    Transport(#[source] anyhow::Error),
+    /// The bind rules of a driver couldn't be decoded.
+    #[error("Failed to decode bind rules")]
+    Decode(#[source] anyhow::Error),
+    /// The filters passed on the command line can't be applied.
+    #[error("Invalid filter")]
+    Filter(#[source] anyhow::Error),
+    /// Writing the list failed.
+    #[error("Failed to write the driver list")]
+    Output(#[from] io::Error),
+}
+
+impl ListError {
+    /// The exit code the `driver` binary should exit with for the error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ListError::Transport(_) | ListError::Output(_) => 1,
+            ListError::Decode(_) => 2,
+            ListError::Filter(_) => 3,
+        }
+    }
+}
+
 pub async fn list(
     cmd: ListCommand,
+    writer: &mut impl Write,
+    color: ColorPolicy,
+    progress: &mut (dyn Progress + Send),
     driver_development_proxy: fdd::DriverDevelopmentProxy,
-) -> Result<()> {
+) -> Result<(), ListError> {
+    if cmd.with_rules && cmd.without_rules {
+        return Err(ListError::Filter(anyhow!(
+            "--with-rules and --without-rules can't be used together"
+        )));
+    }
+
     let empty: [String; 0] = [];
-    let driver_info = common::get_driver_info(&driver_development_proxy, &empty);
+    let driver_info =
+        common::get_driver_info_with_progress(&driver_development_proxy, &empty, progress);
 
     let driver_info = if cmd.loaded {
         // Query devices and create a hash set of loaded drivers.
@@ -28,8 +73,8 @@ pub async fn list(
         // Await the futures concurrently.
         let (driver_info, device_info) = join!(driver_info, device_info);
 
-        let loaded_driver_set: HashSet<String> =
-            HashSet::from_iter(device_info?.into_iter().filter_map(|device_info| {
+        let loaded_driver_set: HashSet<String> = HashSet::from_iter(
+            device_info.map_err(ListError::Transport)?.into_iter().filter_map(|device_info| {
                 let device: Device = device_info.into();
                 let key = match device {
                     Device::V1(ref info) => &info.0.bound_driver_libname,
@@ -43,10 +88,12 @@ pub async fn list(
                     Some(key) => Some(key.to_owned()),
                     None => None,
                 }
-            }));
+            }),
+        );
 
         // Filter the driver list by the hash set.
-        driver_info?
+        driver_info
+            .map_err(ListError::Transport)?
             .into_iter()
             .filter(|driver| {
                 let mut loaded = false;
@@ -64,49 +111,112 @@ pub async fn list(
             })
             .collect()
     } else {
-        driver_info.await?
+        driver_info.await.map_err(ListError::Transport)?
     };
 
-    if cmd.verbose {
+    let mut driver_info: Vec<fdd::DriverInfo> = driver_info
+        .into_iter()
+        .filter(|driver| {
+            (!cmd.with_rules || driver.bind_rules.is_some())
+                && (!cmd.without_rules || driver.bind_rules.is_none())
+        })
+        .collect();
+
+    if let Some(sort) = cmd.sort {
+        // The sort is stable, so drivers with the same value keep the order they were reported
+        // in.
+        driver_info.sort_by_key(|driver| {
+            let value = match sort {
+                SortKey::Name => driver.name.clone(),
+                SortKey::Url => driver.url.clone(),
+                SortKey::Libname => driver.libname.clone(),
+            };
+            (value.is_none(), value)
+        });
+    }
+
+    if cmd.json {
+        let drivers = driver_info.into_iter().map(driver_to_json).collect::<Result<Vec<_>, _>>()?;
+        serde_json::to_writer_pretty(&mut *writer, &drivers).map_err(io::Error::from)?;
+        writeln!(writer)?;
+    } else if cmd.verbose {
         for driver in driver_info {
             if let Some(name) = driver.name {
-                println!("{0: <10}: {1}", "Name", name);
+                writeln!(writer, "{0: <10}: {1}", "Name", name)?;
             }
             if let Some(url) = driver.url {
-                println!("{0: <10}: {1}", "URL", url);
+                writeln!(writer, "{0: <10}: {1}", "URL", url)?;
             }
             if let Some(libname) = driver.libname {
-                println!("{0: <10}: {1}", "Driver", libname);
+                writeln!(writer, "{0: <10}: {1}", "Driver", libname)?;
             }
             match driver.bind_rules {
                 Some(fdd::BindRulesBytecode::BytecodeV1(bytecode)) => {
-                    println!("{0: <10}: {1}", "Bytecode Version", 1);
-                    println!("{0: <10}({1} bytes): {2:?}", "Bytecode:", bytecode.len(), bytecode);
+                    writeln!(writer, "{0: <10}: {1}", "Bytecode Version", 1)?;
+                    writeln!(
+                        writer,
+                        "{0: <10}({1} bytes): {2:?}",
+                        "Bytecode:",
+                        bytecode.len(),
+                        bytecode
+                    )?;
                 }
                 Some(fdd::BindRulesBytecode::BytecodeV2(bytecode)) => {
-                    println!("{0: <10}: {1}", "Bytecode Version", 2);
-                    println!("{0: <10}({1} bytes): ", "Bytecode:", bytecode.len());
+                    writeln!(writer, "{0: <10}: {1}", "Bytecode Version", 2)?;
+                    writeln!(writer, "{0: <10}({1} bytes): ", "Bytecode:", bytecode.len())?;
                     match dump_bind_rules(bytecode.clone()) {
-                        Ok(bytecode_dump) => println!("{}", bytecode_dump),
+                        Ok(bytecode_dump) => writeln!(writer, "{}", bytecode_dump)?,
                         Err(err) => {
-                            println!("  Issue parsing bytecode \"{}\": {:?}", err, bytecode);
+                            writeln!(
+                                writer,
+                                "  Issue parsing bytecode \"{}\": {:?}",
+                                err, bytecode
+                            )?;
                         }
                     }
                 }
-                _ => println!("{0: <10}: {1}", "Bytecode Version", "Unknown"),
+                _ => writeln!(writer, "{0: <10}: {1}", "Bytecode Version", "Unknown")?,
             }
-            println!();
+            writeln!(writer)?;
         }
     } else {
         for driver in driver_info {
             if let Some(name) = driver.name {
                 let libname_or_url = driver.libname.or(driver.url).unwrap_or("".to_string());
-                println!("{:<20}: {}", name, libname_or_url);
+                let name = color.paint(&format!("{:<20}", name), Color::Green);
+                writeln!(writer, "{}: {}", name, libname_or_url)?;
             } else {
                 let url_or_libname = driver.url.or(driver.libname).unwrap_or("".to_string());
-                println!("{}", url_or_libname);
+                writeln!(writer, "{}", url_or_libname)?;
             }
         }
     }
     Ok(())
 }
+
+// Version 1 bind rules are given as their raw bytes and version 2 ones as the
+// dump of their decoded instructions.
+fn driver_to_json(driver: fdd::DriverInfo) -> Result<serde_json::Value, ListError> {
+    let (bytecode_version, bind_rules) = match driver.bind_rules {
+        Some(fdd::BindRulesBytecode::BytecodeV1(bytecode)) => (json!(1), json!(bytecode)),
+        Some(fdd::BindRulesBytecode::BytecodeV2(bytecode)) => {
+            let dump = dump_bind_rules(bytecode)
+                .with_context(|| {
+                    format!(
+                        "Failed to parse the bind rules of {}",
+                        driver.url.as_deref().or(driver.libname.as_deref()).unwrap_or("a driver")
+                    )
+                })
+                .map_err(ListError::Decode)?;
+            (json!(2), json!(dump))
+        }
+        _ => (serde_json::Value::Null, serde_json::Value::Null),
+    };
+    Ok(json!({
+        "name": driver.name,
+        "url": driver.url,
+        "libname": driver.libname,
+        "bytecode_version": bytecode_version,
+        "bind_rules": bind_rules,
+    }))
+}
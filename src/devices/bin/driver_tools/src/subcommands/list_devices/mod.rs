@@ -10,22 +10,23 @@ use {
     args::ListDevicesCommand,
     fidl_fuchsia_device_manager as fdm, fidl_fuchsia_driver_development as fdd,
     fidl_fuchsia_driver_framework as fdf,
+    std::io::Write,
 };
 
 trait DevicePrinter {
-    fn print(&self) -> Result<()>;
-    fn print_verbose(&self) -> Result<()>;
+    fn print(&self, writer: &mut impl Write) -> Result<()>;
+    fn print_verbose(&self, writer: &mut impl Write) -> Result<()>;
 }
 
 impl DevicePrinter for DFv1Device {
-    fn print(&self) -> Result<()> {
+    fn print(&self, writer: &mut impl Write) -> Result<()> {
         if let Some(ref topo_path) = self.0.topological_path {
-            println!("{}", topo_path);
+            writeln!(writer, "{}", topo_path)?;
         }
         Ok(())
     }
 
-    fn print_verbose(&self) -> Result<()> {
+    fn print_verbose(&self, writer: &mut impl Write) -> Result<()> {
         let topo_path = self
             .0
             .topological_path
@@ -33,34 +34,42 @@ impl DevicePrinter for DFv1Device {
             .map(|s| s.strip_prefix("/dev/").unwrap().to_string())
             .unwrap_or("".to_string());
         let (_, name) = topo_path.rsplit_once('/').unwrap_or(("", &topo_path));
-        println!("{0: <9}: {1}", "Name", name);
-        println!("{0: <9}: {1}", "Topo Path", topo_path);
-        println!("{0: <9}: {1}", "Driver", self.0.bound_driver_libname.as_deref().unwrap_or(""));
-        println!(
+        writeln!(writer, "{0: <9}: {1}", "Name", name)?;
+        writeln!(writer, "{0: <9}: {1}", "Topo Path", topo_path)?;
+        writeln!(
+            writer,
+            "{0: <9}: {1}",
+            "Driver",
+            self.0.bound_driver_libname.as_deref().unwrap_or("")
+        )?;
+        writeln!(
+            writer,
             "{0: <9}: {1:?}",
             "Flags",
             self.0.flags.as_ref().unwrap_or(&fdd::DeviceFlags::empty())
-        );
+        )?;
         if let Some(ref property_list) = self.0.property_list {
             let count = property_list.props.len();
-            println!("{} Properties", count);
+            writeln!(writer, "{} Properties", count)?;
             let mut idx = 1;
             for prop in property_list.props.iter() {
                 let id_name = bind::compiler::get_deprecated_key_identifiers()
                     .get(&(prop.id as u32))
                     .map(std::clone::Clone::clone)
                     .unwrap_or_else(|| format!("{:#08}", prop.id));
-                println!(
+                writeln!(
+                    writer,
                     "[{0: >2}/ {1: >2}] : Key {2:30} Value {3:#08x}",
                     idx, count, id_name, prop.value,
-                );
+                )?;
                 idx += 1;
             }
             let count = property_list.str_props.len();
-            println!("{} String Properties", count);
+            writeln!(writer, "{} String Properties", count)?;
             idx = 1;
             for prop in property_list.str_props.iter() {
-                println!(
+                writeln!(
+                    writer,
                     "[{0: >2}/ {1: >2}] : Key {2:30} Value {3:?}",
                     idx,
                     count,
@@ -71,35 +80,45 @@ impl DevicePrinter for DFv1Device {
                         fdm::PropertyValue::BoolValue(value) => value.to_string(),
                         fdm::PropertyValue::EnumValue(ref value) => format!("Enum({})", value),
                     }
-                );
+                )?;
                 idx += 1;
             }
         } else {
-            println!("0 Properties");
-            println!("0 String Properties");
+            writeln!(writer, "0 Properties")?;
+            writeln!(writer, "0 String Properties")?;
         }
-        println!("");
+        writeln!(writer)?;
         Ok(())
     }
 }
 
 impl DevicePrinter for DFv2Node {
-    fn print(&self) -> Result<()> {
-        println!("{}", self.0.moniker.as_ref().expect("DFv2 node does not have a moniker"));
+    fn print(&self, writer: &mut impl Write) -> Result<()> {
+        writeln!(
+            writer,
+            "{}",
+            self.0.moniker.as_ref().expect("DFv2 node does not have a moniker")
+        )?;
         Ok(())
     }
 
-    fn print_verbose(&self) -> Result<()> {
+    fn print_verbose(&self, writer: &mut impl Write) -> Result<()> {
         let moniker = self.0.moniker.as_deref().expect("DFv2 node does not have a moniker");
         let (_, name) = moniker.rsplit_once('.').unwrap_or(("", &moniker));
-        println!("{0: <9}: {1}", "Name", name);
-        println!("{0: <9}: {1}", "Moniker", moniker);
-        println!("{0: <9}: {1}", "Driver", &self.0.bound_driver_url.as_deref().unwrap_or("None"));
+        writeln!(writer, "{0: <9}: {1}", "Name", name)?;
+        writeln!(writer, "{0: <9}: {1}", "Moniker", moniker)?;
+        writeln!(
+            writer,
+            "{0: <9}: {1}",
+            "Driver",
+            &self.0.bound_driver_url.as_deref().unwrap_or("None")
+        )?;
         if let Some(ref node_property_list) = self.0.node_property_list {
-            println!("{} Properties", node_property_list.len());
+            writeln!(writer, "{} Properties", node_property_list.len())?;
             for i in 0..node_property_list.len() {
                 let node_property = &node_property_list[i];
-                println!(
+                writeln!(
+                    writer,
                     "[{:>2}/ {:>2}] : Key {:30} Value {}",
                     i + 1,
                     node_property_list.len(),
@@ -129,34 +148,35 @@ impl DevicePrinter for DFv2Node {
                                 format!("Enum({})", value),
                         })
                         .unwrap_or("None".to_owned()),
-                );
+                )?;
             }
         } else {
-            println!("0 Properties");
+            writeln!(writer, "0 Properties")?;
         }
-        println!("");
+        writeln!(writer)?;
         Ok(())
     }
 }
 
 impl DevicePrinter for Device {
-    fn print(&self) -> Result<()> {
+    fn print(&self, writer: &mut impl Write) -> Result<()> {
         match self {
-            Device::V1(device) => device.print(),
-            Device::V2(node) => node.print(),
+            Device::V1(device) => device.print(writer),
+            Device::V2(node) => node.print(writer),
         }
     }
 
-    fn print_verbose(&self) -> Result<()> {
+    fn print_verbose(&self, writer: &mut impl Write) -> Result<()> {
         match self {
-            Device::V1(device) => device.print_verbose(),
-            Device::V2(node) => node.print_verbose(),
+            Device::V1(device) => device.print_verbose(writer),
+            Device::V2(node) => node.print_verbose(writer),
         }
     }
 }
 
 pub async fn list_devices(
     cmd: ListDevicesCommand,
+    writer: &mut impl Write,
     driver_development_proxy: fdd::DriverDevelopmentProxy,
 ) -> Result<()> {
     let devices: Vec<Device> = match cmd.device {
@@ -169,11 +189,11 @@ pub async fn list_devices(
 
     if cmd.verbose {
         for device in devices {
-            device.print_verbose()?;
+            device.print_verbose(writer)?;
         }
     } else {
         for device in devices {
-            device.print()?;
+            device.print(writer)?;
         }
     }
     Ok(())
@@ -9,11 +9,15 @@ use {
     anyhow::{format_err, Result},
     args::ListHostsCommand,
     fidl_fuchsia_driver_development as fdd,
-    std::collections::{BTreeMap, BTreeSet},
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        io::Write,
+    },
 };
 
 pub async fn list_hosts(
     _cmd: ListHostsCommand,
+    writer: &mut impl Write,
     driver_development_proxy: fdd::DriverDevelopmentProxy,
 ) -> Result<()> {
     let device_info = common::get_device_info(&driver_development_proxy, &[]).await?;
@@ -35,11 +39,11 @@ pub async fn list_hosts(
     for (koid, drivers) in driver_hosts {
         // Some driver hosts have a proxy loaded but nothing else. Ignore those.
         if !drivers.is_empty() {
-            println!("Driver Host: {}", koid);
+            writeln!(writer, "Driver Host: {}", koid)?;
             for driver in drivers {
-                println!("{:>4}{}", "", driver);
+                writeln!(writer, "{:>4}{}", "", driver)?;
             }
-            println!("");
+            writeln!(writer)?;
         }
     }
     Ok(())
@@ -16,23 +16,29 @@ use {
     fuchsia_async::futures::TryStreamExt,
     fuchsia_zircon_status as zx,
     std::fmt,
+    std::io::Write,
     std::path::Path,
 };
 
-pub async fn lsblk(_cmd: LsblkCommand, dev: fio::DirectoryProxy) -> Result<()> {
-    println!(
+pub async fn lsblk(
+    _cmd: LsblkCommand,
+    writer: &mut impl Write,
+    dev: fio::DirectoryProxy,
+) -> Result<()> {
+    writeln!(
+        writer,
         "{:<3} {:<4} {:<16} {:<20} {:<6} {}",
         "ID", "SIZE", "TYPE", "LABEL", "FLAGS", "DEVICE"
-    );
+    )?;
 
     if let Ok(block_dir) =
         fuchsia_fs::open_directory(&dev, &Path::new("class/block"), fio::OpenFlags::RIGHT_READABLE)
     {
         for device in get_devices::<BlockDevice>(&block_dir).await? {
-            println!("{}", device);
+            writeln!(writer, "{}", device)?;
         }
     } else {
-        println!("Error opening /dev/class/block");
+        writeln!(writer, "Error opening /dev/class/block")?;
     }
 
     if let Ok(skip_block_dir) = fuchsia_fs::open_directory(
@@ -41,10 +47,10 @@ pub async fn lsblk(_cmd: LsblkCommand, dev: fio::DirectoryProxy) -> Result<()> {
         fio::OpenFlags::RIGHT_READABLE,
     ) {
         for device in get_devices::<SkipBlockDevice>(&skip_block_dir).await? {
-            println!("{}", device);
+            writeln!(writer, "{}", device)?;
         }
     } else {
-        println!("Error opening /dev/class/skip-block");
+        writeln!(writer, "Error opening /dev/class/skip-block")?;
     }
     Ok(())
 }
@@ -7,10 +7,14 @@ pub mod args;
 use {
     anyhow::Result, args::LspciCommand, fidl::endpoints::Proxy, fidl_fuchsia_hardware_pci as fhpci,
     fidl_fuchsia_io as fio, lspci::bridge::Bridge, lspci::device::Device, lspci::Args,
-    zstd::block::decompress,
+    std::io::Write, zstd::block::decompress,
 };
 
-pub async fn lspci(cmd: LspciCommand, dev: fio::DirectoryProxy) -> Result<()> {
+pub async fn lspci(
+    cmd: LspciCommand,
+    writer: &mut impl Write,
+    dev: fio::DirectoryProxy,
+) -> Result<()> {
     // Creates the proxy and server
     let (proxy, server) = fidl::endpoints::create_proxy::<fio::NodeMarker>()?;
 
@@ -48,9 +52,9 @@ pub async fn lspci(cmd: LspciCommand, dev: fio::DirectoryProxy) -> Result<()> {
             }
         }
         if device.cfg.header_type & 0x1 == 0x1 {
-            print!("{}", Bridge::new(&device));
+            write!(writer, "{}", Bridge::new(&device))?;
         } else {
-            print!("{}", device);
+            write!(writer, "{}", device)?;
         }
     }
     Ok(())
@@ -4,8 +4,12 @@
 
 pub mod args;
 
-use {anyhow::Result, args::LsusbCommand, fidl_fuchsia_device_manager as fdm};
+use {anyhow::Result, args::LsusbCommand, fidl_fuchsia_device_manager as fdm, std::io::Write};
 
-pub async fn lsusb(cmd: LsusbCommand, device_watcher_proxy: fdm::DeviceWatcherProxy) -> Result<()> {
-    lsusb::lsusb(device_watcher_proxy, cmd.into()).await
+pub async fn lsusb(
+    cmd: LsusbCommand,
+    writer: &mut (impl Write + Send),
+    device_watcher_proxy: fdm::DeviceWatcherProxy,
+) -> Result<()> {
+    lsusb::lsusb(device_watcher_proxy, cmd.into(), writer).await
 }
@@ -29,10 +29,12 @@ pub const FALSE_VAL: u32 = 0x00;
 // Bytecode boolean value for true.
 pub const TRUE_VAL: u32 = 0x01;
 
-#[derive(FromPrimitive, PartialEq)]
+#[derive(FromPrimitive, Clone, Copy, Debug, PartialEq)]
 pub enum RawOp {
     EqualCondition = 0x01,
     InequalCondition = 0x02,
+    // Raises the match score if the values are equal, but doesn't reject the device otherwise.
+    SoftEqualCondition = 0x03,
     UnconditionalJump = 0x10,
     JumpIfEqual = 0x11,
     JumpIfNotEqual = 0x12,
@@ -40,13 +42,15 @@ pub enum RawOp {
     Abort = 0x30,
 }
 
-#[derive(Clone, FromPrimitive)]
+#[derive(Clone, Debug, FromPrimitive, PartialEq, Eq, Hash)]
 pub enum RawValueType {
     Key = 0,
     NumberValue,
     StringValue,
     BoolValue,
     EnumValue,
+    // Followed by a 16-byte value instead of the usual 4 bytes.
+    U128Value,
 }
 
 #[derive(FromPrimitive, PartialEq)]
@@ -3,12 +3,13 @@
 // found in the LICENSE file.
 
 use crate::bytecode_encoder::error::BindRulesEncodeError;
-use crate::compiler::instruction::{Condition, Instruction, InstructionInfo};
+use crate::compiler::instruction::{Condition, Instruction, InstructionInfo, RawAstLocation};
 use crate::compiler::{BindRules, BindRulesDecodeError, Symbol, SymbolicInstructionInfo};
 
 use bitfield::bitfield;
 use byteorder::ByteOrder;
 use num_derive::FromPrimitive;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt;
 
@@ -87,6 +88,7 @@ pub fn to_raw_instruction(
             Ok((c, RawOp::Goto as u32, a, b, v))
         }
         Instruction::Label(a) => Ok((RawCondition::Always as u32, RawOp::Label as u32, a, 0, 0)),
+        Instruction::Prefer(_, _) => Err(BindRulesEncodeError::PreferNotSupported),
     }?;
 
     let mut raw_instruction = RawInstruction([0, 0, 0]);
@@ -152,6 +154,28 @@ pub fn decode_from_bytecode_v1(
     Ok(instructions)
 }
 
+/// Returns the keys of the accept statements in the bytecode, in the order that their failure
+/// instructions appear.
+pub fn accept_block_keys(bytecode: &[u8]) -> Result<Vec<u16>, BindRulesDecodeError> {
+    Ok(decode_from_bytecode_v1(&bytecode.to_vec())?
+        .iter()
+        .filter(|inst| inst.ast_location() == RawAstLocation::AcceptStatementFailure as u32)
+        .map(|inst| inst.extra() as u16)
+        .collect())
+}
+
+pub type InstructionsByLine = BTreeMap<u8, Vec<RawInstruction<[u32; 3]>>>;
+
+/// Groups the instructions in the bytecode by the source line they were compiled from. Lines are
+/// in ascending order and the instructions for each line keep their order in the bytecode.
+pub fn instructions_by_line(bytecode: &[u8]) -> Result<InstructionsByLine, BindRulesDecodeError> {
+    let mut lines = BTreeMap::new();
+    for instruction in decode_from_bytecode_v1(&bytecode.to_vec())? {
+        lines.entry(instruction.line() as u8).or_insert_with(Vec::new).push(instruction);
+    }
+    Ok(lines)
+}
+
 pub fn encode_to_bytecode_v1(bind_rules: BindRules) -> Result<Vec<u8>, BindRulesEncodeError> {
     let result = bind_rules
         .instructions
@@ -185,6 +209,7 @@ pub fn encode_to_string_v1<'a>(
 mod tests {
     use super::*;
     use crate::bytecode_encoder::encode_v1::RawInstruction;
+    use crate::compiler::instruction::InstructionDebug;
     use crate::compiler::{SymbolicInstruction, SymbolicInstructionInfo};
     use std::collections::HashMap;
 
@@ -355,4 +380,88 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(bytes, new_bytes);
     }
+
+    #[test]
+    fn test_accept_block_keys() {
+        let accept_failure = |key| InstructionInfo {
+            instruction: Instruction::Abort(Condition::Always),
+            debug: InstructionDebug {
+                line: 1,
+                ast_location: RawAstLocation::AcceptStatementFailure,
+                extra: key,
+            },
+        };
+        let instructions = vec![
+            accept_failure(0x0100),
+            InstructionInfo {
+                instruction: Instruction::Abort(Condition::NotEqual(
+                    Symbol::DeprecatedKey(0x0200),
+                    Symbol::NumberValue(3),
+                )),
+                debug: InstructionDebug {
+                    line: 2,
+                    ast_location: RawAstLocation::ConditionStatement,
+                    extra: 0,
+                },
+            },
+            accept_failure(0x0300),
+            InstructionInfo::new(Instruction::Match(Condition::Always)),
+        ];
+        let bytes = instructions
+            .into_iter()
+            .map(|info| encode_instruction(info).unwrap())
+            .flat_map(|RawInstruction([a, b, c])| {
+                [a.to_le_bytes(), b.to_le_bytes(), c.to_le_bytes()].concat()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(accept_block_keys(&bytes), Ok(vec![0x0100, 0x0300]));
+    }
+
+    #[test]
+    fn test_instructions_by_line() {
+        let on_line = |line, instruction| InstructionInfo {
+            instruction,
+            debug: InstructionDebug {
+                line,
+                ast_location: RawAstLocation::ConditionStatement,
+                extra: 0,
+            },
+        };
+        let instructions = vec![
+            on_line(
+                3,
+                Instruction::Abort(Condition::NotEqual(
+                    Symbol::DeprecatedKey(0x0100),
+                    Symbol::NumberValue(1),
+                )),
+            ),
+            on_line(
+                5,
+                Instruction::Abort(Condition::Equal(
+                    Symbol::DeprecatedKey(0x0200),
+                    Symbol::NumberValue(2),
+                )),
+            ),
+            on_line(3, Instruction::Match(Condition::Always)),
+        ];
+        let bytes = instructions
+            .into_iter()
+            .map(|info| encode_instruction(info).unwrap())
+            .flat_map(|RawInstruction([a, b, c])| {
+                [a.to_le_bytes(), b.to_le_bytes(), c.to_le_bytes()].concat()
+            })
+            .collect::<Vec<_>>();
+
+        let lines = instructions_by_line(&bytes).unwrap();
+        assert_eq!(lines.keys().cloned().collect::<Vec<_>>(), vec![3, 5]);
+        let summary = |line| {
+            lines[&line]
+                .iter()
+                .map(|inst| (inst.operation(), inst.parameter_b(), inst.value()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(3), vec![(0, 0x0100, 1), (1, 0, 0)]);
+        assert_eq!(summary(5), vec![(0, 0x0200, 2)]);
+    }
 }
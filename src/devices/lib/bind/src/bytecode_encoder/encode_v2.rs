@@ -121,6 +121,9 @@ mod test {
     use super::*;
     use crate::compiler::Symbol;
     use crate::compiler::{SymbolicInstruction, SymbolicInstructionInfo};
+    use crate::interpreter::decode_bind_rules::{
+        DecodedBindRules, DecodedCondition, DecodedInstruction,
+    };
     use crate::parser::bind_library::ValueType;
     use std::collections::HashMap;
 
@@ -270,6 +273,12 @@ mod test {
             self.verify_value(rhs);
         }
 
+        pub fn verify_prefer_equal(&mut self, lhs: EncodedValue, rhs: EncodedValue) {
+            self.verify_next_u8(0x03);
+            self.verify_value(lhs);
+            self.verify_value(rhs);
+        }
+
         pub fn verify_jmp_pad(&mut self) {
             self.verify_next_u8(0x20);
         }
@@ -377,6 +386,41 @@ mod test {
         checker.verify_end();
     }
 
+    #[test]
+    fn test_prefer() {
+        let instructions = vec![
+            SymbolicInstruction::AbortIfNotEqual {
+                lhs: Symbol::DeprecatedKey(1),
+                rhs: Symbol::NumberValue(2),
+            },
+            SymbolicInstruction::PreferIfEqual {
+                lhs: Symbol::Key("teal".to_string(), ValueType::Str),
+                rhs: Symbol::StringValue("garganey".to_string()),
+            },
+        ];
+
+        let bind_rules = BindRules {
+            instructions: to_symbolic_inst_info(instructions),
+            symbol_table: HashMap::new(),
+            use_new_bytecode: true,
+        };
+
+        let mut checker = BytecodeChecker::new(encode_to_bytecode_v2(bind_rules).unwrap());
+        checker.verify_bind_rules_header();
+        checker.verify_sym_table_header(22);
+        checker.verify_symbol_table(&["teal", "garganey"]);
+        checker.verify_instructions_header(COND_ABORT_BYTES * 2);
+        checker.verify_abort_not_equal(
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+        );
+        checker.verify_prefer_equal(
+            EncodedValue { value_type: RawValueType::Key, value: 1 },
+            EncodedValue { value_type: RawValueType::StringValue, value: 2 },
+        );
+        checker.verify_end();
+    }
+
     #[test]
     fn test_empty_symbol_table() {
         let bind_rules = BindRules {
@@ -913,6 +957,65 @@ mod test {
         assert_eq!(Err(BindRulesEncodeError::MissingLabel(2)), encode_to_bytecode_v2(bind_rules));
     }
 
+    #[test]
+    fn test_u128_value() {
+        let instructions = vec![SymbolicInstruction::AbortIfNotEqual {
+            lhs: Symbol::Key("uuid".to_string(), ValueType::Number),
+            rhs: Symbol::U128Value(u128::MAX - 1),
+        }];
+
+        let bind_rules = BindRules {
+            instructions: to_symbolic_inst_info(instructions),
+            symbol_table: HashMap::new(),
+            use_new_bytecode: true,
+        };
+
+        let bytecode = encode_to_bytecode_v2(bind_rules).unwrap();
+        let mut checker = BytecodeChecker::new(bytecode.clone());
+        checker.verify_bind_rules_header();
+        checker.verify_sym_table_header(9);
+        checker.verify_symbol_table(&["uuid"]);
+
+        // The 128-bit value is encoded in 16 bytes.
+        checker.verify_instructions_header(OP_BYTES + VALUE_BYTES + 17);
+        checker.verify_next_u8(0x01);
+        checker.verify_value(EncodedValue { value_type: RawValueType::Key, value: 1 });
+        checker.verify_next_u8(RawValueType::U128Value as u8);
+        for byte in &(u128::MAX - 1).to_le_bytes() {
+            checker.verify_next_u8(*byte);
+        }
+        checker.verify_end();
+
+        let decoded = DecodedBindRules::from_bytecode(bytecode).unwrap();
+        assert_eq!(
+            vec![DecodedInstruction::Condition(DecodedCondition {
+                is_equal: true,
+                lhs: Symbol::Key("uuid".to_string(), ValueType::Str),
+                rhs: Symbol::U128Value(u128::MAX - 1),
+            })],
+            decoded.decoded_instructions
+        );
+    }
+
+    #[test]
+    fn test_u128_value_mismatch_value_types() {
+        let instructions = vec![SymbolicInstruction::AbortIfNotEqual {
+            lhs: Symbol::Key("uuid".to_string(), ValueType::Str),
+            rhs: Symbol::U128Value(1 << 64),
+        }];
+
+        let bind_rules = BindRules {
+            instructions: to_symbolic_inst_info(instructions),
+            symbol_table: HashMap::new(),
+            use_new_bytecode: true,
+        };
+
+        assert_eq!(
+            Err(BindRulesEncodeError::MismatchValueTypes(ValueType::Str, ValueType::Number)),
+            encode_to_bytecode_v2(bind_rules)
+        );
+    }
+
     #[test]
     fn test_mismatch_value_types() {
         let instructions = vec![SymbolicInstruction::AbortIfNotEqual {
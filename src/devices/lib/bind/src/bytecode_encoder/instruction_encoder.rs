@@ -73,6 +73,10 @@ impl<'a> InstructionEncoder<'a> {
                 Instruction::Label(label_id) => {
                     self.append_and_update_label(&mut bytecode, label_id)?;
                 }
+                Instruction::Prefer(lhs, rhs) => {
+                    bytecode.push(RawOp::SoftEqualCondition as u8);
+                    self.append_value_comparison(&mut bytecode, symbol_table_encoder, lhs, rhs)?;
+                }
                 Instruction::Match(_) => {
                     // Match statements are not supported in the new bytecode. Once
                     // the old bytecode is removed, they can be deleted.
@@ -210,7 +214,7 @@ impl<'a> InstructionEncoder<'a> {
         }
 
         let rhs_val_type = match rhs {
-            Symbol::NumberValue(_) => ValueType::Number,
+            Symbol::NumberValue(_) | Symbol::U128Value(_) => ValueType::Number,
             Symbol::StringValue(_) => ValueType::Str,
             Symbol::BoolValue(_) => ValueType::Bool,
             Symbol::EnumValue(_) => ValueType::Enum,
@@ -239,6 +243,13 @@ impl<'a> InstructionEncoder<'a> {
         symbol_table_encoder: &mut SymbolTableEncoder,
         symbol: Symbol,
     ) -> Result<(), BindRulesEncodeError> {
+        // 128-bit values don't fit in the usual four value bytes, so they're encoded in full.
+        if let Symbol::U128Value(value) = symbol {
+            bytecode.push(RawValueType::U128Value as u8);
+            bytecode.extend_from_slice(&value.to_le_bytes());
+            return Ok(());
+        }
+
         let (value_type, value) = match symbol {
             Symbol::NumberValue(value) => Ok((RawValueType::NumberValue as u8, value as u32)),
             Symbol::BoolValue(value) => Ok((RawValueType::BoolValue as u8, value as u32)),
@@ -252,6 +263,7 @@ impl<'a> InstructionEncoder<'a> {
             Symbol::EnumValue(value) => {
                 Ok((RawValueType::EnumValue as u8, symbol_table_encoder.get_key(value)?))
             }
+            Symbol::U128Value(_) | Symbol::Any => Err(BindRulesEncodeError::UnsupportedSymbol),
         }?;
 
         bytecode.push(value_type);
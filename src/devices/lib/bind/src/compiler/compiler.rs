@@ -117,6 +117,7 @@ pub enum SymbolicInstruction {
     JumpIfNotEqual { lhs: Symbol, rhs: Symbol, label: u32 },
     UnconditionalAbort,
     UnconditionalBind,
+    PreferIfEqual { lhs: Symbol, rhs: Symbol },
 }
 
 impl SymbolicInstruction {
@@ -144,6 +145,35 @@ impl SymbolicInstruction {
             SymbolicInstruction::UnconditionalBind => {
                 instruction::Instruction::Match(instruction::Condition::Always)
             }
+            SymbolicInstruction::PreferIfEqual { lhs, rhs } => {
+                instruction::Instruction::Prefer(lhs, rhs)
+            }
+        }
+    }
+}
+
+impl fmt::Display for SymbolicInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolicInstruction::AbortIfEqual { lhs, rhs } => {
+                write!(f, "abort if {} == {}", lhs, rhs)
+            }
+            SymbolicInstruction::AbortIfNotEqual { lhs, rhs } => {
+                write!(f, "abort if {} != {}", lhs, rhs)
+            }
+            SymbolicInstruction::Label(label) => write!(f, "label {}", label),
+            SymbolicInstruction::UnconditionalJump { label } => write!(f, "jump {}", label),
+            SymbolicInstruction::JumpIfEqual { lhs, rhs, label } => {
+                write!(f, "jump {} if {} == {}", label, lhs, rhs)
+            }
+            SymbolicInstruction::JumpIfNotEqual { lhs, rhs, label } => {
+                write!(f, "jump {} if {} != {}", label, lhs, rhs)
+            }
+            SymbolicInstruction::UnconditionalAbort => write!(f, "abort"),
+            SymbolicInstruction::UnconditionalBind => write!(f, "bind"),
+            SymbolicInstruction::PreferIfEqual { lhs, rhs } => {
+                write!(f, "prefer if {} == {}", lhs, rhs)
+            }
         }
     }
 }
@@ -197,22 +227,43 @@ pub fn compile<'a>(
     disable_autobind: bool,
     use_new_bytecode: bool,
 ) -> Result<CompiledBindRules<'a>, CompilerError> {
-    if bind_composite::Ast::try_from(rules_str).is_ok() {
-        return Ok(CompiledBindRules::CompositeBind(compile_bind_composite(
+    let (compiled, warnings) =
+        compile_with_warnings(rules_str, libraries, lint, disable_autobind, use_new_bytecode)?;
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    Ok(compiled)
+}
+
+// Compiles the bind rules like compile(), but returns the linter warnings to the caller rather
+// than printing them. Warnings are only collected when linting.
+pub fn compile_with_warnings<'a>(
+    rules_str: &'a str,
+    libraries: &[String],
+    lint: bool,
+    disable_autobind: bool,
+    use_new_bytecode: bool,
+) -> Result<(CompiledBindRules<'a>, Vec<linter::LinterWarning>), CompilerError> {
+    let mut warnings = vec![];
+    let compiled = if bind_composite::Ast::try_from(rules_str).is_ok() {
+        CompiledBindRules::CompositeBind(compile_bind_composite(
             rules_str,
             libraries,
             lint,
             use_new_bytecode,
-        )?));
-    }
-
-    Ok(CompiledBindRules::Bind(compile_bind(
-        rules_str,
-        libraries,
-        lint,
-        disable_autobind,
-        use_new_bytecode,
-    )?))
+            &mut warnings,
+        )?)
+    } else {
+        CompiledBindRules::Bind(compile_bind(
+            rules_str,
+            libraries,
+            lint,
+            disable_autobind,
+            use_new_bytecode,
+            &mut warnings,
+        )?)
+    };
+    Ok((compiled, warnings))
 }
 
 pub fn compile_bind<'a>(
@@ -221,14 +272,21 @@ pub fn compile_bind<'a>(
     lint: bool,
     disable_autobind: bool,
     use_new_bytecode: bool,
+    warnings: &mut Vec<linter::LinterWarning>,
 ) -> Result<BindRules<'a>, CompilerError> {
     let ast = bind_rules::Ast::try_from(rules_str).map_err(CompilerError::BindParserError)?;
-    let symbol_table = get_symbol_table_from_libraries(&ast.using, libraries, lint)?;
+    let symbol_table = get_symbol_table_from_libraries(&ast.using, libraries, lint, warnings)?;
+    if lint {
+        warnings.extend(linter::lint_nesting_depth(&ast.statements));
+    }
 
     let mut instructions = compile_statements(ast.statements, &symbol_table, use_new_bytecode)?;
     if disable_autobind {
         instructions.insert(0, SymbolicInstructionInfo::disable_autobind());
     }
+    if lint {
+        lint_instructions(&instructions, warnings);
+    }
 
     Ok(BindRules {
         symbol_table: symbol_table,
@@ -237,14 +295,32 @@ pub fn compile_bind<'a>(
     })
 }
 
+/// Compiles the bind rules and renders the resulting instructions one per line, with symbols
+/// resolved. The output doesn't depend on the bytecode encoding, so it's suitable for comparing
+/// against golden files in compiler tests.
+pub fn canonical_text(rules_str: &str, libraries: &[String]) -> Result<String, CompilerError> {
+    let bind_rules = compile_bind(rules_str, libraries, false, false, true, &mut vec![])?;
+    Ok(bind_rules
+        .instructions
+        .iter()
+        .map(|info| format!("{}\n", info.instruction))
+        .collect::<String>())
+}
+
 pub fn compile_bind_composite<'a>(
     rules_str: &'a str,
     libraries: &[String],
     lint: bool,
     use_new_bytecode: bool,
+    warnings: &mut Vec<linter::LinterWarning>,
 ) -> Result<CompositeBindRules<'a>, CompilerError> {
     let ast = bind_composite::Ast::try_from(rules_str).map_err(CompilerError::BindParserError)?;
-    let symbol_table = get_symbol_table_from_libraries(&ast.using, libraries, lint)?;
+    let symbol_table = get_symbol_table_from_libraries(&ast.using, libraries, lint, warnings)?;
+    if lint {
+        for node in std::iter::once(&ast.primary_node).chain(&ast.nodes) {
+            warnings.extend(linter::lint_nesting_depth(&node.statements));
+        }
+    }
     let primary_node = CompositeNode {
         name: ast.primary_node.name,
         instructions: compile_statements(
@@ -262,6 +338,11 @@ pub fn compile_bind_composite<'a>(
                 .map(|inst| CompositeNode { name: name, instructions: inst })
         })
         .collect::<Result<Vec<CompositeNode<'_>>, CompilerError>>()?;
+    if lint {
+        for node in std::iter::once(&primary_node).chain(&additional_nodes) {
+            lint_instructions(&node.instructions, warnings);
+        }
+    }
 
     Ok(CompositeBindRules {
         device_name: ast.name.to_string(),
@@ -271,6 +352,23 @@ pub fn compile_bind_composite<'a>(
     })
 }
 
+// Adds a warning for every unreachable instruction that comes from a statement. Instructions
+// without a location are scaffolding that the compiler emits, such as the jump out of an if block
+// ending in `false;`, which the author can't remove.
+fn lint_instructions(
+    instructions: &[SymbolicInstructionInfo<'_>],
+    warnings: &mut Vec<linter::LinterWarning>,
+) {
+    warnings.extend(linter::lint_unreachable_instructions(instructions).into_iter().filter(
+        |warning| match warning {
+            linter::LinterWarning::UnreachableInstruction { index } => {
+                instructions[*index].location.is_some()
+            }
+            _ => true,
+        },
+    ));
+}
+
 pub fn compile_statements<'a, 'b>(
     statements: Vec<Statement<'a>>,
     symbol_table: &'b SymbolTable,
@@ -303,6 +401,7 @@ impl<'a, 'b> Compiler<'a, 'b> {
     fn lookup_value(&self, value: &Value) -> Result<Symbol, CompilerError> {
         match value {
             Value::NumericLiteral(n) => Ok(Symbol::NumberValue(*n)),
+            Value::U128Literal(n) => Ok(Symbol::U128Value(*n)),
             Value::StringLiteral(s) => Ok(Symbol::StringValue(s.to_string())),
             Value::BoolLiteral(b) => Ok(Symbol::BoolValue(*b)),
             Value::Identifier(ident) => self
@@ -474,6 +573,15 @@ impl<'a, 'b> Compiler<'a, 'b> {
                         return Err(CompilerError::TrueStatementMustBeIsolated);
                     }
                 }
+                Statement::Prefer { span: _, identifier, value } => {
+                    self.instructions.push(SymbolicInstructionInfo {
+                        location: None,
+                        instruction: SymbolicInstruction::PreferIfEqual {
+                            lhs: self.lookup_identifier(&identifier)?,
+                            rhs: self.lookup_value(&value)?,
+                        },
+                    });
+                }
             }
         }
         Ok(())
@@ -848,6 +956,34 @@ mod test {
         );
     }
 
+    #[test]
+    fn prefer() {
+        let rules = bind_rules::Ast {
+            using: vec![],
+            statements: vec![Statement::Prefer {
+                span: Span::new(),
+                identifier: make_identifier!("abc"),
+                value: Value::NumericLiteral(42),
+            }],
+        };
+        let mut symbol_table = HashMap::new();
+        symbol_table.insert(
+            make_identifier!("abc"),
+            Symbol::Key("abc".to_string(), bind_library::ValueType::Number),
+        );
+
+        assert_eq!(
+            compile_statements(rules.statements, &symbol_table, true).unwrap(),
+            vec![SymbolicInstructionInfo {
+                location: None,
+                instruction: SymbolicInstruction::PreferIfEqual {
+                    lhs: Symbol::Key("abc".to_string(), bind_library::ValueType::Number),
+                    rhs: Symbol::NumberValue(42)
+                }
+            }]
+        );
+    }
+
     #[test]
     fn dependencies() {
         let rules = bind_rules::Ast {
@@ -948,4 +1084,92 @@ mod test {
             },]
         );
     }
+
+    #[test]
+    fn canonical_text_golden() {
+        let libraries = vec!["library test.lib; uint FOO; bool BAR;".to_string()];
+        let rules = "using test.lib;
+            test.lib.FOO != 1;
+            if test.lib.BAR == true {
+                test.lib.FOO == 2;
+            } else {
+                test.lib.FOO == 3;
+            }";
+
+        assert_eq!(
+            canonical_text(rules, &libraries).unwrap(),
+            "abort if Key(test.lib.FOO) == 1
+jump 1 if Key(test.lib.BAR) != true
+abort if Key(test.lib.FOO) != 2
+jump 0
+label 1
+abort if Key(test.lib.FOO) != 3
+label 0
+"
+        );
+    }
+
+    #[test]
+    fn unreachable_instruction_warnings() {
+        let libraries = vec!["library test.lib; uint FOO; bool BAR;".to_string()];
+        let rules = "using test.lib;
+            if test.lib.BAR == true {
+                false;
+            } else {
+                test.lib.FOO == 3;
+            }";
+
+        // The jump out of the first block follows an abort, but the author can't remove it.
+        let (compiled, warnings) =
+            compile_with_warnings(rules, &libraries, true, false, true).unwrap();
+        let instructions = match compiled {
+            CompiledBindRules::Bind(bind_rules) => bind_rules.instructions,
+            _ => panic!("expected bind rules"),
+        };
+        assert_eq!(
+            linter::lint_unreachable_instructions(&instructions),
+            vec![linter::LinterWarning::UnreachableInstruction { index: 2 }]
+        );
+        assert_eq!(warnings, vec![]);
+    }
+
+    #[test]
+    fn empty_enum_property_warnings() {
+        let libraries = vec!["library test.lib; enum EMPTY; enum FULL { A, B };".to_string()];
+        let rules = "using test.lib;
+            test.lib.FULL == test.lib.FULL.A;";
+
+        let (_, warnings) = compile_with_warnings(rules, &libraries, true, false, true).unwrap();
+        assert_eq!(
+            warnings,
+            vec![linter::LinterWarning::EmptyEnumProperty(make_identifier!["EMPTY"])]
+        );
+
+        // Warnings are only collected when linting.
+        let (_, warnings) = compile_with_warnings(rules, &libraries, false, false, true).unwrap();
+        assert_eq!(warnings, vec![]);
+    }
+
+    #[test]
+    fn deep_nesting_warnings() {
+        let libraries = vec!["library test.lib; uint FOO;".to_string()];
+        let nested = |depth: usize| {
+            let mut rules = "true;".to_string();
+            for _ in 0..depth {
+                rules = format!("if test.lib.FOO == 1 {{ {} }} else {{ false; }}", rules);
+            }
+            format!("using test.lib; {}", rules)
+        };
+
+        let rules = nested(linter::MAX_NESTING_DEPTH);
+        let (_, warnings) = compile_with_warnings(&rules, &libraries, true, false, true).unwrap();
+        assert_eq!(warnings, vec![]);
+
+        let rules = nested(linter::MAX_NESTING_DEPTH + 1);
+        let (_, warnings) = compile_with_warnings(&rules, &libraries, true, false, true).unwrap();
+        assert_eq!(
+            warnings,
+            vec![linter::LinterWarning::DeepNesting { depth: linter::MAX_NESTING_DEPTH + 1 }]
+        );
+    }
 }
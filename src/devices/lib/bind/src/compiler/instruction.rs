@@ -42,6 +42,8 @@ pub enum Instruction {
     Match(Condition),
     Goto(Condition, u32),
     Label(u32),
+    // Raises the match score if the key has the value, without failing the match otherwise.
+    Prefer(Symbol, Symbol),
 }
 
 #[derive(Clone, FromPrimitive, PartialEq)]
@@ -8,8 +8,9 @@ pub mod instruction;
 pub mod symbol_table;
 
 pub use self::compiler::{
-    compile, compile_bind, compile_statements, BindRules, BindRulesDecodeError, CompiledBindRules,
-    CompilerError, CompositeBindRules, CompositeNode, SymbolicInstruction, SymbolicInstructionInfo,
+    compile, compile_bind, compile_statements, compile_with_warnings, BindRules,
+    BindRulesDecodeError, CompiledBindRules, CompilerError, CompositeBindRules, CompositeNode,
+    SymbolicInstruction, SymbolicInstructionInfo,
 };
 
 pub use self::symbol_table::{get_deprecated_key_identifiers, Symbol, SymbolTable};
@@ -22,6 +22,11 @@ pub enum Symbol {
     StringValue(String),
     BoolValue(bool),
     EnumValue(String),
+    U128Value(u128),
+    /// A wildcard device property value for partial device queries. It never
+    /// appears in bind rules; the matcher treats it as satisfying every
+    /// condition on its key, whether `==` or `!=`.
+    Any,
 }
 
 impl fmt::Display for Symbol {
@@ -33,6 +38,25 @@ impl fmt::Display for Symbol {
             Symbol::StringValue(value) => write!(f, "\"{}\"", value),
             Symbol::BoolValue(value) => write!(f, "{}", value),
             Symbol::EnumValue(value) => write!(f, "Enum({})", value),
+            Symbol::U128Value(value) => write!(f, "{}", value),
+            Symbol::Any => write!(f, "*"),
+        }
+    }
+}
+
+impl Symbol {
+    /// Renders the symbol for diagnostics: numbers in hex followed by their decimal value, strings
+    /// quoted, and keys as `key(name)`.
+    pub fn pretty(&self) -> String {
+        match self {
+            Symbol::DeprecatedKey(key) => format!("key({:#x})", key),
+            Symbol::Key(key, _) => format!("key({})", key),
+            Symbol::NumberValue(value) => format!("{:#x} ({})", value, value),
+            Symbol::StringValue(value) => format!("\"{}\"", value),
+            Symbol::BoolValue(value) => value.to_string(),
+            Symbol::EnumValue(value) => value.clone(),
+            Symbol::U128Value(value) => format!("{:#x} ({})", value, value),
+            Symbol::Any => "*".to_string(),
         }
     }
 }
@@ -41,6 +65,7 @@ pub fn get_symbol_table_from_libraries<'a>(
     using: &Vec<Include>,
     libraries: &[String],
     lint: bool,
+    warnings: &mut Vec<linter::LinterWarning>,
 ) -> Result<SymbolTable, CompilerError> {
     let library_asts: Vec<bind_library::Ast> = libraries
         .into_iter()
@@ -49,6 +74,7 @@ pub fn get_symbol_table_from_libraries<'a>(
                 .map_err(CompilerError::BindParserError)?;
             if lint {
                 linter::lint_library(&ast).map_err(CompilerError::LinterError)?;
+                warnings.extend(linter::lint_library_warnings(&ast));
             }
             Ok(ast)
         })
@@ -745,4 +771,31 @@ mod test {
             );
         }
     }
+
+    mod pretty {
+        use super::*;
+
+        #[test]
+        fn each_variant() {
+            assert_eq!(Symbol::NumberValue(2000).pretty(), "0x7d0 (2000)");
+            assert_eq!(Symbol::NumberValue(0).pretty(), "0x0 (0)");
+            assert_eq!(
+                Symbol::U128Value(1 << 64).pretty(),
+                "0x10000000000000000 (18446744073709551616)"
+            );
+            assert_eq!(Symbol::StringValue("poorwill".to_string()).pretty(), "\"poorwill\"");
+            assert_eq!(Symbol::BoolValue(true).pretty(), "true");
+            assert_eq!(Symbol::BoolValue(false).pretty(), "false");
+            assert_eq!(
+                Symbol::Key("nightjar".to_string(), bind_library::ValueType::Str).pretty(),
+                "key(nightjar)"
+            );
+            assert_eq!(Symbol::DeprecatedKey(0x10).pretty(), "key(0x10)");
+            assert_eq!(
+                Symbol::EnumValue("nightjar.Kind.POORWILL".to_string()).pretty(),
+                "nightjar.Kind.POORWILL"
+            );
+            assert_eq!(Symbol::Any.pretty(), "*");
+        }
+    }
 }
@@ -20,19 +20,19 @@ pub fn dump_bind_rules(bytecode: Vec<u8>) -> Result<String, BytecodeError> {
     }
 }
 
-fn dump_condition(cond: DecodedCondition) -> String {
+pub fn dump_condition(cond: DecodedCondition) -> String {
     let op = if cond.is_equal { "==" } else { "!=" };
     let lhs_dump = match cond.lhs {
         Symbol::NumberValue(value) => {
             let deprecated_keys = get_deprecated_key_identifiers();
             match deprecated_keys.get(&(value as u32)) {
                 Some(value) => value.clone(),
-                None => value.to_string(),
+                None => cond.lhs.pretty(),
             }
         }
-        _ => cond.lhs.to_string(),
+        _ => cond.lhs.pretty(),
     };
-    format!("{} {} {}", lhs_dump, op, cond.rhs)
+    format!("{} {} {}", lhs_dump, op, cond.rhs.pretty())
 }
 
 // TODO(fxb/93365): Print the label IDs in the jump and label statements.
@@ -47,6 +47,9 @@ fn dump_instructions(instructions: Vec<DecodedInstruction>) -> String {
                 None => "  Jump to ??".to_string(),
             },
             DecodedInstruction::Label => "  Label ??".to_string(),
+            DecodedInstruction::SoftCondition(cond) => {
+                format!("  Prefer {}", dump_condition(cond))
+            }
         };
 
         bind_rules_dump.push_str("\n");
@@ -60,13 +63,7 @@ fn dump_instructions(instructions: Vec<DecodedInstruction>) -> String {
 mod test {
     use super::*;
     use crate::bytecode_constants::*;
-
-    const BIND_HEADER: [u8; 8] = [0x42, 0x49, 0x4E, 0x44, 0x02, 0, 0, 0];
-
-    fn append_section_header(bytecode: &mut Vec<u8>, magic_num: u32, sz: u32) {
-        bytecode.extend_from_slice(&magic_num.to_be_bytes());
-        bytecode.extend_from_slice(&sz.to_le_bytes());
-    }
+    use crate::test_util::{append_section_header, BIND_HEADER};
 
     #[test]
     fn test_bytecode_print() {
@@ -93,7 +90,7 @@ mod test {
         bytecode.extend_from_slice(&instructions);
 
         let expected_dump =
-            "\n  83886080 == 268435472\n  Jump if Key(WREN) == \"DUCK\" to ??\n  Abort\n  Label ??";
+            "\n  0x5000000 (83886080) == 0x10000010 (268435472)\n  Jump if key(WREN) == \"DUCK\" to ??\n  Abort\n  Label ??";
         assert_eq!(expected_dump.to_string(), dump_bind_rules(bytecode).unwrap());
     }
 
@@ -0,0 +1,132 @@
+// Copyright 2022 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use crate::debugger::debug_dump::dump_condition;
+use crate::interpreter::common::BytecodeError;
+use crate::interpreter::decode_bind_rules::{
+    DecodedBindRules, DecodedInstruction, InstructionDecoder,
+};
+use byteorder::{ByteOrder, LittleEndian};
+
+// Name of the node reached when the program runs to completion, i.e. the device matches.
+const MATCH_NODE: &str = "match";
+
+fn node_name(offset: usize, instructions_len: usize) -> String {
+    if offset == instructions_len {
+        MATCH_NODE.to_string()
+    } else {
+        format!("n{}", offset)
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Emits the control flow graph of the bind rules bytecode as a Graphviz DOT graph. Each
+// instruction is a node named after its offset in the instruction section. Instructions have an
+// edge to the one following them unless they abort or jump unconditionally, and jumps have an
+// edge labeled "jump" to their target. Aborts are drawn as red octagons.
+pub fn to_dot(bytecode: &[u8]) -> Result<String, BytecodeError> {
+    let bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    let instructions_len = bind_rules.instructions.len();
+    let mut decoder = InstructionDecoder::new(&bind_rules.symbol_table, &bind_rules.instructions);
+
+    let mut nodes = vec![];
+    let mut edges = vec![];
+    loop {
+        let offset = decoder.position();
+        let instruction = match decoder.decode_next()? {
+            Some(instruction) => instruction,
+            None => break,
+        };
+        let name = node_name(offset, instructions_len);
+        let next = node_name(decoder.position(), instructions_len);
+
+        match instruction {
+            DecodedInstruction::UnconditionalAbort => {
+                nodes.push(format!("{} [label=\"abort\", shape=octagon, color=red];", name));
+            }
+            DecodedInstruction::Condition(condition) => {
+                nodes.push(format!("{} [label=\"{}\"];", name, escape(&dump_condition(condition))));
+                edges.push(format!("{} -> {};", name, next));
+            }
+            DecodedInstruction::Jump(condition) => {
+                // The jump offset follows the op byte and counts from the end of the instruction.
+                let offset_amount =
+                    LittleEndian::read_u32(&bind_rules.instructions[offset + 1..offset + 5]);
+                let target =
+                    node_name(decoder.position() + offset_amount as usize, instructions_len);
+                match condition {
+                    Some(condition) => {
+                        nodes.push(format!(
+                            "{} [label=\"jump if {}\"];",
+                            name,
+                            escape(&dump_condition(condition))
+                        ));
+                        edges.push(format!("{} -> {};", name, next));
+                    }
+                    None => nodes.push(format!("{} [label=\"jump\"];", name)),
+                }
+                edges.push(format!("{} -> {} [label=\"jump\"];", name, target));
+            }
+            DecodedInstruction::Label => {
+                nodes.push(format!("{} [label=\"label\"];", name));
+                edges.push(format!("{} -> {};", name, next));
+            }
+            DecodedInstruction::SoftCondition(condition) => {
+                nodes.push(format!(
+                    "{} [label=\"prefer {}\"];",
+                    name,
+                    escape(&dump_condition(condition))
+                ));
+                edges.push(format!("{} -> {};", name, next));
+            }
+        }
+    }
+    nodes.push(format!("{} [label=\"match\", shape=doublecircle];", MATCH_NODE));
+
+    let mut dot = String::from("digraph {\n");
+    for line in nodes.iter().chain(edges.iter()) {
+        dot.push_str("  ");
+        dot.push_str(line);
+        dot.push('\n');
+    }
+    dot.push_str("}\n");
+    Ok(dot)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bytecode_constants::*;
+    use crate::test_util::{append_section_header, BIND_HEADER};
+
+    #[test]
+    fn test_jump_edges() {
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, 0);
+
+        let instructions = [
+            0x01, 0x01, 0x10, 0, 0, 0, 0x01, 0x02, 0, 0, 0, // 16 == 2
+            0x11, 0x0B, 0, 0, 0, 0x01, 0x10, 0, 0, 0, 0x01, 0x03, 0, 0, 0, // jump if 16 == 3
+            0x02, 0x01, 0x10, 0, 0, 0, 0x01, 0x04, 0, 0, 0,    // 16 != 4
+            0x20, // label
+            0x30, // abort
+        ];
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(&instructions);
+
+        let dot = to_dot(&bytecode).unwrap();
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.contains("  n0 [label=\"0x10 (16) == 0x2 (2)\"];\n"));
+        assert!(dot.contains("  n11 [label=\"jump if 0x10 (16) == 0x3 (3)\"];\n"));
+        assert!(dot.contains("  n37 [label=\"label\"];\n"));
+        assert!(dot.contains("  n38 [label=\"abort\", shape=octagon, color=red];\n"));
+        assert!(dot.contains("  n0 -> n11;\n"));
+        assert!(dot.contains("  n11 -> n26;\n"));
+        assert!(dot.contains("  n11 -> n37 [label=\"jump\"];\n"));
+        assert!(!dot.contains("n38 ->"));
+    }
+}
@@ -5,6 +5,7 @@
 pub mod debug_dump;
 pub mod debugger;
 pub mod device_specification;
+pub mod graph;
 pub mod offline_debugger;
 
 pub use self::debugger::debug;
@@ -109,6 +109,64 @@ enum DebuggerOutput<'a> {
     },
 }
 
+// An entry in the trace tree, recorded alongside the flat debugger output.
+#[derive(Debug, PartialEq)]
+enum TraceEntry {
+    Output(usize),
+    ElseBlock,
+}
+
+/// A node in the tree form of the debugger trace. The statements evaluated inside an if block are
+/// children of the condition which selected the block, and the statements evaluated inside an else
+/// block are children of an "Else block" node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceNode {
+    pub description: String,
+    pub children: Vec<TraceNode>,
+}
+
+impl TraceNode {
+    fn new(description: String) -> Self {
+        TraceNode { description, children: Vec::new() }
+    }
+
+    /// Renders the tree as text with one node per line, indenting each node by its depth.
+    pub fn to_indented_string(&self) -> String {
+        let mut lines = Vec::new();
+        self.append_indented_lines(0, &mut lines);
+        lines.join("\n")
+    }
+
+    fn append_indented_lines(&self, depth: usize, lines: &mut Vec<String>) {
+        lines.push(format!("{}{}", "  ".repeat(depth), self.description));
+        for child in &self.children {
+            child.append_indented_lines(depth + 1, lines);
+        }
+    }
+
+    /// Renders the tree in the collapsed stack format understood by flamegraph tools. There is one
+    /// line per leaf, containing the semicolon-separated path from the root followed by a count.
+    pub fn to_folded_stacks(&self) -> String {
+        let mut lines = Vec::new();
+        self.append_folded_lines(&mut Vec::new(), &mut lines);
+        lines.join("\n")
+    }
+
+    fn append_folded_lines<'b>(&'b self, path: &mut Vec<&'b str>, lines: &mut Vec<String>) {
+        path.push(&self.description);
+        if self.children.is_empty() {
+            // Semicolons separate the frames, so they can't appear within a frame.
+            let frames = path.iter().map(|frame| frame.replace(';', ",")).collect::<Vec<_>>();
+            lines.push(format!("{} 1", frames.join(";")));
+        } else {
+            for child in &self.children {
+                child.append_folded_lines(path, lines);
+            }
+        }
+        path.pop();
+    }
+}
+
 pub fn debug_from_str<'a>(
     bind_rules: &BindRules,
     device_file: &str,
@@ -134,11 +192,49 @@ pub fn debug_from_device_specification<'a>(
     Ok(binds)
 }
 
+/// Evaluates the bind rules against the device specification like
+/// `debug_from_device_specification`, but instead of logging the trace returns it as a tree that
+/// reflects the nesting of the if and else blocks that were entered.
+pub fn trace_tree_from_device_specification<'a>(
+    symbol_table: &SymbolTable,
+    instructions: &Vec<SymbolicInstructionInfo<'a>>,
+    device_specification: DeviceSpecification,
+) -> Result<(bool, TraceNode), DebuggerError> {
+    let mut debugger = Debugger::new(&device_specification.properties, symbol_table, instructions)?;
+    let binds = debugger.evaluate_bind_rules()?;
+    let tree = debugger.trace_tree(binds)?;
+    Ok((binds, tree))
+}
+
+/// Evaluates the bind rules against the device specification and returns the path that the
+/// evaluation took through them, one line per step. Every evaluated condition is included whether
+/// it passed or failed, along with the else blocks that were entered. Statements that were never
+/// reached, such as those after the statement which rejected the device or inside blocks that
+/// weren't entered, are left out.
+pub fn failing_path_from_device_specification<'a>(
+    symbol_table: &SymbolTable,
+    instructions: &Vec<SymbolicInstructionInfo<'a>>,
+    device_specification: DeviceSpecification,
+) -> Result<(bool, Vec<String>), DebuggerError> {
+    let mut debugger = Debugger::new(&device_specification.properties, symbol_table, instructions)?;
+    let binds = debugger.evaluate_bind_rules()?;
+    let path = debugger.failing_path()?;
+    Ok((binds, path))
+}
+
 struct Debugger<'a> {
     device_properties: DevicePropertyMap,
     symbol_table: &'a SymbolTable,
     instructions: &'a Vec<SymbolicInstructionInfo<'a>>,
     output: Vec<DebuggerOutput<'a>>,
+    // Each entry of the trace is paired with its nesting depth. The depth is incremented when an
+    // if block or an else block is entered. If statements are always the last statement in their
+    // block, so the depth never needs to be decremented.
+    trace: Vec<(usize, TraceEntry)>,
+    depth: usize,
+    // The end label of an if statement whose most recent condition failed. The next statement
+    // is either another condition of the same if statement, or the start of its else block.
+    pending_else: Option<u32>,
 }
 
 impl<'a> Debugger<'a> {
@@ -149,7 +245,15 @@ impl<'a> Debugger<'a> {
     ) -> Result<Self, DebuggerError> {
         let device_properties = Debugger::construct_property_map(properties, &symbol_table)?;
         let output = Vec::new();
-        Ok(Debugger { device_properties, symbol_table, instructions, output })
+        Ok(Debugger {
+            device_properties,
+            symbol_table,
+            instructions,
+            output,
+            trace: Vec::new(),
+            depth: 0,
+            pending_else: None,
+        })
     }
 
     /// Constructs a map of the device's properties. The keys are of type Symbol, and only keys
@@ -180,6 +284,9 @@ impl<'a> Debugger<'a> {
                     Value::NumericLiteral(n) => {
                         DeviceValue { symbol: Some(Symbol::NumberValue(*n)), identifier: None }
                     }
+                    Value::U128Literal(n) => {
+                        DeviceValue { symbol: Some(Symbol::U128Value(*n)), identifier: None }
+                    }
                     Value::StringLiteral(s) => DeviceValue {
                         symbol: Some(Symbol::StringValue(s.to_string())),
                         identifier: None,
@@ -208,6 +315,7 @@ impl<'a> Debugger<'a> {
 
         while let Some(mut instruction) = instructions.next() {
             let mut jump_label = None;
+            let if_end_label = self.enter_trace_block(instruction)?;
 
             match &instruction.instruction {
                 SymbolicInstruction::AbortIfEqual { lhs, rhs } => {
@@ -231,6 +339,7 @@ impl<'a> Debugger<'a> {
                 SymbolicInstruction::JumpIfEqual { lhs, rhs, label } => {
                     let jump_succeeds = self.device_property_matches(lhs, rhs);
                     self.output_jump_if_equal(&instruction.location, rhs, jump_succeeds)?;
+                    self.update_trace_depth(if_end_label, jump_succeeds);
                     if jump_succeeds {
                         jump_label = Some(label);
                     }
@@ -238,6 +347,7 @@ impl<'a> Debugger<'a> {
                 SymbolicInstruction::JumpIfNotEqual { lhs, rhs, label } => {
                     let jump_succeeds = !self.device_property_matches(lhs, rhs);
                     self.output_jump_if_not_equal(&instruction.location, jump_succeeds)?;
+                    self.update_trace_depth(if_end_label, jump_succeeds);
                     if jump_succeeds {
                         jump_label = Some(label);
                     }
@@ -247,6 +357,8 @@ impl<'a> Debugger<'a> {
                     return Ok(false);
                 }
                 SymbolicInstruction::UnconditionalBind => return Ok(true),
+                // Preferences only affect the match score, never whether the device binds.
+                SymbolicInstruction::PreferIfEqual { .. } => (),
             }
 
             if let Some(label) = jump_label {
@@ -259,6 +371,67 @@ impl<'a> Debugger<'a> {
         Err(DebuggerError::NoOutcome)
     }
 
+    /// Called before evaluating each instruction. If the instruction is an if statement condition,
+    /// returns the label at the end of its if statement. Adds an else block node to the trace if
+    /// the instruction is the first one evaluated in an else block.
+    fn enter_trace_block(
+        &mut self,
+        instruction: &SymbolicInstructionInfo<'a>,
+    ) -> Result<Option<u32>, DebuggerError> {
+        if instruction.location.is_none() {
+            return Ok(None);
+        }
+
+        let if_end_label = match (&instruction.location, &instruction.instruction) {
+            (
+                Some(AstLocation::IfCondition(_)),
+                SymbolicInstruction::JumpIfEqual { label, .. }
+                | SymbolicInstruction::JumpIfNotEqual { label, .. },
+            ) => Some(self.if_statement_end_label(*label)?),
+            _ => None,
+        };
+
+        if let Some(pending_label) = self.pending_else.take() {
+            if if_end_label != Some(pending_label) {
+                self.trace.push((self.depth, TraceEntry::ElseBlock));
+                self.depth += 1;
+            }
+        }
+
+        Ok(if_end_label)
+    }
+
+    /// Called after evaluating an instruction. Statements after a successful if statement
+    /// condition are nested inside it.
+    fn update_trace_depth(&mut self, if_end_label: Option<u32>, jump_succeeds: bool) {
+        if let Some(label) = if_end_label {
+            if jump_succeeds {
+                self.pending_else = Some(label);
+            } else {
+                self.depth += 1;
+            }
+        }
+    }
+
+    /// An if statement condition jumps to the start of the next condition (or the else block) if
+    /// it fails. The block before that label ends with a jump to the end of the if statement.
+    fn if_statement_end_label(&self, label: u32) -> Result<u32, DebuggerError> {
+        let index = self
+            .instructions
+            .iter()
+            .position(|info| info.instruction == SymbolicInstruction::Label(label))
+            .ok_or(DebuggerError::MissingLabel)?;
+        match index.checked_sub(1).map(|i| &self.instructions[i].instruction) {
+            Some(SymbolicInstruction::UnconditionalJump { label }) => Ok(*label),
+            _ => Err(DebuggerError::MissingLabel),
+        }
+    }
+
+    fn push_output(&mut self, output: DebuggerOutput<'a>) {
+        self.trace.push((self.depth, TraceEntry::Output(self.output.len())));
+        self.output.push(output);
+    }
+
     fn device_property_matches(&self, lhs: &Symbol, rhs: &Symbol) -> bool {
         if let Some(DeviceValue { symbol: Some(value_symbol), identifier: _ }) =
             self.device_properties.get(lhs)
@@ -275,7 +448,7 @@ impl<'a> Debugger<'a> {
         aborts: bool,
     ) -> Result<(), DebuggerError> {
         if let Some(AstLocation::ConditionStatement(statement)) = location {
-            self.output.push(DebuggerOutput::ConditionStatement { statement, success: !aborts });
+            self.push_output(DebuggerOutput::ConditionStatement { statement, success: !aborts });
             Ok(())
         } else {
             Err(DebuggerError::IncorrectAstLocation)
@@ -291,7 +464,7 @@ impl<'a> Debugger<'a> {
         match location {
             Some(AstLocation::AcceptStatementValue { identifier, value, span }) => {
                 if jump_succeeds {
-                    self.output.push(DebuggerOutput::AcceptStatementSuccess {
+                    self.push_output(DebuggerOutput::AcceptStatementSuccess {
                         identifier,
                         value,
                         value_symbol: rhs,
@@ -301,8 +474,10 @@ impl<'a> Debugger<'a> {
                 Ok(())
             }
             Some(AstLocation::IfCondition(condition)) => {
-                self.output
-                    .push(DebuggerOutput::IfCondition { condition, success: !jump_succeeds });
+                self.push_output(DebuggerOutput::IfCondition {
+                    condition,
+                    success: !jump_succeeds,
+                });
                 Ok(())
             }
             _ => Err(DebuggerError::IncorrectAstLocation),
@@ -315,7 +490,7 @@ impl<'a> Debugger<'a> {
         jump_succeeds: bool,
     ) -> Result<(), DebuggerError> {
         if let Some(AstLocation::IfCondition(condition)) = location {
-            self.output.push(DebuggerOutput::IfCondition { condition, success: !jump_succeeds });
+            self.push_output(DebuggerOutput::IfCondition { condition, success: !jump_succeeds });
             Ok(())
         } else {
             Err(DebuggerError::IncorrectAstLocation)
@@ -328,11 +503,11 @@ impl<'a> Debugger<'a> {
     ) -> Result<(), DebuggerError> {
         match location {
             Some(AstLocation::FalseStatement(statement)) => {
-                self.output.push(DebuggerOutput::FalseStatement { statement });
+                self.push_output(DebuggerOutput::FalseStatement { statement });
                 Ok(())
             }
             Some(AstLocation::AcceptStatementFailure { identifier, span, symbol: _ }) => {
-                self.output.push(DebuggerOutput::AcceptStatementFailure { identifier, span });
+                self.push_output(DebuggerOutput::AcceptStatementFailure { identifier, span });
                 Ok(())
             }
             _ => Err(DebuggerError::IncorrectAstLocation),
@@ -363,6 +538,42 @@ impl<'a> Debugger<'a> {
         Ok(())
     }
 
+    fn trace_tree(&self, binds: bool) -> Result<TraceNode, DebuggerError> {
+        let root =
+            TraceNode::new(if binds { "Device binds" } else { "Device does not bind" }.to_string());
+
+        // The stack holds the path from the root to the most recently added node.
+        let mut stack = vec![root];
+        for (depth, entry) in &self.trace {
+            let description = match entry {
+                TraceEntry::Output(index) => describe_output(&self.output[*index])?,
+                TraceEntry::ElseBlock => "Else block".to_string(),
+            };
+            while stack.len() > depth + 1 {
+                let node = stack.pop().unwrap();
+                stack.last_mut().unwrap().children.push(node);
+            }
+            stack.push(TraceNode::new(description));
+        }
+        while stack.len() > 1 {
+            let node = stack.pop().unwrap();
+            stack.last_mut().unwrap().children.push(node);
+        }
+
+        Ok(stack.pop().unwrap())
+    }
+
+    fn failing_path(&self) -> Result<Vec<String>, DebuggerError> {
+        // The trace only holds the statements that were evaluated, so it is the path as is.
+        self.trace
+            .iter()
+            .map(|(_, entry)| match entry {
+                TraceEntry::Output(index) => describe_output(&self.output[*index]),
+                TraceEntry::ElseBlock => Ok("Else block".to_string()),
+            })
+            .collect()
+    }
+
     fn log_condition_statement(
         &self,
         statement: &Statement,
@@ -507,6 +718,38 @@ fn condition_needs_actual_value(success: bool, op: &ConditionOp) -> bool {
     }
 }
 
+fn describe_output(output: &DebuggerOutput) -> Result<String, DebuggerError> {
+    let outcome_string = |success: bool| if success { "succeeded" } else { "failed" };
+    match output {
+        DebuggerOutput::ConditionStatement { statement, success } => Ok(format!(
+            "Line {}: Condition statement {}: {}",
+            statement.get_span().line,
+            outcome_string(*success),
+            statement.get_span().fragment
+        )),
+        DebuggerOutput::FalseStatement { statement } => {
+            Ok(format!("Line {}: Abort statement reached.", statement.get_span().line))
+        }
+        DebuggerOutput::AcceptStatementSuccess { identifier, value_symbol, span, .. } => {
+            Ok(format!(
+                "Line {}: Accept statement succeeded: value of `{}` was {}.",
+                span.line,
+                identifier,
+                value_symbol_string(value_symbol)?
+            ))
+        }
+        DebuggerOutput::AcceptStatementFailure { identifier, span } => {
+            Ok(format!("Line {}: Accept statement failed for `{}`.", span.line, identifier))
+        }
+        DebuggerOutput::IfCondition { condition, success } => Ok(format!(
+            "Line {}: If statement condition {}: {}",
+            condition.span.line,
+            outcome_string(*success),
+            condition.span.fragment
+        )),
+    }
+}
+
 fn value_symbol_string(symbol: &Symbol) -> Result<String, DebuggerError> {
     match symbol {
         Symbol::DeprecatedKey(..) => Err(DebuggerError::InvalidValueSymbol(symbol.clone())),
@@ -515,6 +758,8 @@ fn value_symbol_string(symbol: &Symbol) -> Result<String, DebuggerError> {
         Symbol::StringValue(s) => Ok(format!("\"{}\"", s)),
         Symbol::BoolValue(b) => Ok(b.to_string()),
         Symbol::EnumValue(s) => Ok(format!("\"{}\"", s)),
+        Symbol::U128Value(n) => Ok(format!("0x{:x}", n)),
+        Symbol::Any => Ok("*".to_string()),
     }
 }
 
@@ -1092,4 +1337,230 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn trace_tree() {
+        /*
+        if abc == 1 {
+            if xyz == 2 {
+                pqr == 3;
+            } else {
+                abort;
+            }
+        } else if abc == 2 {
+            abort;
+        } else {
+            if xyz == 3 {
+                abort;
+            }
+            else {
+                pqr == 3;
+            }
+        }
+        */
+
+        let make_condition = |line, fragment, lhs, rhs| Condition {
+            span: Span { offset: 0, line, fragment },
+            lhs: make_identifier!(lhs),
+            op: ConditionOp::Equals,
+            rhs: Value::NumericLiteral(rhs),
+        };
+        let make_abort = |line| Statement::False { span: Span { offset: 0, line, fragment: "" } };
+        let make_pqr_statement = |line| Statement::ConditionStatement {
+            span: Span { offset: 0, line, fragment: "pqr == 3;" },
+            condition: make_condition(line, "pqr == 3", "pqr", 3),
+        };
+
+        let statements = vec![Statement::If {
+            span: Span::new(),
+            blocks: vec![
+                (
+                    make_condition(1, "abc == 1", "abc", 1),
+                    vec![Statement::If {
+                        span: Span::new(),
+                        blocks: vec![(
+                            make_condition(2, "xyz == 2", "xyz", 2),
+                            vec![make_pqr_statement(3)],
+                        )],
+                        else_block: vec![make_abort(5)],
+                    }],
+                ),
+                (make_condition(7, "abc == 2", "abc", 2), vec![make_abort(8)]),
+            ],
+            else_block: vec![Statement::If {
+                span: Span::new(),
+                blocks: vec![(make_condition(10, "xyz == 3", "xyz", 3), vec![make_abort(11)])],
+                else_block: vec![make_pqr_statement(14)],
+            }],
+        }];
+        let mut symbol_table = HashMap::new();
+        for &key in &["abc", "xyz", "pqr"] {
+            symbol_table.insert(
+                make_identifier!(key),
+                Symbol::Key(key.to_string(), bind_library::ValueType::Number),
+            );
+        }
+        let instructions = compiler::compile_statements(statements, &symbol_table, false).unwrap();
+
+        let leaf = |description: &str| TraceNode::new(description.to_string());
+        let node = |description: &str, children| TraceNode {
+            description: description.to_string(),
+            children,
+        };
+
+        // Enters the else block nested inside the first if block.
+        let properties = vec![
+            Property { key: make_identifier!("abc"), value: Value::NumericLiteral(1) },
+            Property { key: make_identifier!("xyz"), value: Value::NumericLiteral(5) },
+        ];
+        let mut debugger = Debugger::new(&properties, &symbol_table, &instructions).unwrap();
+        let binds = debugger.evaluate_bind_rules().unwrap();
+        let tree = debugger.trace_tree(binds).unwrap();
+        assert_eq!(
+            tree,
+            node(
+                "Device does not bind",
+                vec![node(
+                    "Line 1: If statement condition succeeded: abc == 1",
+                    vec![
+                        leaf("Line 2: If statement condition failed: xyz == 2"),
+                        node("Else block", vec![leaf("Line 5: Abort statement reached.")]),
+                    ]
+                )]
+            )
+        );
+        assert_eq!(
+            tree.to_indented_string(),
+            "Device does not bind
+  Line 1: If statement condition succeeded: abc == 1
+    Line 2: If statement condition failed: xyz == 2
+    Else block
+      Line 5: Abort statement reached."
+        );
+        assert_eq!(
+            tree.to_folded_stacks(),
+            "Device does not bind;Line 1: If statement condition succeeded: abc == 1;\
+Line 2: If statement condition failed: xyz == 2 1
+Device does not bind;Line 1: If statement condition succeeded: abc == 1;Else block;\
+Line 5: Abort statement reached. 1"
+        );
+
+        // Both conditions of the outer if statement fail, so the outer else block is entered.
+        let properties = vec![
+            Property { key: make_identifier!("abc"), value: Value::NumericLiteral(3) },
+            Property { key: make_identifier!("xyz"), value: Value::NumericLiteral(2) },
+            Property { key: make_identifier!("pqr"), value: Value::NumericLiteral(3) },
+        ];
+        let mut debugger = Debugger::new(&properties, &symbol_table, &instructions).unwrap();
+        let binds = debugger.evaluate_bind_rules().unwrap();
+        assert_eq!(
+            debugger.trace_tree(binds).unwrap(),
+            node(
+                "Device binds",
+                vec![
+                    leaf("Line 1: If statement condition failed: abc == 1"),
+                    leaf("Line 7: If statement condition failed: abc == 2"),
+                    node(
+                        "Else block",
+                        vec![
+                            leaf("Line 10: If statement condition failed: xyz == 3"),
+                            node(
+                                "Else block",
+                                vec![leaf("Line 14: Condition statement succeeded: pqr == 3;")]
+                            ),
+                        ]
+                    ),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn failing_path() {
+        /*
+        abc == 1;
+        if xyz == 2 {
+            pqr == 3;
+        } else {
+            pqr == 3;
+            abc == 2;
+            xyz == 5;
+        }
+        */
+
+        let make_condition = |line, fragment, lhs, rhs| Condition {
+            span: Span { offset: 0, line, fragment },
+            lhs: make_identifier!(lhs),
+            op: ConditionOp::Equals,
+            rhs: Value::NumericLiteral(rhs),
+        };
+        let make_statement = |line, fragment, lhs, rhs| Statement::ConditionStatement {
+            span: Span { offset: 0, line, fragment },
+            condition: make_condition(line, fragment, lhs, rhs),
+        };
+
+        let statements = vec![
+            make_statement(1, "abc == 1;", "abc", 1),
+            Statement::If {
+                span: Span::new(),
+                blocks: vec![(
+                    make_condition(2, "xyz == 2", "xyz", 2),
+                    vec![make_statement(3, "pqr == 3;", "pqr", 3)],
+                )],
+                else_block: vec![
+                    make_statement(5, "pqr == 3;", "pqr", 3),
+                    make_statement(6, "abc == 2;", "abc", 2),
+                    make_statement(7, "xyz == 5;", "xyz", 5),
+                ],
+            },
+        ];
+        let mut symbol_table = HashMap::new();
+        for &key in &["abc", "xyz", "pqr"] {
+            symbol_table.insert(
+                make_identifier!(key),
+                Symbol::Key(key.to_string(), bind_library::ValueType::Number),
+            );
+        }
+        let instructions = compiler::compile_statements(statements, &symbol_table, false).unwrap();
+
+        // The evaluation stops at the failed condition statement on line 6. The conditions that
+        // passed on the way are kept, but the if block and the statement on line 7 were never
+        // reached.
+        let properties = vec![
+            Property { key: make_identifier!("abc"), value: Value::NumericLiteral(1) },
+            Property { key: make_identifier!("xyz"), value: Value::NumericLiteral(5) },
+            Property { key: make_identifier!("pqr"), value: Value::NumericLiteral(3) },
+        ];
+        let mut debugger = Debugger::new(&properties, &symbol_table, &instructions).unwrap();
+        let binds = debugger.evaluate_bind_rules().unwrap();
+        assert!(!binds);
+        assert_eq!(
+            debugger.failing_path().unwrap(),
+            vec![
+                "Line 1: Condition statement succeeded: abc == 1;".to_string(),
+                "Line 2: If statement condition failed: xyz == 2".to_string(),
+                "Else block".to_string(),
+                "Line 5: Condition statement succeeded: pqr == 3;".to_string(),
+                "Line 6: Condition statement failed: abc == 2;".to_string(),
+            ]
+        );
+
+        // When the device binds, every statement in the taken block is evaluated.
+        let properties = vec![
+            Property { key: make_identifier!("abc"), value: Value::NumericLiteral(1) },
+            Property { key: make_identifier!("xyz"), value: Value::NumericLiteral(2) },
+            Property { key: make_identifier!("pqr"), value: Value::NumericLiteral(3) },
+        ];
+        let mut debugger = Debugger::new(&properties, &symbol_table, &instructions).unwrap();
+        let binds = debugger.evaluate_bind_rules().unwrap();
+        assert!(binds);
+        assert_eq!(
+            debugger.failing_path().unwrap(),
+            vec![
+                "Line 1: Condition statement succeeded: abc == 1;".to_string(),
+                "Line 2: If statement condition succeeded: xyz == 2".to_string(),
+                "Line 3: Condition statement succeeded: pqr == 3;".to_string(),
+            ]
+        );
+    }
 }
@@ -8,6 +8,7 @@ use crate::compiler::{BindRulesDecodeError, CompilerError};
 use crate::debugger::debugger;
 use crate::debugger::offline_debugger;
 use crate::interpreter::common::BytecodeError;
+use crate::interpreter::match_bind::DeviceStreamError;
 use crate::linter::LinterError;
 use crate::parser::common::{BindParserError, CompoundIdentifier};
 use crate::test;
@@ -123,6 +124,9 @@ impl From<BindParserError> for UserError {
             BindParserError::FalseKeyword(span) => {
                 UserError::new("E026", "Expected 'false' keyword.", Some(span), false)
             }
+            BindParserError::PreferKeyword(span) => {
+                UserError::new("E034", "Expected 'prefer' keyword.", Some(span), false)
+            }
             BindParserError::NoStatements(span) => UserError::new(
                 "E021",
                 "Bind rules must contain at least one statement.",
@@ -455,6 +459,12 @@ impl From<BindRulesEncodeError> for UserError {
             BindRulesEncodeError::DuplicateCompositeNodeName(name) => {
                 UserError::new("E612", &format!("Node name {} is duplicate", name), None, true)
             }
+            BindRulesEncodeError::PreferNotSupported => UserError::new(
+                "E613",
+                "Prefer instructions are not supported in the old bytecode",
+                None,
+                true,
+            ),
         }
     }
 }
@@ -562,6 +572,55 @@ impl From<BytecodeError> for UserError {
             BytecodeError::MissingNodeIdInSymbolTable => {
                 UserError::new("E821", "Missing node name ID in the symbol table", None, false)
             }
+            BytecodeError::InvalidInstructionOffset(offset) => UserError::new(
+                "E822",
+                &format!("Offset {} is not at the start of an instruction", offset),
+                None,
+                false,
+            ),
+            BytecodeError::InvalidJumpTarget { offset } => UserError::new(
+                "E823",
+                &format!(
+                    "Jump target {} is not the start of an instruction in the program",
+                    offset
+                ),
+                None,
+                false,
+            ),
+            BytecodeError::TrailingBytes { count } => UserError::new(
+                "E824",
+                &format!(
+                    "The instruction section ends with {} bytes that don't form a complete \
+                     instruction",
+                    count
+                ),
+                None,
+                false,
+            ),
+            BytecodeError::NotADecisionTable { index } => UserError::new(
+                "E825",
+                &format!(
+                    "Instruction {} isn't an equality or inequality condition, so the bind rules \
+                     can't be shown as a decision table",
+                    index
+                ),
+                None,
+                false,
+            ),
+        }
+    }
+}
+
+impl From<DeviceStreamError> for UserError {
+    fn from(error: DeviceStreamError) -> Self {
+        match error {
+            DeviceStreamError::Bytecode(error) => UserError::from(error),
+            DeviceStreamError::Read(error) => {
+                UserError::new("E901", &format!("Failed to read devices: {}.", error), None, false)
+            }
+            DeviceStreamError::InvalidDevice(error) => {
+                UserError::new("E902", &format!("Invalid device: {}.", error), None, false)
+            }
         }
     }
 }
@@ -32,6 +32,10 @@ pub enum BytecodeError {
     IncorrectNodeSectionSize,
     MissingDeviceNameInSymbolTable,
     MissingNodeIdInSymbolTable,
+    InvalidInstructionOffset(usize),
+    InvalidJumpTarget { offset: usize },
+    TrailingBytes { count: usize },
+    NotADecisionTable { index: usize },
 }
 
 impl fmt::Display for BytecodeError {
@@ -55,6 +59,15 @@ pub fn next_u32<'a>(iter: &mut BytecodeIter<'a>) -> Result<u32, BytecodeError> {
     Ok(byteorder::LittleEndian::read_u32(&bytes))
 }
 
+pub fn next_u128<'a>(iter: &mut BytecodeIter<'a>) -> Result<u128, BytecodeError> {
+    let mut bytes: [u8; 16] = [0; 16];
+    for i in 0..16 {
+        bytes[i] = *next_u8(iter)?;
+    }
+
+    Ok(byteorder::LittleEndian::read_u128(&bytes))
+}
+
 // Return the next four bytes in the iterator as a u32. If the iterator is empty,
 // return None.
 pub fn try_next_u32(iter: &mut BytecodeIter) -> Result<Option<u32>, BytecodeError> {
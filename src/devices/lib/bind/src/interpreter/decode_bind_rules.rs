@@ -7,7 +7,8 @@ use crate::compiler::Symbol;
 use crate::interpreter::common::*;
 use crate::parser::bind_library;
 use num_traits::FromPrimitive;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 // Each section header contains a uint32 magic number and a uint32 value.
 const HEADER_SZ: usize = 8;
@@ -110,6 +111,8 @@ pub enum DecodedInstruction {
     Condition(DecodedCondition),
     Jump(Option<DecodedCondition>),
     Label,
+    // A condition that raises the match score when it holds but never rejects the device.
+    SoftCondition(DecodedCondition),
 }
 
 // This struct decodes and unwraps the given bytecode into a symbol table
@@ -148,6 +151,185 @@ impl DecodedBindRules {
     }
 }
 
+// Bind rules decoded by a DecodedProgramSet. The symbol table strings are shared with every other
+// program decoded by the same set.
+#[derive(Debug, PartialEq, Clone)]
+pub struct InternedBindRules {
+    pub symbol_table: HashMap<u32, Arc<str>>,
+    pub instructions: Vec<u8>,
+    pub decoded_instructions: Vec<DecodedInstruction>,
+}
+
+// Decodes bind rules bytecode, keeping a single copy of each distinct symbol table string across
+// all of the decoded programs. Useful when many programs are loaded for matching at once.
+#[derive(Debug, Default)]
+pub struct DecodedProgramSet {
+    interner: HashSet<Arc<str>>,
+}
+
+impl DecodedProgramSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn decode(&mut self, bytecode: Vec<u8>) -> Result<InternedBindRules, BytecodeError> {
+        let bind_rules = DecodedBindRules::from_bytecode(bytecode)?;
+        let symbol_table = bind_rules
+            .symbol_table
+            .into_iter()
+            .map(|(key, symbol)| (key, self.intern(symbol)))
+            .collect();
+        Ok(InternedBindRules {
+            symbol_table,
+            instructions: bind_rules.instructions,
+            decoded_instructions: bind_rules.decoded_instructions,
+        })
+    }
+
+    // Returns the number of distinct strings shared by the decoded programs.
+    pub fn interned_count(&self) -> usize {
+        self.interner.len()
+    }
+
+    fn intern(&mut self, symbol: String) -> Arc<str> {
+        if let Some(interned) = self.interner.get(symbol.as_str()) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = Arc::from(symbol);
+        self.interner.insert(interned.clone());
+        interned
+    }
+}
+
+// Decodes the instruction that starts `offset` bytes into the instruction section of the bind
+// rules bytecode, e.g. the target of a jump. Returns the instruction along with the offset of the
+// one following it. The instructions before `offset` are decoded too, to verify that it lands on
+// an instruction boundary.
+pub fn decode_instruction_at(
+    bytecode: &[u8],
+    offset: usize,
+) -> Result<(DecodedInstruction, usize), BytecodeError> {
+    let (symbol_table, inst_bytecode) =
+        get_symbol_table_and_instruction_bytecode(bytecode.to_vec())?;
+    let (inst_sz, inst_bytecode) = read_and_remove_header(inst_bytecode, INSTRUCTION_MAGIC_NUM)?;
+    if inst_bytecode.len() != inst_sz as usize {
+        return Err(BytecodeError::IncorrectSectionSize);
+    }
+
+    let mut decoder = InstructionDecoder::new(&symbol_table, &inst_bytecode);
+    while decoder.position() <= offset {
+        let position = decoder.position();
+        let instruction = match decoder.decode_next()? {
+            Some(instruction) => instruction,
+            None => break,
+        };
+        if position == offset {
+            return Ok((instruction, decoder.position()));
+        }
+    }
+    Err(BytecodeError::InvalidInstructionOffset(offset))
+}
+
+// Re-encodes non-composite bind rules with the symbols they reference sorted, renumbered from
+// SYMB_TBL_START_KEY and stripped of unreferenced entries. Bytecode that differs only in how its
+// symbol table is laid out canonicalizes to the same bytes.
+pub fn canonicalize(bytecode: &[u8]) -> Result<Vec<u8>, BytecodeError> {
+    let bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+
+    // Decoding has already verified the instructions, so every reference is in the symbol table.
+    let references = symbol_references(&bind_rules.instructions)?;
+    let mut symbols =
+        references.iter().map(|(_, key, _)| &bind_rules.symbol_table[key]).collect::<Vec<_>>();
+    symbols.sort();
+    symbols.dedup();
+    let canonical_keys = symbols
+        .iter()
+        .enumerate()
+        .map(|(i, symbol)| (*symbol, SYMB_TBL_START_KEY + i as u32))
+        .collect::<HashMap<_, _>>();
+
+    // Symbol keys are always four bytes, so rewriting them leaves the jump offsets valid.
+    let mut instructions = bind_rules.instructions.clone();
+    for (offset, key, _) in &references {
+        let canonical_key = canonical_keys[&bind_rules.symbol_table[key]];
+        instructions[*offset..*offset + 4].copy_from_slice(&canonical_key.to_le_bytes());
+    }
+
+    let mut symbol_table = vec![];
+    for symbol in &symbols {
+        symbol_table.extend_from_slice(&canonical_keys[*symbol].to_le_bytes());
+        symbol_table.extend_from_slice(symbol.as_bytes());
+        symbol_table.push(0);
+    }
+
+    let mut canonical = vec![];
+    for (magic_num, value) in
+        [(BIND_MAGIC_NUM, BYTECODE_VERSION), (SYMB_MAGIC_NUM, symbol_table.len() as u32)]
+    {
+        canonical.extend_from_slice(&magic_num.to_be_bytes());
+        canonical.extend_from_slice(&value.to_le_bytes());
+    }
+    canonical.extend_from_slice(&symbol_table);
+    canonical.extend_from_slice(&INSTRUCTION_MAGIC_NUM.to_be_bytes());
+    canonical.extend_from_slice(&(instructions.len() as u32).to_le_bytes());
+    canonical.extend_from_slice(&instructions);
+    Ok(canonical)
+}
+
+// Returns the offset, key and value type of every symbol table reference in the instruction
+// section of the bytecode.
+fn symbol_references(
+    instructions: &[u8],
+) -> Result<Vec<(usize, u32, RawValueType)>, BytecodeError> {
+    let mut references = vec![];
+    let mut iter = instructions.iter();
+    while let Some(op_byte) = iter.next() {
+        let op = FromPrimitive::from_u8(*op_byte).ok_or(BytecodeError::InvalidOp(*op_byte))?;
+        let value_count = match op {
+            RawOp::EqualCondition | RawOp::InequalCondition | RawOp::SoftEqualCondition => 2,
+            RawOp::JumpIfEqual | RawOp::JumpIfNotEqual => {
+                next_u32(&mut iter)?;
+                2
+            }
+            RawOp::UnconditionalJump => {
+                next_u32(&mut iter)?;
+                0
+            }
+            RawOp::JumpLandPad | RawOp::Abort => 0,
+        };
+        for _ in 0..value_count {
+            let value_byte = *next_u8(&mut iter)?;
+            let value_type = FromPrimitive::from_u8(value_byte)
+                .ok_or(BytecodeError::InvalidValueType(value_byte))?;
+            match value_type {
+                RawValueType::Key | RawValueType::StringValue | RawValueType::EnumValue => {
+                    let offset = instructions.len() - iter.len();
+                    references.push((offset, next_u32(&mut iter)?, value_type));
+                }
+                RawValueType::U128Value => {
+                    next_u128(&mut iter)?;
+                }
+                RawValueType::NumberValue | RawValueType::BoolValue => {
+                    next_u32(&mut iter)?;
+                }
+            }
+        }
+    }
+    Ok(references)
+}
+
+// Returns the value types that each symbol table entry referenced by non-composite bind rules is
+// used as, keyed by the entry's key. An entry used as more than one type, e.g. as both a key and
+// a string value, points at an encoding bug.
+pub fn symbol_usage(bytecode: &[u8]) -> Result<HashMap<u32, HashSet<RawValueType>>, BytecodeError> {
+    let bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    let mut usage: HashMap<u32, HashSet<RawValueType>> = HashMap::new();
+    for (_, key, value_type) in symbol_references(&bind_rules.instructions)? {
+        usage.entry(key).or_default().insert(value_type);
+    }
+    Ok(usage)
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Node {
     // Symbol table ID for the node name.
@@ -318,7 +500,10 @@ fn read_symbol_table(bytecode: Vec<u8>) -> Result<HashMap<u32, String>, Bytecode
 #[derive(Debug, Clone)]
 pub struct InstructionDecoder<'a> {
     symbol_table: &'a HashMap<u32, String>,
+    instructions: &'a Vec<u8>,
     inst_iter: BytecodeIter<'a>,
+    // The offsets that the decoded jump instructions land on.
+    jump_targets: Vec<usize>,
 }
 
 impl<'a> InstructionDecoder<'a> {
@@ -326,34 +511,88 @@ impl<'a> InstructionDecoder<'a> {
         symbol_table: &'a HashMap<u32, String>,
         instructions: &'a Vec<u8>,
     ) -> InstructionDecoder<'a> {
-        InstructionDecoder { symbol_table: symbol_table, inst_iter: instructions.iter() }
+        InstructionDecoder {
+            symbol_table: symbol_table,
+            instructions: instructions,
+            inst_iter: instructions.iter(),
+            jump_targets: vec![],
+        }
     }
 
     pub fn decode(&mut self) -> Result<Vec<DecodedInstruction>, BytecodeError> {
         let mut decoded_instructions: Vec<DecodedInstruction> = vec![];
-        while let Some(byte) = self.inst_iter.next() {
-            let op_byte = FromPrimitive::from_u8(*byte).ok_or(BytecodeError::InvalidOp(*byte))?;
-            let instruction = match op_byte {
-                RawOp::UnconditionalJump | RawOp::JumpIfEqual | RawOp::JumpIfNotEqual => {
-                    self.decode_control_flow_statement(op_byte)?
+        let mut instruction_offsets = HashSet::new();
+        instruction_offsets.insert(self.position());
+        loop {
+            let start = self.position();
+            match self.decode_next() {
+                Ok(Some(instruction)) => decoded_instructions.push(instruction),
+                Ok(None) => break,
+                // Running out of bytes partway through an instruction that follows a complete
+                // one means there are leftover bytes at the end of the section. If not even the
+                // first instruction is complete, the section was truncated.
+                Err(BytecodeError::UnexpectedEnd) if !decoded_instructions.is_empty() => {
+                    return Err(BytecodeError::TrailingBytes {
+                        count: self.instructions.len() - start,
+                    });
                 }
-                RawOp::EqualCondition | RawOp::InequalCondition => DecodedInstruction::Condition(
-                    self.decode_conditional_statement(op_byte == RawOp::EqualCondition)?,
-                ),
-                RawOp::Abort => DecodedInstruction::UnconditionalAbort,
-                RawOp::JumpLandPad => DecodedInstruction::Label,
-            };
-            decoded_instructions.push(instruction);
+                Err(e) => return Err(e),
+            }
+            instruction_offsets.insert(self.position());
+        }
+
+        // Every jump must land on the start of an instruction inside the program.
+        instruction_offsets.remove(&self.instructions.len());
+        if let Some(offset) =
+            self.jump_targets.iter().find(|target| !instruction_offsets.contains(target))
+        {
+            return Err(BytecodeError::InvalidJumpTarget { offset: *offset });
+        }
+
+        // And the instruction it lands on must be a jump pad.
+        if self
+            .jump_targets
+            .iter()
+            .any(|target| self.instructions[*target] != RawOp::JumpLandPad as u8)
+        {
+            return Err(BytecodeError::InvalidJumpLocation);
         }
 
         Ok(decoded_instructions)
     }
 
+    // Decodes the next instruction, or returns None if there are no instructions left.
+    pub fn decode_next(&mut self) -> Result<Option<DecodedInstruction>, BytecodeError> {
+        let byte = match self.inst_iter.next() {
+            Some(byte) => byte,
+            None => return Ok(None),
+        };
+        let op_byte = FromPrimitive::from_u8(*byte).ok_or(BytecodeError::InvalidOp(*byte))?;
+        let instruction = match op_byte {
+            RawOp::UnconditionalJump | RawOp::JumpIfEqual | RawOp::JumpIfNotEqual => {
+                self.decode_control_flow_statement(op_byte)?
+            }
+            RawOp::EqualCondition | RawOp::InequalCondition => DecodedInstruction::Condition(
+                self.decode_conditional_statement(op_byte == RawOp::EqualCondition)?,
+            ),
+            RawOp::SoftEqualCondition => {
+                DecodedInstruction::SoftCondition(self.decode_conditional_statement(true)?)
+            }
+            RawOp::Abort => DecodedInstruction::UnconditionalAbort,
+            RawOp::JumpLandPad => DecodedInstruction::Label,
+        };
+        Ok(Some(instruction))
+    }
+
+    // Returns the offset of the next instruction to decode.
+    pub fn position(&self) -> usize {
+        self.instructions.len() - self.inst_iter.len()
+    }
+
     fn decode_control_flow_statement(
         &mut self,
         op_byte: RawOp,
     ) -> Result<DecodedInstruction, BytecodeError> {
-        // TODO(fxb/93278): verify offset amount takes you to a jump landing pad.
         let offset_amount = next_u32(&mut self.inst_iter)?;
 
         let condition = match op_byte {
@@ -365,9 +604,11 @@ impl<'a> InstructionDecoder<'a> {
             }
         };
 
-        if self.inst_iter.len() as u32 <= offset_amount {
-            return Err(BytecodeError::InvalidJumpLocation);
+        let target = self.position() + offset_amount as usize;
+        if target >= self.instructions.len() {
+            return Err(BytecodeError::InvalidJumpTarget { offset: target });
         }
+        self.jump_targets.push(target);
 
         Ok(DecodedInstruction::Jump(condition))
     }
@@ -386,7 +627,13 @@ impl<'a> InstructionDecoder<'a> {
         let val_primitive = *next_u8(&mut self.inst_iter)?;
         let val_type = FromPrimitive::from_u8(val_primitive)
             .ok_or(BytecodeError::InvalidValueType(val_primitive))?;
-        let val = next_u32(&mut self.inst_iter)?;
+        // 128-bit values are stored in 16 bytes instead of a u32.
+        let val = match val_type {
+            RawValueType::U128Value => {
+                return Ok(Symbol::U128Value(next_u128(&mut self.inst_iter)?));
+            }
+            _ => next_u32(&mut self.inst_iter)?,
+        };
 
         match val_type {
             RawValueType::NumberValue => Ok(Symbol::NumberValue(val as u64)),
@@ -400,6 +647,7 @@ impl<'a> InstructionDecoder<'a> {
             }
             RawValueType::StringValue => Ok(Symbol::StringValue(self.lookup_symbol_table(val)?)),
             RawValueType::EnumValue => Ok(Symbol::EnumValue(self.lookup_symbol_table(val)?)),
+            RawValueType::U128Value => unreachable!("128-bit values are decoded above"),
         }
     }
 
@@ -414,14 +662,21 @@ impl<'a> InstructionDecoder<'a> {
 #[cfg(test)]
 mod test {
     use super::*;
-
-    const BIND_HEADER: [u8; 8] = [0x42, 0x49, 0x4E, 0x44, 0x02, 0, 0, 0];
+    use crate::test_util::{append_section_header, append_symbol, BIND_HEADER};
 
     const COMPOSITE_NAME_ID_BYTES: u32 = 4;
 
-    fn append_section_header(bytecode: &mut Vec<u8>, magic_num: u32, sz: u32) {
-        bytecode.extend_from_slice(&magic_num.to_be_bytes());
-        bytecode.extend_from_slice(&sz.to_le_bytes());
+    fn program(symbols: &[(u32, &str)], instructions: &[u8]) -> Vec<u8> {
+        let mut symbol_table = vec![];
+        for (key, symbol) in symbols {
+            append_symbol(&mut symbol_table, *key, symbol);
+        }
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, symbol_table.len() as u32);
+        bytecode.extend_from_slice(&symbol_table);
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(instructions);
+        bytecode
     }
 
     fn append_node_header(bytecode: &mut Vec<u8>, node_type: RawNodeType, node_id: u32, sz: u32) {
@@ -684,9 +939,136 @@ mod test {
         bytecode.extend_from_slice(&instructions);
 
         // The last jump would put your instruction pointer past the last element.
+        assert_eq!(
+            Err(BytecodeError::InvalidJumpTarget { offset: 24 }),
+            DecodedRules::new(bytecode)
+        );
+    }
+
+    #[test]
+    fn test_jump_into_instruction() {
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, 0);
+
+        let instructions = [
+            0x10, 0x01, 0, 0, 0, // jump 1 (this lands inside the condition)
+            0x01, 0x01, 0, 0, 0, 0x05, 0x01, 0x10, 0, 0, 0,    // 0x05000000 == 0x10
+            0x20, // jump pad
+        ];
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(&instructions);
+
+        assert_eq!(
+            Err(BytecodeError::InvalidJumpTarget { offset: 6 }),
+            DecodedRules::new(bytecode)
+        );
+    }
+
+    #[test]
+    fn test_jump_onto_non_jump_pad() {
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, 0);
+
+        let instructions = [
+            0x10, 0x01, 0, 0, 0,    // jump 1 (this lands on the abort)
+            0x20, // jump pad
+            0x30, // abort
+        ];
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(&instructions);
+
         assert_eq!(Err(BytecodeError::InvalidJumpLocation), DecodedRules::new(bytecode));
     }
 
+    #[test]
+    fn test_canonicalize() {
+        // Key 1 == "pci", with the symbols in order.
+        let a = program(
+            &[(1, "BIND_PROTOCOL"), (2, "pci")],
+            &[0x01, 0x00, 1, 0, 0, 0, 0x02, 2, 0, 0, 0],
+        );
+        // The same condition with the symbols swapped and an unused one.
+        let b = program(
+            &[(1, "pci"), (2, "unused"), (3, "BIND_PROTOCOL")],
+            &[0x01, 0x00, 3, 0, 0, 0, 0x02, 1, 0, 0, 0],
+        );
+        // Key 1 != "pci".
+        let c = program(
+            &[(1, "BIND_PROTOCOL"), (2, "pci")],
+            &[0x02, 0x00, 1, 0, 0, 0, 0x02, 2, 0, 0, 0],
+        );
+
+        assert_eq!(canonicalize(&a), Ok(a.clone()));
+        assert_eq!(canonicalize(&a), canonicalize(&b));
+        assert_ne!(canonicalize(&a), canonicalize(&c));
+    }
+
+    #[test]
+    fn test_symbol_usage() {
+        // The "pci" entry is used as both a key and a string value.
+        let bytecode = program(
+            &[(1, "BIND_PROTOCOL"), (2, "pci")],
+            &[
+                0x01, 0x00, 1, 0, 0, 0, 0x02, 2, 0, 0, 0, // BIND_PROTOCOL == "pci"
+                0x01, 0x00, 2, 0, 0, 0, 0x02, 2, 0, 0, 0, // "pci" == "pci"
+            ],
+        );
+
+        let usage = symbol_usage(&bytecode).unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[&1], [RawValueType::Key].iter().cloned().collect());
+        assert_eq!(
+            usage[&2],
+            [RawValueType::Key, RawValueType::StringValue].iter().cloned().collect()
+        );
+    }
+
+    #[test]
+    fn test_decoded_program_set() {
+        // Key 1 == "pci".
+        let a = program(
+            &[(1, "BIND_PROTOCOL"), (2, "pci")],
+            &[0x01, 0x00, 1, 0, 0, 0, 0x02, 2, 0, 0, 0],
+        );
+        // Key 1 == "usb", with BIND_PROTOCOL under a different symbol key.
+        let b = program(
+            &[(1, "usb"), (2, "BIND_PROTOCOL")],
+            &[0x01, 0x00, 2, 0, 0, 0, 0x02, 1, 0, 0, 0],
+        );
+
+        let mut programs = DecodedProgramSet::new();
+        let a = programs.decode(a).unwrap();
+        let b = programs.decode(b).unwrap();
+
+        assert_eq!(programs.interned_count(), 3);
+        assert_eq!(&*a.symbol_table[&1], "BIND_PROTOCOL");
+        assert!(Arc::ptr_eq(&a.symbol_table[&1], &b.symbol_table[&2]));
+        assert_eq!(
+            a.decoded_instructions,
+            DecodedBindRules::from_bytecode(program(
+                &[(1, "BIND_PROTOCOL"), (2, "pci")],
+                &[0x01, 0x00, 1, 0, 0, 0, 0x02, 2, 0, 0, 0],
+            ))
+            .unwrap()
+            .decoded_instructions
+        );
+    }
+
+    #[test]
+    fn test_trailing_bytes() {
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, 0);
+
+        let instructions = [
+            0x01, 0x01, 0, 0, 0, 0x05, 0x01, 0x10, 0, 0, 0,    // 0x05000000 == 0x10
+            0x01, // partial condition
+        ];
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(&instructions);
+
+        assert_eq!(Err(BytecodeError::TrailingBytes { count: 1 }), DecodedRules::new(bytecode));
+    }
+
     #[test]
     fn test_valid_bytecode() {
         let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
@@ -1201,8 +1583,8 @@ mod test {
         bytecode.extend_from_slice(&[3, 0, 0, 0]);
         bytecode.extend_from_slice(&node_name_1);
 
-        // There is no value type enum for 0x05.
-        let primary_node_inst = [0x02, 0x01, 0, 0, 0, 0x02, 0x05, 0, 0, 0, 0x10];
+        // There is no value type enum for 0x06.
+        let primary_node_inst = [0x02, 0x01, 0, 0, 0, 0x02, 0x06, 0, 0, 0, 0x10];
 
         let additional_node_inst = [0x01, 0x01, 0, 0, 0, 0x05, 0x01, 0x10, 0, 0x20, 0];
 
@@ -1226,7 +1608,7 @@ mod test {
         );
         bytecode.extend_from_slice(&additional_node_inst);
 
-        assert_eq!(Err(BytecodeError::InvalidValueType(0x05)), DecodedRules::new(bytecode));
+        assert_eq!(Err(BytecodeError::InvalidValueType(0x06)), DecodedRules::new(bytecode));
     }
 
     #[test]
@@ -1248,8 +1630,8 @@ mod test {
 
         let primary_node_inst = [0x01, 0x01, 0, 0, 0, 0x05, 0x01, 0x10, 0, 0x20, 0];
 
-        // There is no value type enum for 0x05.
-        let additional_node_inst = [0x02, 0x01, 0, 0, 0, 0x02, 0x05, 0, 0, 0, 0x10];
+        // There is no value type enum for 0x06.
+        let additional_node_inst = [0x02, 0x01, 0, 0, 0, 0x02, 0x06, 0, 0, 0, 0x10];
 
         let composite_insts_sz = COMPOSITE_NAME_ID_BYTES
             + ((NODE_TYPE_HEADER_SZ * 2) + primary_node_inst.len() + additional_node_inst.len())
@@ -1271,7 +1653,7 @@ mod test {
         );
         bytecode.extend_from_slice(&additional_node_inst);
 
-        assert_eq!(Err(BytecodeError::InvalidValueType(0x05)), DecodedRules::new(bytecode));
+        assert_eq!(Err(BytecodeError::InvalidValueType(0x06)), DecodedRules::new(bytecode));
     }
 
     #[test]
@@ -1695,7 +2077,10 @@ mod test {
         );
         bytecode.extend_from_slice(&additional_node_inst);
 
-        assert_eq!(Err(BytecodeError::InvalidJumpLocation), DecodedRules::new(bytecode));
+        assert_eq!(
+            Err(BytecodeError::InvalidJumpTarget { offset: 20 }),
+            DecodedRules::new(bytecode)
+        );
     }
 
     #[test]
@@ -1741,7 +2126,10 @@ mod test {
         );
         bytecode.extend_from_slice(&additional_node_inst);
 
-        assert_eq!(Err(BytecodeError::InvalidJumpLocation), DecodedRules::new(bytecode));
+        assert_eq!(
+            Err(BytecodeError::InvalidJumpTarget { offset: 20 }),
+            DecodedRules::new(bytecode)
+        );
     }
 
     #[test]
@@ -1862,4 +2250,42 @@ mod test {
             DecodedRules::new(bytecode).unwrap()
         );
     }
+
+    #[test]
+    fn test_decode_instruction_at() {
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, 0);
+
+        let instructions = [
+            0x01, 0x01, 0, 0, 0, 0x05, 0x01, 0x10, 0, 0, 0x10, // 0x05000000 == 0x10001000
+            0x02, 0x01, 0, 0, 0, 0x05, 0x01, 0x01, 0, 0, 0,    // 0x05000000 != 1
+            0x30, // abort
+        ];
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(&instructions);
+
+        assert_eq!(
+            Ok((
+                DecodedInstruction::Condition(DecodedCondition {
+                    is_equal: false,
+                    lhs: Symbol::NumberValue(0x05000000),
+                    rhs: Symbol::NumberValue(1),
+                }),
+                22
+            )),
+            decode_instruction_at(&bytecode, 11)
+        );
+        assert_eq!(
+            Ok((DecodedInstruction::UnconditionalAbort, 23)),
+            decode_instruction_at(&bytecode, 22)
+        );
+        assert_eq!(
+            Err(BytecodeError::InvalidInstructionOffset(5)),
+            decode_instruction_at(&bytecode, 5)
+        );
+        assert_eq!(
+            Err(BytecodeError::InvalidInstructionOffset(23)),
+            decode_instruction_at(&bytecode, 23)
+        );
+    }
 }
@@ -0,0 +1,280 @@
+// Copyright 2022 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use crate::compiler::{get_deprecated_key_identifiers, Symbol};
+use crate::interpreter::common::BytecodeError;
+use crate::interpreter::decode_bind_rules::{
+    DecodedBindRules, DecodedCondition, DecodedInstruction,
+};
+use std::fmt;
+
+fn describe_condition(condition: &DecodedCondition, equal: &str, not_equal: &str) -> String {
+    format!(
+        "{} {} {}",
+        condition.lhs.pretty(),
+        if condition.is_equal { equal } else { not_equal },
+        condition.rhs.pretty()
+    )
+}
+
+fn describe_instruction(instruction: &DecodedInstruction) -> Option<String> {
+    match instruction {
+        DecodedInstruction::Condition(condition) => {
+            Some(describe_condition(condition, "must equal", "must not equal"))
+        }
+        DecodedInstruction::UnconditionalAbort => Some("no device matches".to_string()),
+        DecodedInstruction::Jump(Some(condition)) => Some(format!(
+            "if {} skip ahead",
+            describe_condition(condition, "equals", "does not equal")
+        )),
+        DecodedInstruction::Jump(None) => Some("skip ahead".to_string()),
+        DecodedInstruction::Label => None,
+        DecodedInstruction::SoftCondition(condition) => Some(format!(
+            "preferably {}",
+            describe_condition(condition, "equals", "does not equal")
+        )),
+    }
+}
+
+// Decodes the bind rules bytecode and summarizes its binding requirements in prose, e.g.
+// "key(BIND_PROTOCOL) must equal pci AND key(vendor_id) must not equal 0x0 (0)". Intended for generating
+// driver documentation.
+pub fn describe_requirements(bytecode: &[u8]) -> Result<String, BytecodeError> {
+    let bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    let requirements = bind_rules
+        .decoded_instructions
+        .iter()
+        .filter_map(describe_instruction)
+        .collect::<Vec<String>>();
+
+    if requirements.is_empty() {
+        return Ok("any device matches".to_string());
+    }
+
+    Ok(requirements.join(" AND "))
+}
+
+// Decodes the bind rules bytecode and renders its symbol table as a C header, with one
+// `#define SYM_<key> "<string>"` per symbol in key order.
+pub fn symbol_table_c_header(bytecode: &[u8]) -> Result<String, BytecodeError> {
+    let bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    let mut symbols = bind_rules.symbol_table.into_iter().collect::<Vec<(u32, String)>>();
+    symbols.sort();
+
+    let mut header = "#pragma once\n\n".to_string();
+    for (key, value) in symbols {
+        header.push_str(&format!(
+            "#define SYM_{} \"{}\"\n",
+            key,
+            value.replace('\\', "\\\\").replace('"', "\\\"")
+        ));
+    }
+    Ok(header)
+}
+
+/// A condition that a device must satisfy, e.g. `fuchsia.BIND_PROTOCOL == pci`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionRow {
+    /// The property key, by name when it's known.
+    pub key: String,
+    /// Either "==" or "!=".
+    pub operator: String,
+    pub value: String,
+}
+
+/// The conditions of bind rules made up only of equality and inequality conditions. A device
+/// matches if it satisfies every row. Displays as a grid with one row per condition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionTable {
+    pub rows: Vec<DecisionRow>,
+}
+
+impl fmt::Display for DecisionTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = ("Key", "Operator", "Value");
+        let widths = self.rows.iter().fold(
+            (header.0.len(), header.1.len(), header.2.len()),
+            |(key, operator, value), row| {
+                (
+                    key.max(row.key.len()),
+                    operator.max(row.operator.len()),
+                    value.max(row.value.len()),
+                )
+            },
+        );
+        let rows = std::iter::once(header).chain(
+            self.rows
+                .iter()
+                .map(|row| (row.key.as_str(), row.operator.as_str(), row.value.as_str())),
+        );
+        for (index, (key, operator, value)) in rows.enumerate() {
+            writeln!(
+                f,
+                "| {:key_width$} | {:operator_width$} | {:value_width$} |",
+                key,
+                operator,
+                value,
+                key_width = widths.0,
+                operator_width = widths.1,
+                value_width = widths.2
+            )?;
+            if index == 0 {
+                writeln!(
+                    f,
+                    "|-{}-|-{}-|-{}-|",
+                    "-".repeat(widths.0),
+                    "-".repeat(widths.1),
+                    "-".repeat(widths.2)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Decodes bind rules made up only of equality and inequality conditions into the table of
+// conditions a device must satisfy, for documenting and reviewing drivers. Numeric keys are shown
+// by the name of the deprecated key they stand for when there is one. Returns an error if the
+// bind rules hold any other kind of instruction.
+pub fn to_decision_table(bytecode: &[u8]) -> Result<DecisionTable, BytecodeError> {
+    let bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    let key_names = get_deprecated_key_identifiers();
+    let rows = bind_rules
+        .decoded_instructions
+        .iter()
+        .enumerate()
+        .map(|(index, instruction)| match instruction {
+            DecodedInstruction::Condition(condition) => {
+                let key = match condition.lhs {
+                    Symbol::DeprecatedKey(key) => key_names.get(&key).cloned(),
+                    Symbol::NumberValue(key) if key <= u32::MAX as u64 => {
+                        key_names.get(&(key as u32)).cloned()
+                    }
+                    _ => None,
+                };
+                Ok(DecisionRow {
+                    key: key.unwrap_or_else(|| condition.lhs.pretty()),
+                    operator: if condition.is_equal { "==" } else { "!=" }.to_string(),
+                    value: condition.rhs.pretty(),
+                })
+            }
+            _ => Err(BytecodeError::NotADecisionTable { index }),
+        })
+        .collect::<Result<Vec<_>, BytecodeError>>()?;
+    Ok(DecisionTable { rows })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bytecode_constants::*;
+    use crate::test_util::{append_section_header, append_symbol, BIND_HEADER};
+
+    #[test]
+    fn test_two_conditions() {
+        let mut symbol_table = vec![];
+        append_symbol(&mut symbol_table, 1, "BIND_PROTOCOL");
+        append_symbol(&mut symbol_table, 2, "pci");
+        append_symbol(&mut symbol_table, 3, "vendor_id");
+
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, symbol_table.len() as u32);
+        bytecode.extend_from_slice(&symbol_table);
+
+        let instructions = [
+            0x01, 0x00, 0x01, 0, 0, 0, 0x04, 0x02, 0, 0, 0, // BIND_PROTOCOL == pci
+            0x02, 0x00, 0x03, 0, 0, 0, 0x01, 0, 0, 0, 0, // vendor_id != 0
+        ];
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(&instructions);
+
+        assert_eq!(
+            Ok("key(BIND_PROTOCOL) must equal pci AND key(vendor_id) must not equal 0x0 (0)"
+                .to_string()),
+            describe_requirements(&bytecode)
+        );
+    }
+
+    #[test]
+    fn test_no_instructions() {
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, 0);
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, 0);
+        assert_eq!(Ok("any device matches".to_string()), describe_requirements(&bytecode));
+    }
+
+    #[test]
+    fn test_decision_table() {
+        let mut symbol_table = vec![];
+        append_symbol(&mut symbol_table, 1, "pci");
+        append_symbol(&mut symbol_table, 2, "vendor_id");
+
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, symbol_table.len() as u32);
+        bytecode.extend_from_slice(&symbol_table);
+
+        let instructions = [
+            0x01, 0x01, 0x01, 0, 0, 0, 0x02, 0x01, 0, 0, 0, // fuchsia.BIND_PROTOCOL == "pci"
+            0x02, 0x00, 0x02, 0, 0, 0, 0x01, 0, 0, 0, 0, // vendor_id != 0
+            0x01, 0x01, 0x00, 0x20, 0, 0, 0x03, 0x01, 0, 0, 0, // 0x2000 == true
+        ];
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(&instructions);
+
+        let row = |key: &str, operator: &str, value: &str| DecisionRow {
+            key: key.to_string(),
+            operator: operator.to_string(),
+            value: value.to_string(),
+        };
+        let table = to_decision_table(&bytecode).unwrap();
+        assert_eq!(
+            table.rows,
+            vec![
+                row("fuchsia.BIND_PROTOCOL", "==", "\"pci\""),
+                row("key(vendor_id)", "!=", "0x0 (0)"),
+                row("0x2000 (8192)", "==", "true"),
+            ]
+        );
+        assert_eq!(
+            table.to_string(),
+            "| Key                   | Operator | Value   |\n\
+             |-----------------------|----------|---------|\n\
+             | fuchsia.BIND_PROTOCOL | ==       | \"pci\"   |\n\
+             | key(vendor_id)        | !=       | 0x0 (0) |\n\
+             | 0x2000 (8192)         | ==       | true    |\n"
+        );
+
+        // Jumps and aborts can't be expressed as rows.
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, 0);
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, 1);
+        bytecode.push(0x30);
+        assert_eq!(
+            to_decision_table(&bytecode),
+            Err(BytecodeError::NotADecisionTable { index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_symbol_table_c_header() {
+        let mut symbol_table = vec![];
+        append_symbol(&mut symbol_table, 1, "BIND_PROTOCOL");
+        append_symbol(&mut symbol_table, 2, "pci");
+        append_symbol(&mut symbol_table, 3, "vendor_id");
+
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, symbol_table.len() as u32);
+        bytecode.extend_from_slice(&symbol_table);
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, 0);
+
+        assert_eq!(
+            Ok("#pragma once\n\n\
+                #define SYM_1 \"BIND_PROTOCOL\"\n\
+                #define SYM_2 \"pci\"\n\
+                #define SYM_3 \"vendor_id\"\n"
+                .to_string()),
+            symbol_table_c_header(&bytecode)
+        );
+    }
+}
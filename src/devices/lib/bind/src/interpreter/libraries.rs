@@ -0,0 +1,106 @@
+// Copyright 2022 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use crate::compiler::Symbol;
+use crate::interpreter::common::BytecodeError;
+use crate::interpreter::decode_bind_rules::{DecodedInstruction, DecodedRules, InstructionDecoder};
+use std::collections::HashSet;
+
+// Returns the library of a key, e.g. "fuchsia.pci" for "fuchsia.pci.VENDOR_ID". Deprecated keys
+// and keys without a namespace don't belong to a library.
+fn key_library(symbol: &Symbol) -> Option<String> {
+    match symbol {
+        Symbol::Key(name, _) => name.rsplit_once('.').map(|(library, _)| library.to_string()),
+        _ => None,
+    }
+}
+
+fn instruction_library(instruction: &DecodedInstruction) -> Option<String> {
+    match instruction {
+        DecodedInstruction::Condition(condition)
+        | DecodedInstruction::SoftCondition(condition)
+        | DecodedInstruction::Jump(Some(condition)) => key_library(&condition.lhs),
+        _ => None,
+    }
+}
+
+// Decodes the bytecode and returns the set of bind libraries whose keys are used by the
+// program. This lets tooling determine a driver's bind library dependencies from its
+// bytecode alone. Both normal and composite bind rules are supported.
+pub fn referenced_libraries(bytecode: &[u8]) -> Result<HashSet<String>, BytecodeError> {
+    let instructions = match DecodedRules::new(bytecode.to_vec())? {
+        DecodedRules::Normal(bind_rules) => bind_rules.decoded_instructions,
+        DecodedRules::Composite(bind_rules) => {
+            let mut instructions = vec![];
+            for node in
+                std::iter::once(&bind_rules.primary_node).chain(&bind_rules.additional_nodes)
+            {
+                instructions.extend(
+                    InstructionDecoder::new(&bind_rules.symbol_table, &node.instructions)
+                        .decode()?,
+                );
+            }
+            instructions
+        }
+    };
+
+    Ok(instructions.iter().filter_map(instruction_library).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bytecode_constants::*;
+    use crate::test_util::{append_section_header, append_symbol, BIND_HEADER};
+
+    #[test]
+    fn test_pci_and_usb_libraries() {
+        let mut symbol_table = vec![];
+        append_symbol(&mut symbol_table, 1, "fuchsia.pci.VENDOR_ID");
+        append_symbol(&mut symbol_table, 2, "fuchsia.usb.CLASS");
+        append_symbol(&mut symbol_table, 3, "fuchsia.pci.DEVICE_ID");
+
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, symbol_table.len() as u32);
+        bytecode.extend_from_slice(&symbol_table);
+
+        let instructions = [
+            0x01, 0x00, 0x01, 0, 0, 0, 0x01, 0x86, 0x80, 0,
+            0, // fuchsia.pci.VENDOR_ID == 0x8086
+            0x02, 0x00, 0x02, 0, 0, 0, 0x01, 0x03, 0, 0, 0, // fuchsia.usb.CLASS != 3
+            0x01, 0x00, 0x03, 0, 0, 0, 0x01, 0x01, 0, 0, 0, // fuchsia.pci.DEVICE_ID == 1
+        ];
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(&instructions);
+
+        assert_eq!(
+            Ok(["fuchsia.pci".to_string(), "fuchsia.usb".to_string()].iter().cloned().collect()),
+            referenced_libraries(&bytecode)
+        );
+    }
+
+    #[test]
+    fn test_soft_condition_library() {
+        let mut symbol_table = vec![];
+        append_symbol(&mut symbol_table, 1, "fuchsia.pci.VENDOR_ID");
+        append_symbol(&mut symbol_table, 2, "fuchsia.acpi.HID");
+
+        let mut bytecode: Vec<u8> = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, symbol_table.len() as u32);
+        bytecode.extend_from_slice(&symbol_table);
+
+        let instructions = [
+            0x01, 0x00, 0x01, 0, 0, 0, 0x01, 0x86, 0x80, 0,
+            0, // fuchsia.pci.VENDOR_ID == 0x8086
+            0x03, 0x00, 0x02, 0, 0, 0, 0x01, 0x05, 0, 0, 0, // prefer fuchsia.acpi.HID == 5
+        ];
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(&instructions);
+
+        assert_eq!(
+            Ok(["fuchsia.pci".to_string(), "fuchsia.acpi".to_string()].iter().cloned().collect()),
+            referenced_libraries(&bytecode)
+        );
+    }
+}
@@ -4,12 +4,19 @@
 
 use crate::bytecode_constants::*;
 use crate::compiler::Symbol;
+use crate::errors::UserError;
 use crate::interpreter::common::*;
-use crate::interpreter::decode_bind_rules::DecodedBindRules;
+use crate::interpreter::decode_bind_rules::{
+    DecodedBindRules, DecodedInstruction, InstructionDecoder, InternedBindRules,
+};
 use crate::parser::bind_library;
-use core::hash::Hash;
+use core::cmp::Ordering;
+use core::fmt;
 use num_traits::FromPrimitive;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read};
+use std::iter;
+use thiserror::Error;
 
 #[derive(PartialEq)]
 enum Condition {
@@ -29,89 +36,495 @@ pub enum PropertyKey {
 
 pub type DeviceProperties = HashMap<PropertyKey, Symbol>;
 
+pub type DriverId = String;
+
+// Builds DeviceProperties with the key and value types that match each kind of property.
+#[derive(Debug, Default)]
+pub struct DevicePropertiesBuilder {
+    properties: DeviceProperties,
+}
+
+impl DevicePropertiesBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn number(mut self, key: u64, value: u64) -> Self {
+        self.properties.insert(PropertyKey::NumberKey(key), Symbol::NumberValue(value));
+        self
+    }
+
+    pub fn string(mut self, key: &str, value: &str) -> Self {
+        self.properties.insert(
+            PropertyKey::StringKey(key.to_string()),
+            Symbol::StringValue(value.to_string()),
+        );
+        self
+    }
+
+    pub fn bool_prop(mut self, key: u64, value: bool) -> Self {
+        self.properties.insert(PropertyKey::NumberKey(key), Symbol::BoolValue(value));
+        self
+    }
+
+    pub fn build(self) -> DeviceProperties {
+        self.properties
+    }
+}
+
+// Maps number-based property keys to their names, e.g. the deprecated keys from
+// compiler::get_deprecated_key_identifiers().
+pub type KeyResolver = HashMap<u64, String>;
+
+// Merges layers of device properties into a single set, e.g. platform defaults followed by board
+// overrides. When a key appears in several layers, the value from the last layer is used.
+pub fn merge_device_properties(layers: &[DeviceProperties]) -> DeviceProperties {
+    let mut properties = DeviceProperties::new();
+    for layer in layers {
+        properties.extend(layer.iter().map(|(key, value)| (key.clone(), value.clone())));
+    }
+    properties
+}
+
+// A 64-bit FNV-1a hasher. Unlike DefaultHasher, whose algorithm may change between Rust releases,
+// it gives the same hashes everywhere, so fingerprints can be persisted and compared across builds.
+// Integers are written in little-endian order for the same reason.
+struct Fnv1aHasher(u64);
+
+impl Fnv1aHasher {
+    fn new() -> Self {
+        Fnv1aHasher(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    // Strings are prefixed with their length so that adjacent strings can't run into each other.
+    fn write_str(&mut self, value: &str) {
+        self.write_u64(value.len() as u64);
+        self.write(value.as_bytes());
+    }
+
+    fn write_key(&mut self, key: &PropertyKey) {
+        match key {
+            PropertyKey::NumberKey(key) => {
+                self.write(&[0]);
+                self.write_u64(*key);
+            }
+            PropertyKey::StringKey(key) => {
+                self.write(&[1]);
+                self.write_str(key);
+            }
+        }
+    }
+
+    fn write_symbol(&mut self, symbol: &Symbol) {
+        match symbol {
+            Symbol::DeprecatedKey(key) => {
+                self.write(&[0]);
+                self.write(&key.to_le_bytes());
+            }
+            Symbol::Key(key, value_type) => {
+                self.write(&[1]);
+                self.write_str(key);
+                self.write(&[*value_type as u8]);
+            }
+            Symbol::NumberValue(value) => {
+                self.write(&[2]);
+                self.write_u64(*value);
+            }
+            Symbol::StringValue(value) => {
+                self.write(&[3]);
+                self.write_str(value);
+            }
+            Symbol::BoolValue(value) => self.write(&[4, *value as u8]),
+            Symbol::EnumValue(value) => {
+                self.write(&[5]);
+                self.write_str(value);
+            }
+            Symbol::U128Value(value) => {
+                self.write(&[6]);
+                self.write(&value.to_le_bytes());
+            }
+            Symbol::Any => self.write(&[7]),
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+// Returns a hash of the device properties that can be used as a key for caching match results.
+// The hash only depends on the contents of the properties, not on the order of iteration, and is
+// the same across runs and Rust releases.
+pub fn fingerprint(properties: &DeviceProperties) -> u64 {
+    // Hash each key and value pair on its own, then hash the sorted pair hashes.
+    let mut pair_hashes = properties
+        .iter()
+        .map(|(key, value)| {
+            let mut hasher = Fnv1aHasher::new();
+            hasher.write_key(key);
+            hasher.write_symbol(value);
+            hasher.finish()
+        })
+        .collect::<Vec<u64>>();
+    pair_hashes.sort_unstable();
+
+    let mut hasher = Fnv1aHasher::new();
+    for pair_hash in pair_hashes {
+        hasher.write_u64(pair_hash);
+    }
+    hasher.finish()
+}
+
+// Renders the device properties one per line as `key = value`, for debugging output. Number keys
+// are listed first in numerical order, followed by string keys in alphabetical order. Number keys
+// are shown by name when the resolver knows them.
+pub fn format_properties(props: &DeviceProperties, resolver: Option<&KeyResolver>) -> String {
+    let mut properties = props.iter().collect::<Vec<_>>();
+    properties.sort_by(|(lhs, _), (rhs, _)| match (lhs, rhs) {
+        (PropertyKey::NumberKey(lhs), PropertyKey::NumberKey(rhs)) => lhs.cmp(rhs),
+        (PropertyKey::StringKey(lhs), PropertyKey::StringKey(rhs)) => lhs.cmp(rhs),
+        (PropertyKey::NumberKey(_), PropertyKey::StringKey(_)) => Ordering::Less,
+        (PropertyKey::StringKey(_), PropertyKey::NumberKey(_)) => Ordering::Greater,
+    });
+
+    properties
+        .into_iter()
+        .map(|(key, value)| {
+            let key = match key {
+                PropertyKey::NumberKey(key) => match resolver.and_then(|names| names.get(key)) {
+                    Some(name) => name.clone(),
+                    None => format!("{:#x}", key),
+                },
+                PropertyKey::StringKey(key) => key.clone(),
+            };
+            format!("{} = {}", key, value.pretty())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 pub struct MatchBindData<'a> {
     pub symbol_table: &'a HashMap<u32, String>,
     pub instructions: &'a Vec<u8>,
 }
 
-struct DeviceMatcher<'a> {
+// The outcome of matching bind rules against a device, along with the instruction that rejected
+// the device if it didn't match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchResult {
+    pub matched: bool,
+    // The offset into the instructions of the opcode of the condition or abort that failed.
+    pub rejected_at: Option<usize>,
+    // The failed instruction, e.g. "0x0001 == 0x2 (2)" or "abort".
+    pub rejected_instruction: Option<String>,
+    // The number of soft conditions that held before matching finished. Drivers that match a
+    // device can be ranked by their scores.
+    pub score: u32,
+}
+
+impl MatchResult {
+    fn matched(score: u32) -> Self {
+        MatchResult { matched: true, rejected_at: None, rejected_instruction: None, score }
+    }
+
+    fn rejected(offset: usize, instruction: String, score: u32) -> Self {
+        MatchResult {
+            matched: false,
+            rejected_at: Some(offset),
+            rejected_instruction: Some(instruction),
+            score,
+        }
+    }
+}
+
+// Options for matching bind rules against a device.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MatchOptions {
+    // Whether to evaluate every instruction rather than stopping once a condition fails and
+    // skipping the instructions that jumps pass over, e.g. to measure the cost of the whole
+    // program. The result is the same as when matching stops early: errors raised by the
+    // instructions that matching would have skipped are recorded in `MatchStats` instead of
+    // being returned.
+    pub evaluate_all: bool,
+}
+
+// What matching bind rules with `MatchOptions` did besides deciding the result.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MatchStats {
+    // The number of instructions evaluated.
+    pub evaluated: usize,
+    // The offset and error of each instruction off the path that matching takes that failed to
+    // evaluate. Only instructions evaluated because of `MatchOptions::evaluate_all` end up here.
+    pub skipped_errors: Vec<(usize, BytecodeError)>,
+}
+
+// A condition evaluated while matching bind rules against a device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStep {
+    // The op of the instruction holding the condition.
+    pub op: RawOp,
+    // The property key, e.g. "0x0001" or "fuchsia.BIND_PROTOCOL".
+    pub key: String,
+    // The value the bind rules compare the property against.
+    pub expected: String,
+    // The device's value for the property, or None if the device doesn't have it.
+    pub actual: Option<String>,
+    // Whether the condition held. For a conditional jump, whether the jump was taken.
+    pub passed: bool,
+}
+
+fn describe_condition_key(key: &PropertyKey) -> String {
+    match key {
+        PropertyKey::NumberKey(key) => format!("{:#06x}", key),
+        PropertyKey::StringKey(key) => key.clone(),
+    }
+}
+
+// What a traced match records on the way to its result.
+#[derive(Default)]
+struct Trace {
+    steps: Vec<TraceStep>,
+    // The indices of the symbol table entries that were looked up.
+    used_symbols: BTreeSet<u32>,
+}
+
+struct DeviceMatcher<'a, S = String> {
     properties: &'a DeviceProperties,
-    symbol_table: &'a HashMap<u32, String>,
+    symbol_table: &'a HashMap<u32, S>,
     iter: BytecodeIter<'a>,
+    // The trace, if the match is being traced.
+    trace: Option<Trace>,
 }
 
-impl<'a> DeviceMatcher<'a> {
-    pub fn match_bind(mut self) -> Result<bool, BytecodeError> {
+impl<'a, S: AsRef<str>> DeviceMatcher<'a, S> {
+    pub fn new(
+        properties: &'a DeviceProperties,
+        symbol_table: &'a HashMap<u32, S>,
+        instructions: &'a [u8],
+    ) -> Self {
+        DeviceMatcher { properties, symbol_table, iter: instructions.iter(), trace: None }
+    }
+
+    // Makes the matcher record a trace of the match as it runs.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Trace::default());
+        self
+    }
+
+    pub fn match_bind(self) -> Result<bool, BytecodeError> {
+        Ok(self.match_bind_verbose()?.matched)
+    }
+
+    pub fn match_bind_verbose(mut self) -> Result<MatchResult, BytecodeError> {
+        Ok(self.run(&MatchOptions::default())?.0)
+    }
+
+    pub fn match_bind_with_options(
+        mut self,
+        options: &MatchOptions,
+    ) -> Result<(MatchResult, MatchStats), BytecodeError> {
+        self.run(options)
+    }
+
+    pub fn match_bind_traced(self) -> Result<(bool, Vec<TraceStep>), BytecodeError> {
+        let (matched, trace) = self.run_traced()?;
+        Ok((matched, trace.steps))
+    }
+
+    pub fn match_bind_used_symbols(self) -> Result<(bool, BTreeSet<u32>), BytecodeError> {
+        let (matched, trace) = self.run_traced()?;
+        Ok((matched, trace.used_symbols))
+    }
+
+    fn run_traced(self) -> Result<(bool, Trace), BytecodeError> {
+        let mut matcher = self.with_trace();
+        let (result, _) = matcher.run(&MatchOptions::default())?;
+        Ok((result.matched, matcher.trace.unwrap_or_default()))
+    }
+
+    // Returns the result of the match along with what evaluating the instructions did.
+    fn run(&mut self, options: &MatchOptions) -> Result<(MatchResult, MatchStats), BytecodeError> {
+        let instructions = self.iter.as_slice();
+        let len = instructions.len();
+        let mut score = 0;
+        let mut stats = MatchStats::default();
+        // When evaluating every instruction, the result once a condition fails, and the offset
+        // of the label that a taken jump lands on. Until matching reaches that label, the
+        // instructions it would have skipped are evaluated without affecting the result.
+        let mut result = None;
+        let mut jump_target = None;
         while let Some(byte) = self.iter.next() {
-            let op_byte = FromPrimitive::from_u8(*byte).ok_or(BytecodeError::InvalidOp(*byte))?;
-            match op_byte {
-                RawOp::EqualCondition | RawOp::InequalCondition => {
-                    if !self.evaluate_condition_inst(op_byte)? {
-                        return Ok(false);
+            // The offset of the opcode that was just read.
+            let offset = len - self.iter.len() - 1;
+            if jump_target == Some(offset) {
+                jump_target = None;
+            }
+            let live = result.is_none() && jump_target.is_none();
+            // Instructions off the path that matching takes aren't traced.
+            let trace = if live { None } else { self.trace.take() };
+            stats.evaluated += 1;
+
+            let step = (|| -> Result<(), BytecodeError> {
+                let op_byte =
+                    FromPrimitive::from_u8(*byte).ok_or(BytecodeError::InvalidOp(*byte))?;
+                match op_byte {
+                    RawOp::EqualCondition | RawOp::InequalCondition => {
+                        let (condition, op) = if op_byte == RawOp::EqualCondition {
+                            (Condition::Equal, "==")
+                        } else {
+                            (Condition::Inequal, "!=")
+                        };
+                        let (key, value) = self.read_condition_values()?;
+                        let passed = self.evaluate_values(condition, &key, &value)?;
+                        self.record(op_byte, &key, &value, passed);
+                        if live && !passed {
+                            result = Some(MatchResult::rejected(
+                                offset,
+                                format!(
+                                    "{} {} {}",
+                                    describe_condition_key(&key),
+                                    op,
+                                    value.pretty()
+                                ),
+                                score,
+                            ));
+                        }
+                    }
+                    RawOp::Abort => {
+                        if live {
+                            result =
+                                Some(MatchResult::rejected(offset, "abort".to_string(), score));
+                        }
+                    }
+                    RawOp::SoftEqualCondition => {
+                        let (key, value) = self.read_condition_values()?;
+                        let passed = self.evaluate_values(Condition::Equal, &key, &value)?;
+                        self.record(op_byte, &key, &value, passed);
+                        if live && passed {
+                            score += 1;
+                        }
+                    }
+                    RawOp::UnconditionalJump | RawOp::JumpIfEqual | RawOp::JumpIfNotEqual => {
+                        let condition = match op_byte {
+                            RawOp::JumpIfEqual => Condition::Equal,
+                            RawOp::JumpIfNotEqual => Condition::Inequal,
+                            _ => Condition::Unconditional,
+                        };
+                        match self.evaluate_jump_inst(op_byte, condition)? {
+                            Some(target) if live && options.evaluate_all => {
+                                jump_target = Some(len - self.iter.len() + target);
+                            }
+                            Some(target) if live => {
+                                // Skip to the label, which is consumed along with the bytes before it.
+                                self.iter.nth(target);
+                            }
+                            _ => (),
+                        }
+                    }
+                    RawOp::JumpLandPad => {
+                        // No-op.
                     }
                 }
-                RawOp::Abort => {
-                    return Ok(false);
+                Ok(())
+            })();
+
+            if trace.is_some() {
+                self.trace = trace;
+            }
+            if let Err(err) = step {
+                if live {
+                    return Err(err);
                 }
-                RawOp::UnconditionalJump => self.evaluate_jump_inst(Condition::Unconditional)?,
-                RawOp::JumpIfEqual => self.evaluate_jump_inst(Condition::Equal)?,
-                RawOp::JumpIfNotEqual => self.evaluate_jump_inst(Condition::Inequal)?,
-                RawOp::JumpLandPad => {
-                    // No-op.
+                // Instructions that matching doesn't reach can't change the result, so their
+                // errors are only recorded. The instructions after a bad one may not decode, so
+                // resume at the label that the skipping jump lands on, or stop if the result is
+                // already decided.
+                stats.skipped_errors.push((offset, err));
+                match jump_target.take() {
+                    Some(target) if result.is_none() => self.iter = instructions[target..].iter(),
+                    _ => break,
                 }
-            };
+                continue;
+            }
+            if result.is_some() && !options.evaluate_all {
+                break;
+            }
         }
 
-        Ok(true)
-    }
-
-    // Evaluates a conditional instruction and returns false if the condition failed.
-    fn evaluate_condition_inst(&mut self, op: RawOp) -> Result<bool, BytecodeError> {
-        let condition = match op {
-            RawOp::EqualCondition => Condition::Equal,
-            RawOp::InequalCondition => Condition::Inequal,
-            _ => panic!(
-                "evaluate_condition_inst() should only be called for Equal or Inequal instructions"
-            ),
-        };
-
-        Ok(self.read_and_evaluate_values(condition)?)
+        Ok((result.unwrap_or_else(|| MatchResult::matched(score)), stats))
     }
 
-    fn evaluate_jump_inst(&mut self, condition: Condition) -> Result<(), BytecodeError> {
-        let offset = next_u32(&mut self.iter)?;
-        if condition != Condition::Unconditional && !self.read_and_evaluate_values(condition)? {
-            return Ok(());
-        }
-
-        // Skip through the bytes by the amount in the offset.
-        for _ in 0..offset {
-            next_u8(&mut self.iter)?;
+    // Reads a jump instruction and returns how many bytes after it the label it jumps to is, or
+    // None if the jump isn't taken.
+    fn evaluate_jump_inst(
+        &mut self,
+        op: RawOp,
+        condition: Condition,
+    ) -> Result<Option<usize>, BytecodeError> {
+        let offset = next_u32(&mut self.iter)? as usize;
+        if condition != Condition::Unconditional && !self.read_and_evaluate_values(op, condition)? {
+            return Ok(None);
         }
 
-        // Verify that the next instruction is a jump pad.
-        if *next_u8(&mut self.iter)? != RawOp::JumpLandPad as u8 {
+        // The target must be a jump pad within the instructions.
+        if self.iter.as_slice().get(offset) != Some(&(RawOp::JumpLandPad as u8)) {
             return Err(BytecodeError::InvalidJumpLocation);
         }
 
-        Ok(())
+        Ok(Some(offset))
     }
 
     // Read in two values and evaluate them based on the given condition.
-    fn read_and_evaluate_values(&mut self, condition: Condition) -> Result<bool, BytecodeError> {
-        let property_key = match self.read_next_value()? {
-            Symbol::NumberValue(key) => PropertyKey::NumberKey(key),
-            Symbol::StringValue(key) => PropertyKey::StringKey(key),
-            Symbol::Key(key, _) => PropertyKey::StringKey(key),
-            _ => {
-                return Err(BytecodeError::InvalidKeyType);
-            }
-        };
+    fn read_and_evaluate_values(
+        &mut self,
+        op: RawOp,
+        condition: Condition,
+    ) -> Result<bool, BytecodeError> {
+        let (key, bind_value) = self.read_condition_values()?;
+        let passed = self.evaluate_values(condition, &key, &bind_value)?;
+        self.record(op, &key, &bind_value, passed);
+        Ok(passed)
+    }
+
+    // Add an evaluated condition to the trace if one is being recorded.
+    fn record(&mut self, op: RawOp, key: &PropertyKey, bind_value: &Symbol, passed: bool) {
+        if let Some(trace) = &mut self.trace {
+            trace.steps.push(TraceStep {
+                op,
+                key: describe_condition_key(key),
+                expected: bind_value.pretty(),
+                actual: self.properties.get(key).map(Symbol::pretty),
+                passed,
+            });
+        }
+    }
+
+    // Read in the key and the value of a condition.
+    fn read_condition_values(&mut self) -> Result<(PropertyKey, Symbol), BytecodeError> {
+        let property_key = to_property_key(&self.read_next_value()?)?;
+        Ok((property_key, self.read_next_value()?))
+    }
 
-        let bind_value = self.read_next_value()?;
-        match self.properties.get(&property_key) {
+    fn evaluate_values(
+        &self,
+        condition: Condition,
+        property_key: &PropertyKey,
+        bind_value: &Symbol,
+    ) -> Result<bool, BytecodeError> {
+        match self.properties.get(property_key) {
             None => Ok(condition == Condition::Inequal),
-            Some(device_value) => compare_symbols(condition, device_value, &bind_value),
+            Some(device_value) => compare_symbols(condition, device_value, bind_value),
         }
     }
 
@@ -122,7 +535,12 @@ impl<'a> DeviceMatcher<'a> {
         let value_type = FromPrimitive::from_u8(value_type)
             .ok_or(BytecodeError::InvalidValueType(value_type))?;
 
-        let value = next_u32(&mut self.iter)?;
+        // 128-bit values are stored in 16 bytes instead of a u32.
+        let value = match value_type {
+            RawValueType::U128Value => return Ok(Symbol::U128Value(next_u128(&mut self.iter)?)),
+            _ => next_u32(&mut self.iter)?,
+        };
+
         match value_type {
             RawValueType::NumberValue => Ok(Symbol::NumberValue(value as u64)),
             RawValueType::Key => {
@@ -137,14 +555,18 @@ impl<'a> DeviceMatcher<'a> {
                 _ => Err(BytecodeError::InvalidBoolValue(value)),
             },
             RawValueType::EnumValue => Ok(Symbol::EnumValue(self.lookup_symbol_table(value)?)),
+            RawValueType::U128Value => unreachable!("128-bit values are read above"),
         }
     }
 
-    fn lookup_symbol_table(&self, key: u32) -> Result<String, BytecodeError> {
+    fn lookup_symbol_table(&mut self, key: u32) -> Result<String, BytecodeError> {
+        if let Some(trace) = &mut self.trace {
+            trace.used_symbols.insert(key);
+        }
         self.symbol_table
             .get(&key)
             .ok_or(BytecodeError::MissingEntryInSymbolTable(key))
-            .map(|val| val.to_string())
+            .map(|val| val.as_ref().to_string())
     }
 }
 
@@ -153,13 +575,22 @@ fn compare_symbols(
     lhs: &Symbol,
     rhs: &Symbol,
 ) -> Result<bool, BytecodeError> {
-    if std::mem::discriminant(lhs) != std::mem::discriminant(rhs) {
-        return Err(BytecodeError::MismatchValueTypes);
+    // A wildcard device value stands in for whichever value the condition needs.
+    if *lhs == Symbol::Any {
+        return Ok(true);
     }
 
+    // Number and 128-bit values may be compared against each other, so only those are widened.
+    let equal = match (lhs, rhs) {
+        (Symbol::NumberValue(lhs), Symbol::U128Value(rhs)) => *lhs as u128 == *rhs,
+        (Symbol::U128Value(lhs), Symbol::NumberValue(rhs)) => *lhs == *rhs as u128,
+        (lhs, rhs) if std::mem::discriminant(lhs) == std::mem::discriminant(rhs) => lhs == rhs,
+        _ => return Err(BytecodeError::MismatchValueTypes),
+    };
+
     Ok(match condition {
-        Condition::Equal => lhs == rhs,
-        Condition::Inequal => lhs != rhs,
+        Condition::Equal => equal,
+        Condition::Inequal => !equal,
         Condition::Unconditional => {
             panic!("This function shouldn't be called for Unconditional.")
         }
@@ -173,11 +604,35 @@ pub fn match_bytecode(
     properties: &DeviceProperties,
 ) -> Result<bool, BytecodeError> {
     let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode)?;
-    let matcher = DeviceMatcher {
-        properties: &properties,
-        symbol_table: &decoded_bind_rules.symbol_table,
-        iter: decoded_bind_rules.instructions.iter(),
-    };
+    let matcher = DeviceMatcher::new(
+        properties,
+        &decoded_bind_rules.symbol_table,
+        &decoded_bind_rules.instructions,
+    );
+    matcher.match_bind()
+}
+
+// Like match_bytecode(), but also reports which instruction rejected the device.
+pub fn match_bytecode_verbose(
+    bytecode: Vec<u8>,
+    properties: &DeviceProperties,
+) -> Result<MatchResult, BytecodeError> {
+    let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode)?;
+    let matcher = DeviceMatcher::new(
+        properties,
+        &decoded_bind_rules.symbol_table,
+        &decoded_bind_rules.instructions,
+    );
+    matcher.match_bind_verbose()
+}
+
+// Return true if the bind rules decoded by a DecodedProgramSet match the device properties.
+pub fn match_interned(
+    bind_rules: &InternedBindRules,
+    properties: &DeviceProperties,
+) -> Result<bool, BytecodeError> {
+    let matcher =
+        DeviceMatcher::new(properties, &bind_rules.symbol_table, &bind_rules.instructions);
     matcher.match_bind()
 }
 
@@ -186,17 +641,457 @@ pub fn match_bind(
     bind_data: MatchBindData,
     properties: &DeviceProperties,
 ) -> Result<bool, BytecodeError> {
-    let matcher = DeviceMatcher {
-        properties: &properties,
-        symbol_table: &bind_data.symbol_table,
-        iter: bind_data.instructions.iter(),
-    };
+    let matcher = DeviceMatcher::new(properties, bind_data.symbol_table, bind_data.instructions);
     matcher.match_bind()
 }
 
+// Like match_bind(), but also returns every condition evaluated on the way to the result, in
+// order, with the device's value for each property.
+pub fn match_bind_traced(
+    bind_data: MatchBindData,
+    properties: &DeviceProperties,
+) -> Result<(bool, Vec<TraceStep>), BytecodeError> {
+    let matcher = DeviceMatcher::new(properties, bind_data.symbol_table, bind_data.instructions);
+    matcher.match_bind_traced()
+}
+
+// Like match_bind(), but also returns the indices of the symbol table entries that were looked up
+// on the way to the result, e.g. to see which strings mattered for the device.
+pub fn match_bind_used_symbols(
+    bind_data: MatchBindData,
+    properties: &DeviceProperties,
+) -> Result<(bool, BTreeSet<u32>), BytecodeError> {
+    let matcher = DeviceMatcher::new(properties, bind_data.symbol_table, bind_data.instructions);
+    matcher.match_bind_used_symbols()
+}
+
+// Like match_bind(), but also reports which instruction rejected the device and returns the
+// number of instructions evaluated. With MatchOptions::evaluate_all, that's every instruction in
+// the program, which gives its worst-case cost, unless one off the matching path fails to decode.
+pub fn match_bind_with_options(
+    bind_data: MatchBindData,
+    properties: &DeviceProperties,
+    options: &MatchOptions,
+) -> Result<(MatchResult, MatchStats), BytecodeError> {
+    let matcher = DeviceMatcher::new(properties, bind_data.symbol_table, bind_data.instructions);
+    matcher.match_bind_with_options(options)
+}
+
+// Like match_bind(), but first verifies the whole instruction stream, including any instructions
+// that matching would skip after an abort or a jump. Returns an error if any instruction is
+// malformed, even if matching alone would have rejected the device before reaching it.
+pub fn match_bind_strict(
+    bind_data: MatchBindData,
+    properties: &DeviceProperties,
+) -> Result<bool, BytecodeError> {
+    InstructionDecoder::new(bind_data.symbol_table, bind_data.instructions).decode()?;
+    match_bind(bind_data, properties)
+}
+
+// Simulates the driver manager's choice among several drivers that could bind to a device. Each
+// driver's bytecode is matched against the device properties, and the matching driver with the
+// most conditions is selected since it describes the device most specifically. Ties go to the
+// driver listed first. Returns None if no driver matches.
+pub fn select_driver(
+    device: &DeviceProperties,
+    drivers: &[(DriverId, Vec<u8>)],
+) -> Result<Option<DriverId>, BytecodeError> {
+    let mut selected: Option<(&DriverId, usize)> = None;
+    for (id, bytecode) in drivers {
+        let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode.clone())?;
+        let matcher = DeviceMatcher::new(
+            device,
+            &decoded_bind_rules.symbol_table,
+            &decoded_bind_rules.instructions,
+        );
+        if !matcher.match_bind()? {
+            continue;
+        }
+
+        let condition_count = decoded_bind_rules
+            .decoded_instructions
+            .iter()
+            .filter(|inst| {
+                matches!(inst, DecodedInstruction::Condition(_) | DecodedInstruction::Jump(Some(_)))
+            })
+            .count();
+        match selected {
+            Some((_, count)) if count >= condition_count => {}
+            _ => selected = Some((id, condition_count)),
+        }
+    }
+    Ok(selected.map(|(id, _)| id.clone()))
+}
+
+// Returns a score for how specific the bind rules are, for preferring the more specific of several
+// matching drivers. Equality conditions pin a property to one value, so they score 2. Inequality
+// conditions and the conditions of if blocks, which only hold on some paths, score 1. Soft
+// conditions don't restrict what matches, so they score nothing.
+pub fn selectivity(bytecode: &[u8]) -> Result<u32, BytecodeError> {
+    let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    Ok(decoded_bind_rules
+        .decoded_instructions
+        .iter()
+        .map(|instruction| match instruction {
+            DecodedInstruction::Condition(condition) if condition.is_equal => 2,
+            DecodedInstruction::Condition(_) | DecodedInstruction::Jump(Some(_)) => 1,
+            _ => 0,
+        })
+        .sum())
+}
+
+// Returns whether the bind rules match every device, which usually means a driver was shipped
+// without its conditions. Only conditions and aborts can reject a device. Jump conditions just
+// choose a path and soft conditions only raise the score.
+pub fn is_match_all(bytecode: &[u8]) -> Result<bool, BytecodeError> {
+    let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    Ok(!decoded_bind_rules.decoded_instructions.iter().any(|instruction| {
+        matches!(
+            instruction,
+            DecodedInstruction::Condition(_) | DecodedInstruction::UnconditionalAbort
+        )
+    }))
+}
+
+// Returns the IDs of every program that matches the device properties, in the order the programs
+// are listed. Each program is decoded once and all of them are matched against the same
+// properties.
+pub fn match_many(
+    programs: &[(DriverId, Vec<u8>)],
+    properties: &DeviceProperties,
+) -> Result<Vec<DriverId>, BytecodeError> {
+    let mut matches = vec![];
+    for (id, bytecode) in programs {
+        let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode.clone())?;
+        let matcher = DeviceMatcher::new(
+            properties,
+            &decoded_bind_rules.symbol_table,
+            &decoded_bind_rules.instructions,
+        );
+        if matcher.match_bind()? {
+            matches.push(id.clone());
+        }
+    }
+    Ok(matches)
+}
+
+// Returns the indices of the devices that each driver binds, for reporting coverage of a driver
+// set over a device corpus. Every driver has an entry, which is empty if it binds none of the
+// devices. Each driver's bytecode is decoded once and matched against all of the devices.
+pub fn drivers_matching_any(
+    drivers: &[(DriverId, Vec<u8>)],
+    devices: &[DeviceProperties],
+) -> Result<HashMap<DriverId, Vec<usize>>, BytecodeError> {
+    let mut matches = HashMap::new();
+    for (id, bytecode) in drivers {
+        let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode.clone())?;
+        let mut bound = vec![];
+        for (index, properties) in devices.iter().enumerate() {
+            let matcher = DeviceMatcher::new(
+                properties,
+                &decoded_bind_rules.symbol_table,
+                &decoded_bind_rules.instructions,
+            );
+            if matcher.match_bind()? {
+                bound.push(index);
+            }
+        }
+        matches.insert(id.clone(), bound);
+    }
+    Ok(matches)
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum DeviceStreamError {
+    Bytecode(BytecodeError),
+    Read(String),
+    InvalidDevice(String),
+}
+
+impl fmt::Display for DeviceStreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", UserError::from(self.clone()))
+    }
+}
+
+// Matches the bytecode against each device read from a stream of newline-delimited JSON objects,
+// yielding a result per device as it's read so that memory use doesn't grow with the number of
+// devices. Each object maps property keys to their values. Keys that are integers, in decimal or
+// prefixed with 0x, are number keys and other keys are string keys. Values may be numbers, strings
+// or bools. Blank lines are skipped. If the bytecode can't be decoded, that error is the only item.
+pub fn match_stream<R: Read>(
+    bytecode: &[u8],
+    devices: R,
+) -> impl Iterator<Item = Result<bool, DeviceStreamError>> {
+    let mut decoded_bind_rules = Some(DecodedBindRules::from_bytecode(bytecode.to_vec()));
+    let mut lines = BufReader::new(devices).lines();
+    iter::from_fn(move || {
+        let bind_rules = match decoded_bind_rules.as_ref()? {
+            Ok(bind_rules) => bind_rules,
+            Err(error) => {
+                let error = DeviceStreamError::Bytecode(error.clone());
+                decoded_bind_rules = None;
+                return Some(Err(error));
+            }
+        };
+        let line = loop {
+            match lines.next()? {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => break line,
+                Err(error) => return Some(Err(DeviceStreamError::Read(error.to_string()))),
+            }
+        };
+        Some(parse_device_json(&line).and_then(|properties| {
+            let matcher =
+                DeviceMatcher::new(&properties, &bind_rules.symbol_table, &bind_rules.instructions);
+            matcher.match_bind().map_err(DeviceStreamError::Bytecode)
+        }))
+    })
+}
+
+fn parse_device_json(line: &str) -> Result<DeviceProperties, DeviceStreamError> {
+    let invalid = |reason: String| DeviceStreamError::InvalidDevice(reason);
+    let object = match serde_json::from_str(line).map_err(|e| invalid(e.to_string()))? {
+        serde_json::Value::Object(object) => object,
+        _ => return Err(invalid(format!("{} is not an object", line))),
+    };
+
+    let mut properties = DeviceProperties::new();
+    for (key, value) in object {
+        let parsed_key = match key.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => key.parse::<u64>().ok(),
+        };
+        let value = match value {
+            serde_json::Value::Number(number) => Symbol::NumberValue(
+                number.as_u64().ok_or_else(|| invalid(format!("{} is not a u64", number)))?,
+            ),
+            serde_json::Value::String(string) => Symbol::StringValue(string),
+            serde_json::Value::Bool(boolean) => Symbol::BoolValue(boolean),
+            other => return Err(invalid(format!("{} has unsupported value {}", key, other))),
+        };
+        let key = match parsed_key {
+            Some(number) => PropertyKey::NumberKey(number),
+            None => PropertyKey::StringKey(key),
+        };
+        properties.insert(key, value);
+    }
+    Ok(properties)
+}
+
+// Builds the smallest set of device properties that the bytecode matches, for generating positive
+// test cases. Each equality condition contributes one property. Properties left unset fail
+// equality conditions and pass inequality conditions, so nothing else is needed. Returns None
+// if the conditions contradict each other or the program aborts. Conditions on jumps aren't
+// explored, so for programs with if blocks None may also mean the properties required by the
+// unconditional statements alone don't match.
+pub fn minimal_satisfying_properties(
+    bytecode: &[u8],
+) -> Result<Option<DeviceProperties>, BytecodeError> {
+    let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    let mut properties = DeviceProperties::new();
+    for instruction in &decoded_bind_rules.decoded_instructions {
+        let condition = match instruction {
+            DecodedInstruction::Condition(condition) => condition,
+            DecodedInstruction::UnconditionalAbort => return Ok(None),
+            _ => continue,
+        };
+        if !condition.is_equal {
+            continue;
+        }
+        let key = to_property_key(&condition.lhs)?;
+        match properties.get(&key) {
+            Some(value) if *value != condition.rhs => return Ok(None),
+            Some(_) => {}
+            None => {
+                properties.insert(key, condition.rhs.clone());
+            }
+        }
+    }
+
+    // Inequality conditions on the required properties may still fail.
+    let matcher = DeviceMatcher::new(
+        &properties,
+        &decoded_bind_rules.symbol_table,
+        &decoded_bind_rules.instructions,
+    );
+    if matcher.match_bind()? {
+        Ok(Some(properties))
+    } else {
+        Ok(None)
+    }
+}
+
+fn to_property_key(symbol: &Symbol) -> Result<PropertyKey, BytecodeError> {
+    match symbol {
+        Symbol::NumberValue(key) => Ok(PropertyKey::NumberKey(*key)),
+        Symbol::StringValue(key) | Symbol::Key(key, _) => Ok(PropertyKey::StringKey(key.clone())),
+        _ => Err(BytecodeError::InvalidKeyType),
+    }
+}
+
+// Returns each property key tested by the bind rules, in the order they're first tested, along
+// with the value the key must equal if the rules require one. Keys only tested for inequality,
+// in jump conditions or in soft conditions have no required value. Intended for pre-filtering
+// candidate drivers before running the full matcher.
+pub fn index_keys(bytecode: &[u8]) -> Result<Vec<(PropertyKey, Option<Symbol>)>, BytecodeError> {
+    let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    let mut keys: Vec<(PropertyKey, Option<Symbol>)> = vec![];
+    for instruction in &decoded_bind_rules.decoded_instructions {
+        let (condition, required) = match instruction {
+            DecodedInstruction::Condition(condition) => (condition, condition.is_equal),
+            DecodedInstruction::Jump(Some(condition))
+            | DecodedInstruction::SoftCondition(condition) => (condition, false),
+            _ => continue,
+        };
+        let key = to_property_key(&condition.lhs)?;
+        let required_value = if required { Some(condition.rhs.clone()) } else { None };
+        match keys.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, value)) => {
+                if value.is_none() {
+                    *value = required_value;
+                }
+            }
+            None => keys.push((key, required_value)),
+        }
+    }
+    Ok(keys)
+}
+
+// Returns each pair of property keys that are always tested together across the given bind rules,
+// along with the number of bind rules testing them. A pair is reported when every bind rules
+// testing either key also tests the other. Intended for choosing compound indexes.
+pub fn co_occurring_keys(
+    bytecodes: &[Vec<u8>],
+) -> Result<Vec<(PropertyKey, PropertyKey, usize)>, BytecodeError> {
+    // Keys in the order they're first tested, with the number of bind rules testing each.
+    let mut keys: Vec<(PropertyKey, usize)> = vec![];
+    let mut pair_counts: HashMap<(usize, usize), usize> = HashMap::new();
+    for bytecode in bytecodes {
+        let mut tested = vec![];
+        for (key, _) in index_keys(bytecode)? {
+            let position = match keys.iter().position(|(existing, _)| *existing == key) {
+                Some(position) => position,
+                None => {
+                    keys.push((key, 0));
+                    keys.len() - 1
+                }
+            };
+            keys[position].1 += 1;
+            tested.push(position);
+        }
+        tested.sort_unstable();
+        for (i, a) in tested.iter().enumerate() {
+            for b in &tested[i + 1..] {
+                *pair_counts.entry((*a, *b)).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut pairs = pair_counts
+        .into_iter()
+        .filter(|((a, b), count)| keys[*a].1 == *count && keys[*b].1 == *count)
+        .collect::<Vec<_>>();
+    pairs.sort_unstable();
+    Ok(pairs
+        .into_iter()
+        .map(|((a, b), count)| (keys[a].0.clone(), keys[b].0.clone(), count))
+        .collect())
+}
+
+// Returns the index among the decoded instructions of the first condition or jump that reads
+// `key`, or None if the bind rules never read it.
+pub fn first_use_of(bytecode: &[u8], key: &PropertyKey) -> Result<Option<usize>, BytecodeError> {
+    let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    for (index, instruction) in decoded_bind_rules.decoded_instructions.iter().enumerate() {
+        let condition = match instruction {
+            DecodedInstruction::Condition(condition)
+            | DecodedInstruction::Jump(Some(condition))
+            | DecodedInstruction::SoftCondition(condition) => condition,
+            _ => continue,
+        };
+        if to_property_key(&condition.lhs)? == *key {
+            return Ok(Some(index));
+        }
+    }
+    Ok(None)
+}
+
+// Returns the values the bind rules require for each key, or None if the rules contain control
+// flow and so the conditions aren't all unconditionally required.
+fn required_values(bytecode: &[u8]) -> Result<Option<DeviceProperties>, BytecodeError> {
+    let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    let mut required = DeviceProperties::new();
+    for instruction in &decoded_bind_rules.decoded_instructions {
+        match instruction {
+            DecodedInstruction::Condition(condition) if condition.is_equal => {
+                required.entry(to_property_key(&condition.lhs)?).or_insert(condition.rhs.clone());
+            }
+            DecodedInstruction::Jump(_) | DecodedInstruction::Label => return Ok(None),
+            _ => {}
+        }
+    }
+    Ok(Some(required))
+}
+
+// Matcher features that bind rules may need beyond plain conditions and aborts. Older driver
+// managers may not support all of them.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum MatcherFeature {
+    Jumps,
+    SoftConditions,
+    U128Values,
+}
+
+pub type FeatureSet = HashSet<MatcherFeature>;
+
+// Returns the matcher features the bind rules need, so that a caller can check that a matcher
+// supports them before loading the rules.
+pub fn requires_matcher_features(bytecode: &[u8]) -> Result<FeatureSet, BytecodeError> {
+    let decoded_bind_rules = DecodedBindRules::from_bytecode(bytecode.to_vec())?;
+    let mut features = FeatureSet::new();
+    for instruction in &decoded_bind_rules.decoded_instructions {
+        let condition = match instruction {
+            DecodedInstruction::Condition(condition) => condition,
+            DecodedInstruction::Jump(condition) => {
+                features.insert(MatcherFeature::Jumps);
+                match condition {
+                    Some(condition) => condition,
+                    None => continue,
+                }
+            }
+            DecodedInstruction::Label => {
+                features.insert(MatcherFeature::Jumps);
+                continue;
+            }
+            DecodedInstruction::SoftCondition(condition) => {
+                features.insert(MatcherFeature::SoftConditions);
+                condition
+            }
+            DecodedInstruction::UnconditionalAbort => continue,
+        };
+        if let Symbol::U128Value(_) = condition.rhs {
+            features.insert(MatcherFeature::U128Values);
+        }
+    }
+    Ok(features)
+}
+
+// Returns true if no device can match both bind rules because they require different values for
+// some shared key. This is conservative: rules containing control flow are never reported as
+// mutually exclusive.
+pub fn mutually_exclusive(a: &[u8], b: &[u8]) -> Result<bool, BytecodeError> {
+    let (a, b) = match (required_values(a)?, required_values(b)?) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Ok(false),
+    };
+    Ok(a.iter().any(|(key, value)| b.get(key).map_or(false, |other| other != value)))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::test_util::{append_section_header, BIND_HEADER};
 
     // Constants representing the number of bytes in an operand and value.
     const OP_BYTES: u32 = 1;
@@ -282,11 +1177,11 @@ mod test {
         bind_rules: DecodedBindRules,
         device_properties: &DeviceProperties,
     ) {
-        let matcher = DeviceMatcher {
-            properties: device_properties,
-            symbol_table: &bind_rules.symbol_table,
-            iter: bind_rules.instructions.iter(),
-        };
+        let matcher = DeviceMatcher::new(
+            device_properties,
+            &bind_rules.symbol_table,
+            &bind_rules.instructions,
+        );
 
         assert_eq!(expected_result, matcher.match_bind());
     }
@@ -656,19 +1551,72 @@ mod test {
     }
 
     #[test]
-    fn missing_entry_in_symbol_table() {
-        let mut symbol_table: HashMap<u32, String> = HashMap::new();
-        symbol_table.insert(1, "nightjar".to_string());
-        symbol_table.insert(2, "poorwill".to_string());
+    fn match_with_u128_values() {
+        let mut device_properties: DeviceProperties = HashMap::new();
+        device_properties.insert(PropertyKey::NumberKey(1), Symbol::U128Value(u128::MAX - 1));
+        device_properties.insert(PropertyKey::NumberKey(2), Symbol::NumberValue(7));
+
+        let append_u128_cond = |bytecode: &mut Vec<u8>, op: u8, key: u32, value: u128| {
+            bytecode.push(op);
+            append_encoded_value(
+                bytecode,
+                EncodedValue { value_type: RawValueType::NumberValue, value: key },
+            );
+            bytecode.push(RawValueType::U128Value as u8);
+            bytecode.extend_from_slice(&value.to_le_bytes());
+        };
+
+        let verify = |expected: Result<bool, BytecodeError>, instructions: Vec<u8>| {
+            verify_match_result(
+                expected,
+                DecodedBindRules {
+                    symbol_table: HashMap::new(),
+                    instructions: instructions,
+                    decoded_instructions: vec![],
+                },
+                &device_properties,
+            );
+        };
 
         let mut instructions: Vec<u8> = vec![];
-        append_inequal_cond(
-            &mut instructions,
-            EncodedValue { value_type: RawValueType::StringValue, value: 10 },
-            EncodedValue { value_type: RawValueType::StringValue, value: 1 },
-        );
-        verify_match_result(
-            Err(BytecodeError::MissingEntryInSymbolTable(10)),
+        append_u128_cond(&mut instructions, 0x01, 1, u128::MAX - 1);
+        verify(Ok(true), instructions);
+
+        // Values that differ only in the upper bits should not match.
+        let mut instructions: Vec<u8> = vec![];
+        append_u128_cond(&mut instructions, 0x01, 1, (u128::MAX - 1) & u64::MAX as u128);
+        verify(Ok(false), instructions);
+
+        let mut instructions: Vec<u8> = vec![];
+        append_u128_cond(&mut instructions, 0x02, 1, u128::MAX);
+        verify(Ok(true), instructions);
+
+        // 128-bit values can be compared against number values.
+        let mut instructions: Vec<u8> = vec![];
+        append_u128_cond(&mut instructions, 0x01, 2, 7);
+        verify(Ok(true), instructions);
+
+        // The value is cut off.
+        let mut instructions: Vec<u8> = vec![];
+        append_u128_cond(&mut instructions, 0x01, 1, u128::MAX - 1);
+        instructions.pop();
+        verify(Err(BytecodeError::UnexpectedEnd), instructions);
+    }
+
+    #[test]
+    fn missing_entry_in_symbol_table() {
+        let mut symbol_table: HashMap<u32, String> = HashMap::new();
+        symbol_table.insert(1, "nightjar".to_string());
+        symbol_table.insert(2, "poorwill".to_string());
+
+        let mut instructions: Vec<u8> = vec![];
+        append_inequal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::StringValue, value: 10 },
+            EncodedValue { value_type: RawValueType::StringValue, value: 1 },
+        );
+        verify_match_result(
+            Err(BytecodeError::MissingEntryInSymbolTable(10)),
             DecodedBindRules {
                 symbol_table: HashMap::new(),
                 instructions: instructions,
@@ -716,9 +1664,9 @@ mod test {
 
     #[test]
     fn invalid_value_type() {
-        let instructions: Vec<u8> = vec![0x01, 0x05, 0, 0, 0, 0, 0x01, 0, 0, 0, 0];
+        let instructions: Vec<u8> = vec![0x01, 0x06, 0, 0, 0, 0, 0x01, 0, 0, 0, 0];
         verify_match_result(
-            Err(BytecodeError::InvalidValueType(0x05)),
+            Err(BytecodeError::InvalidValueType(0x06)),
             DecodedBindRules {
                 symbol_table: HashMap::new(),
                 instructions: instructions,
@@ -1001,6 +1949,44 @@ mod test {
         );
     }
 
+    #[test]
+    fn jump_if_not_equal_to_missing_label() {
+        let mut instructions: Vec<u8> = vec![];
+        append_jump_if_not_equal(
+            &mut instructions,
+            ABORT_BYTES,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 10 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_abort(&mut instructions);
+
+        // The jump isn't taken, so the device is rejected by the abort.
+        let mut device_properties: DeviceProperties = HashMap::new();
+        device_properties.insert(PropertyKey::NumberKey(10), Symbol::NumberValue(2000));
+        verify_match_result(
+            Ok(false),
+            DecodedBindRules {
+                symbol_table: HashMap::new(),
+                instructions: instructions.clone(),
+                decoded_instructions: vec![],
+            },
+            &device_properties,
+        );
+
+        // The jump is taken, but there's no label after the abort.
+        let mut device_properties: DeviceProperties = HashMap::new();
+        device_properties.insert(PropertyKey::NumberKey(10), Symbol::NumberValue(20));
+        verify_match_result(
+            Err(BytecodeError::InvalidJumpLocation),
+            DecodedBindRules {
+                symbol_table: HashMap::new(),
+                instructions: instructions,
+                decoded_instructions: vec![],
+            },
+            &device_properties,
+        );
+    }
+
     #[test]
     fn no_jump_pad() {
         let mut device_properties: DeviceProperties = HashMap::new();
@@ -1041,7 +2027,7 @@ mod test {
         append_abort(&mut instructions);
         append_jump_pad(&mut instructions);
         verify_match_result(
-            Err(BytecodeError::UnexpectedEnd),
+            Err(BytecodeError::InvalidJumpLocation),
             DecodedBindRules {
                 symbol_table: HashMap::new(),
                 instructions: instructions,
@@ -1155,4 +2141,1020 @@ mod test {
             &device_properties,
         );
     }
+
+    #[test]
+    fn merge_device_properties_layers() {
+        let mut platform: DeviceProperties = HashMap::new();
+        platform.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(10));
+        platform.insert(PropertyKey::NumberKey(2), Symbol::NumberValue(20));
+
+        let mut board: DeviceProperties = HashMap::new();
+        board.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(11));
+        board.insert(PropertyKey::StringKey("nothing".to_string()), Symbol::BoolValue(true));
+
+        let mut device: DeviceProperties = HashMap::new();
+        device.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(12));
+
+        let mut expected: DeviceProperties = HashMap::new();
+        expected.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(12));
+        expected.insert(PropertyKey::NumberKey(2), Symbol::NumberValue(20));
+        expected.insert(PropertyKey::StringKey("nothing".to_string()), Symbol::BoolValue(true));
+
+        assert_eq!(expected, merge_device_properties(&[platform, board, device]));
+        assert_eq!(DeviceProperties::new(), merge_device_properties(&[]));
+    }
+
+    fn driver_bytecode(instructions: &[u8]) -> Vec<u8> {
+        let mut bytecode = BIND_HEADER.to_vec();
+        append_section_header(&mut bytecode, SYMB_MAGIC_NUM, 0);
+        append_section_header(&mut bytecode, INSTRUCTION_MAGIC_NUM, instructions.len() as u32);
+        bytecode.extend_from_slice(instructions);
+        bytecode
+    }
+
+    #[test]
+    fn select_most_specific_driver() {
+        let mut device_properties: DeviceProperties = HashMap::new();
+        device_properties.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(2000));
+        device_properties.insert(PropertyKey::NumberKey(2), Symbol::NumberValue(500));
+
+        let mut generic = vec![];
+        append_equal_cond(
+            &mut generic,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+
+        let mut specific = generic.clone();
+        append_equal_cond(
+            &mut specific,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+
+        let mut mismatch = specific.clone();
+        append_inequal_cond(
+            &mut mismatch,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+
+        let drivers = vec![
+            ("generic".to_string(), driver_bytecode(&generic)),
+            ("specific".to_string(), driver_bytecode(&specific)),
+            ("mismatch".to_string(), driver_bytecode(&mismatch)),
+        ];
+        assert_eq!(Ok(Some("specific".to_string())), select_driver(&device_properties, &drivers));
+        assert_eq!(Ok(None), select_driver(&device_properties, &drivers[2..]));
+    }
+
+    #[test]
+    fn fingerprint_ignores_insertion_order() {
+        let mut first: DeviceProperties = HashMap::new();
+        let mut second: DeviceProperties = HashMap::new();
+        let properties = (0..32)
+            .map(|i| (PropertyKey::NumberKey(i), Symbol::NumberValue(i * 10)))
+            .chain(std::iter::once((
+                PropertyKey::StringKey("nothing".to_string()),
+                Symbol::StringValue("something".to_string()),
+            )))
+            .collect::<Vec<_>>();
+        for (key, value) in properties.iter().cloned() {
+            first.insert(key, value);
+        }
+        for (key, value) in properties.iter().rev().cloned() {
+            second.insert(key, value);
+        }
+        assert_eq!(fingerprint(&first), fingerprint(&second));
+
+        second.insert(PropertyKey::NumberKey(0), Symbol::NumberValue(1));
+        assert_ne!(fingerprint(&first), fingerprint(&second));
+    }
+
+    #[test]
+    fn fingerprint_is_fixed() {
+        // No properties hash an empty sequence of bytes, giving the FNV-1a offset basis.
+        assert_eq!(0xcbf29ce484222325, fingerprint(&HashMap::new()));
+
+        let mut properties: DeviceProperties = HashMap::new();
+        properties.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(2));
+        properties.insert(
+            PropertyKey::StringKey("fuchsia.BIND_PROTOCOL".to_string()),
+            Symbol::StringValue("fuchsia.pci".to_string()),
+        );
+        assert_eq!(0xeaa8bce7680037b0, fingerprint(&properties));
+    }
+
+    #[test]
+    fn format_properties_sorted_by_key() {
+        let mut properties: DeviceProperties = HashMap::new();
+        properties.insert(
+            PropertyKey::StringKey("killdeer".to_string()),
+            Symbol::StringValue("plover".to_string()),
+        );
+        properties.insert(PropertyKey::NumberKey(3), Symbol::BoolValue(true));
+        properties.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(2000));
+
+        assert_eq!(
+            format_properties(&properties, None),
+            "0x1 = 0x7d0 (2000)\n0x3 = true\nkilldeer = \"plover\""
+        );
+
+        let resolver: KeyResolver = vec![(1, "BIND_PROTOCOL".to_string())].into_iter().collect();
+        assert_eq!(
+            format_properties(&properties, Some(&resolver)),
+            "BIND_PROTOCOL = 0x7d0 (2000)\n0x3 = true\nkilldeer = \"plover\""
+        );
+    }
+
+    #[test]
+    fn minimal_properties_for_equality_conditions() {
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::BoolValue, value: 1 },
+        );
+        let bytecode = driver_bytecode(&instructions);
+
+        let mut expected: DeviceProperties = HashMap::new();
+        expected.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(2000));
+        expected.insert(PropertyKey::NumberKey(2), Symbol::BoolValue(true));
+        assert_eq!(minimal_satisfying_properties(&bytecode), Ok(Some(expected.clone())));
+        assert_eq!(match_bytecode(bytecode, &expected), Ok(true));
+
+        // Requiring a different value for the same key can't be satisfied.
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 10 },
+        );
+        assert_eq!(minimal_satisfying_properties(&driver_bytecode(&instructions)), Ok(None));
+    }
+
+    #[test]
+    fn index_keys_for_equality_and_inequality() {
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_inequal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+
+        assert_eq!(
+            index_keys(&driver_bytecode(&instructions)),
+            Ok(vec![
+                (PropertyKey::NumberKey(1), Some(Symbol::NumberValue(2000))),
+                (PropertyKey::NumberKey(2), None),
+            ])
+        );
+    }
+
+    #[test]
+    fn co_occurring_keys_across_drivers() {
+        let mut a = vec![];
+        append_equal_cond(
+            &mut a,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_inequal_cond(
+            &mut a,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+        append_equal_cond(
+            &mut a,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 3 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 7 },
+        );
+        let mut b = vec![];
+        append_equal_cond(
+            &mut b,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 10 },
+        );
+        append_equal_cond(
+            &mut b,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+        let mut c = vec![];
+        append_equal_cond(
+            &mut c,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 3 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 8 },
+        );
+
+        // Keys 1 and 2 are always tested together. Key 3 is also tested alone.
+        assert_eq!(
+            co_occurring_keys(&[driver_bytecode(&a), driver_bytecode(&b), driver_bytecode(&c)]),
+            Ok(vec![(PropertyKey::NumberKey(1), PropertyKey::NumberKey(2), 2)])
+        );
+    }
+
+    #[test]
+    fn matcher_features_for_bind_rules() {
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        assert_eq!(requires_matcher_features(&driver_bytecode(&instructions)), Ok(HashSet::new()));
+
+        // Jump over a condition and a 128-bit condition, whose value is a type byte followed by
+        // 16 bytes.
+        append_jump_if_equal(
+            &mut instructions,
+            COND_INST_BYTES + OP_BYTES + VALUE_BYTES + 17,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+        append_inequal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 3 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 7 },
+        );
+        instructions.push(RawOp::EqualCondition as u8);
+        append_encoded_value(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 4 },
+        );
+        instructions.push(RawValueType::U128Value as u8);
+        instructions.extend_from_slice(&u128::MAX.to_le_bytes());
+        append_jump_pad(&mut instructions);
+        assert_eq!(
+            requires_matcher_features(&driver_bytecode(&instructions)),
+            Ok(vec![MatcherFeature::Jumps, MatcherFeature::U128Values].into_iter().collect())
+        );
+    }
+
+    #[test]
+    fn mutually_exclusive_values_for_shared_key() {
+        let mut a = vec![];
+        append_equal_cond(
+            &mut a,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        let mut b = vec![];
+        append_equal_cond(
+            &mut b,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+        let mut c = vec![];
+        append_equal_cond(
+            &mut c,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+
+        assert_eq!(mutually_exclusive(&driver_bytecode(&a), &driver_bytecode(&b)), Ok(true));
+        assert_eq!(mutually_exclusive(&driver_bytecode(&a), &driver_bytecode(&c)), Ok(false));
+    }
+
+    #[test]
+    fn wildcard_device_value() {
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_inequal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+
+        let mut device_properties: DeviceProperties = HashMap::new();
+        device_properties.insert(PropertyKey::NumberKey(1), Symbol::Any);
+        device_properties.insert(PropertyKey::NumberKey(2), Symbol::Any);
+        assert_eq!(match_bytecode(driver_bytecode(&instructions), &device_properties), Ok(true));
+
+        device_properties.insert(PropertyKey::NumberKey(2), Symbol::NumberValue(500));
+        assert_eq!(match_bytecode(driver_bytecode(&instructions), &device_properties), Ok(false));
+    }
+
+    #[test]
+    fn first_use_of_key() {
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_inequal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 3 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 7 },
+        );
+        append_inequal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 3 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 8 },
+        );
+        let bytecode = driver_bytecode(&instructions);
+
+        assert_eq!(first_use_of(&bytecode, &PropertyKey::NumberKey(3)), Ok(Some(2)));
+        assert_eq!(first_use_of(&bytecode, &PropertyKey::NumberKey(4)), Ok(None));
+    }
+
+    #[test]
+    fn match_verbose_rejection_offsets() {
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_inequal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+        append_unconditional_jump(&mut instructions, ABORT_BYTES);
+        append_abort(&mut instructions);
+        append_jump_pad(&mut instructions);
+        append_jump_if_equal(
+            &mut instructions,
+            ABORT_BYTES,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 3 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 7 },
+        );
+        append_abort(&mut instructions);
+        append_jump_pad(&mut instructions);
+        let bytecode = driver_bytecode(&instructions);
+
+        let mut device_properties: DeviceProperties = HashMap::new();
+        device_properties.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(2000));
+        device_properties.insert(PropertyKey::NumberKey(2), Symbol::NumberValue(400));
+        device_properties.insert(PropertyKey::NumberKey(3), Symbol::NumberValue(7));
+        assert_eq!(
+            match_bytecode_verbose(bytecode.clone(), &device_properties),
+            Ok(MatchResult {
+                matched: true,
+                rejected_at: None,
+                rejected_instruction: None,
+                score: 0
+            })
+        );
+
+        // The equal condition is the first instruction.
+        device_properties.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(1000));
+        assert_eq!(
+            match_bytecode_verbose(bytecode.clone(), &device_properties),
+            Ok(MatchResult {
+                matched: false,
+                rejected_at: Some(0),
+                rejected_instruction: Some("0x0001 == 0x7d0 (2000)".to_string()),
+                score: 0,
+            })
+        );
+
+        // The inequal condition follows it.
+        device_properties.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(2000));
+        device_properties.insert(PropertyKey::NumberKey(2), Symbol::NumberValue(500));
+        assert_eq!(
+            match_bytecode_verbose(bytecode.clone(), &device_properties),
+            Ok(MatchResult {
+                matched: false,
+                rejected_at: Some(COND_INST_BYTES as usize),
+                rejected_instruction: Some("0x0002 != 0x1f4 (500)".to_string()),
+                score: 0,
+            })
+        );
+
+        // The jump over the first abort is taken, but not the jump over the second.
+        device_properties.insert(PropertyKey::NumberKey(2), Symbol::NumberValue(400));
+        device_properties.insert(PropertyKey::NumberKey(3), Symbol::NumberValue(8));
+        let second_abort =
+            COND_INST_BYTES * 2 + UNCOND_JMP_BYTES + ABORT_BYTES + JMP_PAD_BYTES + COND_JMP_BYTES;
+        assert_eq!(
+            match_bytecode_verbose(bytecode, &device_properties),
+            Ok(MatchResult {
+                matched: false,
+                rejected_at: Some(second_abort as usize),
+                rejected_instruction: Some("abort".to_string()),
+                score: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn enum_conditions() {
+        let mut device_properties: DeviceProperties = HashMap::new();
+        device_properties.insert(
+            PropertyKey::StringKey("nightjar".to_string()),
+            Symbol::EnumValue("nighthawk".to_string()),
+        );
+
+        let mut symbol_table: HashMap<u32, String> = HashMap::new();
+        symbol_table.insert(1, "nightjar".to_string());
+        symbol_table.insert(2, "nighthawk".to_string());
+        symbol_table.insert(3, "poorwill".to_string());
+
+        let verify =
+            |expected, append_cond: fn(&mut Vec<u8>, EncodedValue, EncodedValue), value| {
+                let mut instructions: Vec<u8> = vec![];
+                append_cond(
+                    &mut instructions,
+                    EncodedValue { value_type: RawValueType::StringValue, value: 1 },
+                    EncodedValue { value_type: RawValueType::EnumValue, value },
+                );
+                verify_match_result(
+                    expected,
+                    DecodedBindRules {
+                        symbol_table: symbol_table.clone(),
+                        instructions: instructions,
+                        decoded_instructions: vec![],
+                    },
+                    &device_properties,
+                );
+            };
+
+        verify(Ok(true), append_equal_cond, 2);
+        verify(Ok(false), append_equal_cond, 3);
+        verify(Ok(true), append_inequal_cond, 3);
+        verify(Ok(false), append_inequal_cond, 2);
+
+        // Enum values can't be compared against numbers.
+        let mut instructions: Vec<u8> = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::StringValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+        );
+        verify_match_result(
+            Err(BytecodeError::MismatchValueTypes),
+            DecodedBindRules {
+                symbol_table: symbol_table.clone(),
+                instructions: instructions,
+                decoded_instructions: vec![],
+            },
+            &device_properties,
+        );
+    }
+
+    #[test]
+    fn device_properties_builder() {
+        let properties = DevicePropertiesBuilder::new()
+            .number(1, 2000)
+            .string("nightjar", "poorwill")
+            .bool_prop(2, true)
+            .build();
+
+        let mut expected: DeviceProperties = HashMap::new();
+        expected.insert(PropertyKey::NumberKey(1), Symbol::NumberValue(2000));
+        expected.insert(
+            PropertyKey::StringKey("nightjar".to_string()),
+            Symbol::StringValue("poorwill".to_string()),
+        );
+        expected.insert(PropertyKey::NumberKey(2), Symbol::BoolValue(true));
+        assert_eq!(properties, expected);
+
+        // Later values for the same key replace earlier ones.
+        assert_eq!(
+            DevicePropertiesBuilder::new().number(1, 5).number(1, 6).build(),
+            DevicePropertiesBuilder::new().number(1, 6).build()
+        );
+    }
+
+    #[test]
+    fn device_properties_builder_match_bytecode() {
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::BoolValue, value: 1 },
+        );
+        let bytecode = driver_bytecode(&instructions);
+
+        let properties = DevicePropertiesBuilder::new().number(1, 2000).bool_prop(2, true).build();
+        assert_eq!(match_bytecode(bytecode.clone(), &properties), Ok(true));
+
+        let properties = DevicePropertiesBuilder::new().number(1, 2000).bool_prop(2, false).build();
+        assert_eq!(match_bytecode(bytecode, &properties), Ok(false));
+    }
+
+    #[test]
+    fn match_bind_traced_records_each_condition() {
+        let symbol_table = HashMap::new();
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_inequal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 3 },
+            EncodedValue { value_type: RawValueType::BoolValue, value: 1 },
+        );
+        let bind_data =
+            || MatchBindData { symbol_table: &symbol_table, instructions: &instructions };
+
+        let properties = DevicePropertiesBuilder::new().number(1, 2000).bool_prop(3, true).build();
+        let (matched, trace) = match_bind_traced(bind_data(), &properties).unwrap();
+        assert!(matched);
+        assert_eq!(trace.len(), 3);
+        assert_eq!(
+            trace[0],
+            TraceStep {
+                op: RawOp::EqualCondition,
+                key: "0x0001".to_string(),
+                expected: "0x7d0 (2000)".to_string(),
+                actual: Some("0x7d0 (2000)".to_string()),
+                passed: true,
+            }
+        );
+        assert_eq!(
+            trace[1],
+            TraceStep {
+                op: RawOp::InequalCondition,
+                key: "0x0002".to_string(),
+                expected: "0x1f4 (500)".to_string(),
+                actual: None,
+                passed: true,
+            }
+        );
+        assert!(trace.iter().all(|step| step.passed));
+
+        // The trace stops at the condition that rejects the device.
+        let properties = DevicePropertiesBuilder::new().number(1, 2000).number(2, 500).build();
+        let (matched, trace) = match_bind_traced(bind_data(), &properties).unwrap();
+        assert!(!matched);
+        assert_eq!(trace.iter().map(|step| step.passed).collect::<Vec<_>>(), vec![true, false]);
+        assert_eq!(trace[1].actual, Some("0x1f4 (500)".to_string()));
+    }
+
+    #[test]
+    fn match_bind_traced_records_jump_conditions() {
+        let symbol_table = HashMap::new();
+        let mut instructions = vec![];
+        append_jump_if_equal(
+            &mut instructions,
+            COND_INST_BYTES,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+        append_jump_pad(&mut instructions);
+        let bind_data = MatchBindData { symbol_table: &symbol_table, instructions: &instructions };
+
+        // The jump is taken, so the condition it skips isn't evaluated.
+        let properties = DevicePropertiesBuilder::new().number(1, 2000).build();
+        let (matched, trace) = match_bind_traced(bind_data, &properties).unwrap();
+        assert!(matched);
+        assert_eq!(trace.len(), 1);
+        assert_eq!(trace[0].op, RawOp::JumpIfEqual);
+        assert!(trace[0].passed);
+    }
+
+    #[test]
+    fn match_bind_used_symbols_records_lookups() {
+        let mut symbol_table = HashMap::new();
+        symbol_table.insert(1, "nightjar".to_string());
+        symbol_table.insert(2, "poorwill".to_string());
+
+        // The second condition is never reached since the first rejects the device.
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::StringValue, value: 1 },
+        );
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::StringValue, value: 2 },
+        );
+        let bind_data = MatchBindData { symbol_table: &symbol_table, instructions: &instructions };
+
+        let mut properties: DeviceProperties = HashMap::new();
+        properties.insert(PropertyKey::NumberKey(1), Symbol::StringValue("nighthawk".to_string()));
+        assert_eq!(
+            match_bind_used_symbols(bind_data, &properties),
+            Ok((false, vec![1].into_iter().collect()))
+        );
+    }
+
+    #[test]
+    fn match_bind_strict_verifies_past_abort() {
+        let symbol_table = HashMap::new();
+        let mut instructions = vec![];
+        append_abort(&mut instructions);
+        // A condition whose value has an invalid type.
+        instructions.push(0x01);
+        append_encoded_value(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+        );
+        instructions.push(0x09);
+        instructions.extend_from_slice(&2u32.to_le_bytes());
+
+        let bind_data =
+            || MatchBindData { symbol_table: &symbol_table, instructions: &instructions };
+        assert_eq!(match_bind(bind_data(), &HashMap::new()), Ok(false));
+        assert_eq!(
+            match_bind_strict(bind_data(), &HashMap::new()),
+            Err(BytecodeError::InvalidValueType(0x09))
+        );
+
+        // Well-formed instructions still report the match decision.
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+        );
+        let bind_data = MatchBindData { symbol_table: &symbol_table, instructions: &instructions };
+        let properties = DevicePropertiesBuilder::new().number(1, 2).build();
+        assert_eq!(match_bind_strict(bind_data, &properties), Ok(true));
+    }
+
+    #[test]
+    fn soft_condition_raises_score() {
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        instructions.push(RawOp::SoftEqualCondition as u8);
+        append_encoded_value(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+        );
+        append_encoded_value(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+        );
+        let bytecode = driver_bytecode(&instructions);
+
+        // The soft condition holds, so it raises the score.
+        let properties = DevicePropertiesBuilder::new().number(1, 2000).number(2, 1).build();
+        assert_eq!(
+            match_bytecode_verbose(bytecode.clone(), &properties),
+            Ok(MatchResult {
+                matched: true,
+                rejected_at: None,
+                rejected_instruction: None,
+                score: 1
+            })
+        );
+
+        // The device still matches without the preferred value or the property itself.
+        let properties = DevicePropertiesBuilder::new().number(1, 2000).number(2, 5).build();
+        assert_eq!(
+            match_bytecode_verbose(bytecode.clone(), &properties),
+            Ok(MatchResult {
+                matched: true,
+                rejected_at: None,
+                rejected_instruction: None,
+                score: 0
+            })
+        );
+        let properties = DevicePropertiesBuilder::new().number(1, 2000).build();
+        assert_eq!(match_bytecode(bytecode, &properties), Ok(true));
+    }
+
+    #[test]
+    fn compiled_prefer_raises_score() {
+        let libraries = vec!["library test.lib; string KIND; string COLOR;".to_string()];
+        let rules = "using test.lib; test.lib.KIND == \"duck\"; prefer test.lib.COLOR == \"teal\";";
+        let bytecode = crate::compiler::compile(rules, &libraries, false, false, true)
+            .unwrap()
+            .encode_to_bytecode()
+            .unwrap();
+
+        let properties = DevicePropertiesBuilder::new()
+            .string("test.lib.KIND", "duck")
+            .string("test.lib.COLOR", "teal")
+            .build();
+        let result = match_bytecode_verbose(bytecode.clone(), &properties).unwrap();
+        assert!(result.matched);
+        assert_eq!(result.score, 1);
+
+        // The preference isn't required for a match.
+        let properties = DevicePropertiesBuilder::new()
+            .string("test.lib.KIND", "duck")
+            .string("test.lib.COLOR", "mallard")
+            .build();
+        let result = match_bytecode_verbose(bytecode.clone(), &properties).unwrap();
+        assert!(result.matched);
+        assert_eq!(result.score, 0);
+
+        let properties = DevicePropertiesBuilder::new().string("test.lib.COLOR", "teal").build();
+        assert_eq!(match_bytecode(bytecode, &properties), Ok(false));
+    }
+
+    #[test]
+    fn match_many_programs() {
+        let mut matching = vec![];
+        append_equal_cond(
+            &mut matching,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        let mut also_matching = vec![];
+        append_inequal_cond(
+            &mut also_matching,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+        let mut aborting = vec![];
+        append_abort(&mut aborting);
+
+        let programs = vec![
+            ("matching".to_string(), driver_bytecode(&matching)),
+            ("aborting".to_string(), driver_bytecode(&aborting)),
+            ("also_matching".to_string(), driver_bytecode(&also_matching)),
+        ];
+        let properties = DevicePropertiesBuilder::new().number(1, 2000).number(2, 400).build();
+        assert_eq!(
+            match_many(&programs, &properties),
+            Ok(vec!["matching".to_string(), "also_matching".to_string()])
+        );
+        assert_eq!(match_many(&[], &properties), Ok(vec![]));
+    }
+
+    #[test]
+    fn selectivity_prefers_equality() {
+        let mut equalities = vec![];
+        append_equal_cond(
+            &mut equalities,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_equal_cond(
+            &mut equalities,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+        let mut inequality = vec![];
+        append_inequal_cond(
+            &mut inequality,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+
+        let equalities_score = selectivity(&driver_bytecode(&equalities)).unwrap();
+        let inequality_score = selectivity(&driver_bytecode(&inequality)).unwrap();
+        assert_eq!(equalities_score, 4);
+        assert_eq!(inequality_score, 1);
+        assert!(equalities_score > inequality_score);
+        assert_eq!(selectivity(&driver_bytecode(&[])), Ok(0));
+    }
+
+    #[test]
+    fn evaluate_all_instructions() {
+        let symbol_table = HashMap::new();
+        let mut instructions = vec![];
+        append_jump_if_equal(
+            &mut instructions,
+            COND_INST_BYTES,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 5 },
+        );
+        append_jump_pad(&mut instructions);
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 3 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 7 },
+        );
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 4 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+        );
+        let bind_data =
+            || MatchBindData { symbol_table: &symbol_table, instructions: &instructions };
+        let evaluate_all = MatchOptions { evaluate_all: true };
+
+        // The jump is taken and the condition after the label rejects the device.
+        let properties = DevicePropertiesBuilder::new().number(1, 2000).number(3, 8).build();
+        let (result, stats) =
+            match_bind_with_options(bind_data(), &properties, &MatchOptions::default()).unwrap();
+        assert!(!result.matched);
+        assert_eq!(stats.evaluated, 2);
+        assert_eq!(
+            match_bind_with_options(bind_data(), &properties, &evaluate_all),
+            Ok((result, MatchStats { evaluated: 5, skipped_errors: vec![] }))
+        );
+
+        let properties =
+            DevicePropertiesBuilder::new().number(1, 2000).number(3, 7).number(4, 1).build();
+        let (result, stats) =
+            match_bind_with_options(bind_data(), &properties, &MatchOptions::default()).unwrap();
+        assert!(result.matched);
+        assert_eq!(stats.evaluated, 3);
+        assert_eq!(
+            match_bind_with_options(bind_data(), &properties, &evaluate_all),
+            Ok((result, MatchStats { evaluated: 5, skipped_errors: vec![] }))
+        );
+    }
+
+    #[test]
+    fn evaluate_all_skipped_errors() {
+        let symbol_table = HashMap::new();
+        // Compares a number property against a bool, which fails to evaluate.
+        let append_bad_cond = |instructions: &mut Vec<u8>| {
+            append_equal_cond(
+                instructions,
+                EncodedValue { value_type: RawValueType::NumberValue, value: 5 },
+                EncodedValue { value_type: RawValueType::BoolValue, value: 1 },
+            )
+        };
+        let properties =
+            DevicePropertiesBuilder::new().number(1, 2000).number(3, 7).number(5, 1).build();
+        let evaluate_all = MatchOptions { evaluate_all: true };
+
+        // A taken jump over the bad condition.
+        let mut instructions = vec![];
+        append_jump_if_equal(
+            &mut instructions,
+            COND_INST_BYTES,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_bad_cond(&mut instructions);
+        append_jump_pad(&mut instructions);
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 3 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 7 },
+        );
+        let bind_data =
+            || MatchBindData { symbol_table: &symbol_table, instructions: &instructions };
+        let (result, stats) =
+            match_bind_with_options(bind_data(), &properties, &MatchOptions::default()).unwrap();
+        assert!(result.matched);
+        assert_eq!(stats.evaluated, 2);
+        assert_eq!(
+            match_bind_with_options(bind_data(), &properties, &evaluate_all),
+            Ok((
+                result,
+                MatchStats {
+                    evaluated: 4,
+                    skipped_errors: vec![(
+                        COND_JMP_BYTES as usize,
+                        BytecodeError::MismatchValueTypes
+                    )],
+                }
+            ))
+        );
+
+        // The bad condition after an abort.
+        let mut instructions = vec![];
+        append_abort(&mut instructions);
+        append_bad_cond(&mut instructions);
+        let bind_data =
+            || MatchBindData { symbol_table: &symbol_table, instructions: &instructions };
+        let (result, stats) =
+            match_bind_with_options(bind_data(), &properties, &MatchOptions::default()).unwrap();
+        assert_eq!(result.rejected_at, Some(0));
+        assert_eq!(stats.evaluated, 1);
+        assert_eq!(
+            match_bind_with_options(bind_data(), &properties, &evaluate_all),
+            Ok((
+                result,
+                MatchStats {
+                    evaluated: 2,
+                    skipped_errors: vec![(ABORT_BYTES as usize, BytecodeError::MismatchValueTypes)],
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn match_all_bytecode() {
+        assert_eq!(is_match_all(&driver_bytecode(&[])), Ok(true));
+
+        // A jump over nothing doesn't reject any device.
+        let mut jump = vec![];
+        append_jump_if_equal(
+            &mut jump,
+            0,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        append_jump_pad(&mut jump);
+        assert_eq!(is_match_all(&driver_bytecode(&jump)), Ok(true));
+
+        let mut condition = vec![];
+        append_equal_cond(
+            &mut condition,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        assert_eq!(is_match_all(&driver_bytecode(&condition)), Ok(false));
+
+        let mut abort = vec![];
+        append_abort(&mut abort);
+        assert_eq!(is_match_all(&driver_bytecode(&abort)), Ok(false));
+    }
+
+    #[test]
+    fn match_stream_of_devices() {
+        let mut instructions = vec![];
+        append_equal_cond(
+            &mut instructions,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        let devices = "{\"1\": 2000, \"2\": 5}\n\
+                       {\"0x1\": 10}\n\
+                       \n\
+                       {\"0x1\": 2000, \"name\": \"whimbrel\", \"enabled\": true}\n";
+
+        assert_eq!(
+            match_stream(&driver_bytecode(&instructions), devices.as_bytes()).collect::<Vec<_>>(),
+            vec![Ok(true), Ok(false), Ok(true)]
+        );
+
+        let results =
+            match_stream(&driver_bytecode(&instructions), "[1]\n".as_bytes()).collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![Err(DeviceStreamError::InvalidDevice("[1] is not an object".to_string()))]
+        );
+
+        assert_eq!(
+            match_stream(&[], devices.as_bytes()).collect::<Vec<_>>(),
+            vec![Err(DeviceStreamError::Bytecode(BytecodeError::UnexpectedEnd))]
+        );
+    }
+
+    #[test]
+    fn drivers_matching_any_devices() {
+        let mut first = vec![];
+        append_equal_cond(
+            &mut first,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 1 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2000 },
+        );
+        let mut second = vec![];
+        append_inequal_cond(
+            &mut second,
+            EncodedValue { value_type: RawValueType::NumberValue, value: 2 },
+            EncodedValue { value_type: RawValueType::NumberValue, value: 500 },
+        );
+
+        let drivers = vec![
+            ("first".to_string(), driver_bytecode(&first)),
+            ("second".to_string(), driver_bytecode(&second)),
+        ];
+        let devices = vec![
+            DevicePropertiesBuilder::new().number(1, 2000).number(2, 500).build(),
+            DevicePropertiesBuilder::new().number(1, 10).build(),
+            DevicePropertiesBuilder::new().number(1, 2000).number(2, 400).build(),
+        ];
+
+        let mut expected = HashMap::new();
+        expected.insert("first".to_string(), vec![0, 2]);
+        expected.insert("second".to_string(), vec![1, 2]);
+        assert_eq!(drivers_matching_any(&drivers, &devices), Ok(expected));
+
+        let mut expected = HashMap::new();
+        expected.insert("first".to_string(), vec![]);
+        expected.insert("second".to_string(), vec![]);
+        assert_eq!(drivers_matching_any(&drivers, &[]), Ok(expected));
+    }
 }
@@ -4,4 +4,6 @@
 
 pub mod common;
 pub mod decode_bind_rules;
+pub mod describe;
+pub mod libraries;
 pub mod match_bind;
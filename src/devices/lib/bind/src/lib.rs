@@ -15,3 +15,4 @@ pub mod interpreter;
 pub mod linter;
 pub mod parser;
 pub mod test;
+pub mod test_util;
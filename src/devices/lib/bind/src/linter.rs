@@ -2,9 +2,12 @@
 // Use of this source code is governed by a BSD-style license that can be
 // found in the LICENSE file.
 
+use crate::compiler::{SymbolicInstruction, SymbolicInstructionInfo};
 use crate::errors::UserError;
 use crate::parser::bind_library;
+use crate::parser::bind_rules::Statement;
 use crate::parser::common::CompoundIdentifier;
+use std::collections::HashSet;
 use std::fmt;
 use thiserror::Error;
 
@@ -27,10 +30,135 @@ pub fn lint_library<'a>(library: &bind_library::Ast) -> Result<(), LinterError>
     Ok(())
 }
 
+/// Lints that don't prevent compilation but are worth reporting to the bind rules author.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinterWarning {
+    /// The instruction at `index` can never be executed.
+    UnreachableInstruction { index: usize },
+    /// An enum property is declared without any values, so no rule can compare against it.
+    EmptyEnumProperty(CompoundIdentifier),
+    /// If statements are nested `depth` levels deep, which is more than `MAX_NESTING_DEPTH`.
+    DeepNesting { depth: usize },
+}
+
+// The deepest that if statements may be nested before the linter warns about them.
+pub const MAX_NESTING_DEPTH: usize = 4;
+
+impl fmt::Display for LinterWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinterWarning::UnreachableInstruction { index } => {
+                write!(f, "Instruction {} is unreachable and can be removed.", index)
+            }
+            LinterWarning::EmptyEnumProperty(identifier) => {
+                write!(f, "Enum property {} is declared without any values.", identifier)
+            }
+            LinterWarning::DeepNesting { depth } => write!(
+                f,
+                "If statements are nested {} levels deep, the maximum is {}.",
+                depth, MAX_NESTING_DEPTH
+            ),
+        }
+    }
+}
+
+// Returns a warning for every enum property that the library declares without any values.
+// Extensions are skipped since the property's values may be declared in the library it extends.
+pub fn lint_library_warnings(library: &bind_library::Ast) -> Vec<LinterWarning> {
+    library
+        .declarations
+        .iter()
+        .filter(|declaration| {
+            declaration.value_type == bind_library::ValueType::Enum
+                && !declaration.extends
+                && declaration.values.is_empty()
+        })
+        .map(|declaration| LinterWarning::EmptyEnumProperty(declaration.identifier.clone()))
+        .collect()
+}
+
+// Returns a warning for every instruction that follows an unconditional abort, bind or jump and
+// that isn't re-entered through a label targeted by some reachable jump.
+pub fn lint_unreachable_instructions(
+    instructions: &[SymbolicInstructionInfo<'_>],
+) -> Vec<LinterWarning> {
+    let mut warnings = vec![];
+    let mut reachable = true;
+    let mut jump_targets = HashSet::new();
+
+    for (index, info) in instructions.iter().enumerate() {
+        if let SymbolicInstruction::Label(label) = info.instruction {
+            reachable = reachable || jump_targets.contains(&label);
+            continue;
+        }
+
+        if !reachable {
+            warnings.push(LinterWarning::UnreachableInstruction { index });
+            continue;
+        }
+
+        match info.instruction {
+            SymbolicInstruction::JumpIfEqual { label, .. }
+            | SymbolicInstruction::JumpIfNotEqual { label, .. } => {
+                jump_targets.insert(label);
+            }
+            SymbolicInstruction::UnconditionalJump { label } => {
+                jump_targets.insert(label);
+                reachable = false;
+            }
+            SymbolicInstruction::UnconditionalAbort | SymbolicInstruction::UnconditionalBind => {
+                reachable = false;
+            }
+            _ => (),
+        }
+    }
+
+    warnings
+}
+
+// Returns a warning if the if statements in the block are nested more than MAX_NESTING_DEPTH
+// levels deep, since deeply nested rules are hard to follow.
+pub fn lint_nesting_depth(statements: &[Statement<'_>]) -> Option<LinterWarning> {
+    let depth = max_nesting_depth(statements);
+    if depth > MAX_NESTING_DEPTH {
+        Some(LinterWarning::DeepNesting { depth })
+    } else {
+        None
+    }
+}
+
+// Returns how deeply the if statements in the block are nested, where a block without any if
+// statements has a depth of 0.
+pub fn max_nesting_depth(statements: &[Statement<'_>]) -> usize {
+    statements
+        .iter()
+        .map(|statement| match statement {
+            Statement::If { blocks, else_block, .. } => {
+                1 + blocks
+                    .iter()
+                    .map(|(_, block)| block)
+                    .chain(std::iter::once(else_block))
+                    .map(|block| max_nesting_depth(block))
+                    .max()
+                    .unwrap_or(0)
+            }
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::compiler::Symbol;
     use crate::make_identifier;
+    use crate::parser::bind_rules;
+    use std::convert::TryFrom;
+
+    fn instruction(instruction: SymbolicInstruction) -> SymbolicInstructionInfo<'static> {
+        SymbolicInstructionInfo { location: None, instruction }
+    }
 
     #[test]
     fn library_lint_success() {
@@ -59,4 +187,69 @@ mod tests {
             ]))
         );
     }
+
+    #[test]
+    fn empty_enum_property() {
+        let ast = bind_library::Ast::try_from(
+            "library a;\nenum empty;\nenum full { x, y };\nextend enum b.extended;",
+        )
+        .unwrap();
+
+        assert_eq!(
+            lint_library_warnings(&ast),
+            vec![LinterWarning::EmptyEnumProperty(make_identifier!["empty"])]
+        );
+    }
+
+    #[test]
+    fn unreachable_after_unconditional_abort() {
+        let instructions = vec![
+            instruction(SymbolicInstruction::AbortIfNotEqual {
+                lhs: Symbol::DeprecatedKey(1),
+                rhs: Symbol::NumberValue(2),
+            }),
+            instruction(SymbolicInstruction::UnconditionalAbort),
+            instruction(SymbolicInstruction::AbortIfEqual {
+                lhs: Symbol::DeprecatedKey(3),
+                rhs: Symbol::NumberValue(4),
+            }),
+            instruction(SymbolicInstruction::UnconditionalBind),
+        ];
+
+        assert_eq!(
+            lint_unreachable_instructions(&instructions),
+            vec![
+                LinterWarning::UnreachableInstruction { index: 2 },
+                LinterWarning::UnreachableInstruction { index: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn label_reentry_is_reachable() {
+        let instructions = vec![
+            instruction(SymbolicInstruction::JumpIfEqual {
+                lhs: Symbol::DeprecatedKey(1),
+                rhs: Symbol::NumberValue(2),
+                label: 0,
+            }),
+            instruction(SymbolicInstruction::UnconditionalAbort),
+            instruction(SymbolicInstruction::Label(0)),
+            instruction(SymbolicInstruction::UnconditionalBind),
+        ];
+
+        assert_eq!(lint_unreachable_instructions(&instructions), vec![]);
+    }
+
+    #[test]
+    fn nesting_depth() {
+        let ast = bind_rules::Ast::try_from(
+            "if a == 1 {\n  if b == 2 {\n    true;\n  } else {\n    false;\n  }\n} else {\n  c == 3;\n}",
+        )
+        .unwrap();
+        assert_eq!(max_nesting_depth(&ast.statements), 2);
+
+        let ast = bind_rules::Ast::try_from("a == 1;\nb == 2;").unwrap();
+        assert_eq!(max_nesting_depth(&ast.statements), 0);
+    }
 }
@@ -11,7 +11,7 @@ use nom::{
     bytes::complete::tag,
     combinator::{opt, value},
     multi::{many1, separated_nonempty_list},
-    sequence::{delimited, preceded, terminated, tuple},
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
     IResult,
 };
 use std::convert::TryFrom;
@@ -58,6 +58,11 @@ pub enum Statement<'a> {
     True {
         span: Span<'a>,
     },
+    Prefer {
+        span: Span<'a>,
+        identifier: CompoundIdentifier,
+        value: Value,
+    },
 }
 
 pub type StatementBlock<'a> = Vec<Statement<'a>>;
@@ -70,6 +75,7 @@ impl<'a> Statement<'a> {
             Statement::If { span, .. } => span,
             Statement::False { span } => span,
             Statement::True { span } => span,
+            Statement::Prefer { span, .. } => span,
         }
     }
 }
@@ -187,8 +193,27 @@ fn keyword_true(input: NomSpan) -> IResult<NomSpan, Statement, BindParserError>
     Ok((to, Statement::True { span }))
 }
 
+fn keyword_prefer(input: NomSpan) -> IResult<NomSpan, NomSpan, BindParserError> {
+    ws(map_err(tag("prefer"), BindParserError::PreferKeyword))(input)
+}
+
+fn prefer(input: NomSpan) -> IResult<NomSpan, Statement, BindParserError> {
+    let from = skip_ws(input)?;
+
+    // Only equality can be preferred.
+    let equals = ws(map_err(tag("=="), BindParserError::ConditionOp));
+    let terminator = ws(map_err(tag(";"), BindParserError::Semicolon));
+    let preference = separated_pair(ws(compound_identifier), equals, ws(condition_value));
+
+    let (to, (identifier, value)) =
+        terminated(preceded(keyword_prefer, preference), terminator)(from)?;
+
+    let span = Span::from_to(&from, &to);
+    Ok((to, Statement::Prefer { span, identifier, value }))
+}
+
 fn statement(input: NomSpan) -> IResult<NomSpan, Statement, BindParserError> {
-    alt((condition_statement, if_statement, accept, keyword_false, keyword_true))(input)
+    alt((condition_statement, if_statement, accept, prefer, keyword_false, keyword_true))(input)
 }
 
 fn rules(input: NomSpan) -> IResult<NomSpan, Ast, BindParserError> {
@@ -699,6 +724,84 @@ mod test {
         }
     }
 
+    mod prefer_statement {
+        use super::*;
+
+        #[test]
+        fn simple() {
+            check_result(
+                prefer(NomSpan::new("prefer a == 1;")),
+                "",
+                Statement::Prefer {
+                    span: Span { offset: 0, line: 1, fragment: "prefer a == 1;" },
+                    identifier: make_identifier!["a"],
+                    value: Value::NumericLiteral(1),
+                },
+            );
+        }
+
+        #[test]
+        fn invalid() {
+            // Must have prefer keyword.
+            assert_eq!(
+                prefer(NomSpan::new("a == 1;")),
+                Err(nom::Err::Error(BindParserError::PreferKeyword("a == 1;".to_string())))
+            );
+
+            // Only equality can be preferred.
+            assert_eq!(
+                prefer(NomSpan::new("prefer a != 1;")),
+                Err(nom::Err::Error(BindParserError::ConditionOp("!= 1;".to_string())))
+            );
+
+            // Must have semicolon.
+            assert_eq!(
+                prefer(NomSpan::new("prefer a == 1")),
+                Err(nom::Err::Error(BindParserError::Semicolon("".to_string())))
+            );
+        }
+
+        #[test]
+        fn empty() {
+            assert_eq!(
+                prefer(NomSpan::new("")),
+                Err(nom::Err::Error(BindParserError::PreferKeyword("".to_string())))
+            );
+        }
+
+        #[test]
+        fn span() {
+            // Span doesn't contain leading or trailing whitespace, and line number is correct.
+            check_result(
+                prefer(NomSpan::new(" \n\t\r\nprefer a \n\t\r\n== 1; \n\t\r\n")),
+                " \n\t\r\n",
+                Statement::Prefer {
+                    span: Span { offset: 5, line: 3, fragment: "prefer a \n\t\r\n== 1;" },
+                    identifier: make_identifier!["a"],
+                    value: Value::NumericLiteral(1),
+                },
+            );
+        }
+
+        #[test]
+        fn identifier_named_prefer() {
+            // A condition on a key named "prefer" is still a condition statement.
+            check_result(
+                statement(NomSpan::new("prefer == 1;")),
+                "",
+                Statement::ConditionStatement {
+                    span: Span { offset: 0, line: 1, fragment: "prefer == 1;" },
+                    condition: Condition {
+                        span: Span { offset: 0, line: 1, fragment: "prefer == 1" },
+                        lhs: make_identifier!["prefer"],
+                        op: ConditionOp::Equals,
+                        rhs: Value::NumericLiteral(1),
+                    },
+                },
+            );
+        }
+    }
+
     mod rules {
         use super::*;
 
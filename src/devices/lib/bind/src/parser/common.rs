@@ -19,6 +19,7 @@ use nom::{
 };
 use nom_locate::LocatedSpan;
 use regex::Regex;
+use std::convert::TryFrom;
 use std::fmt;
 use thiserror::Error;
 
@@ -84,6 +85,8 @@ pub struct Include {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     NumericLiteral(u64),
+    // Numeric literals too large for a u64, such as UUIDs.
+    U128Literal(u128),
     StringLiteral(String),
     BoolLiteral(bool),
     Identifier(CompoundIdentifier),
@@ -113,6 +116,7 @@ pub enum BindParserError {
     AcceptKeyword(String),
     TrueKeyword(String),
     FalseKeyword(String),
+    PreferKeyword(String),
     NoStatements(String),
     NoNodes(String),
     Eof(String),
@@ -159,6 +163,20 @@ pub fn numeric_literal(input: NomSpan) -> IResult<NomSpan, u64, BindParserError>
     map_err(alt((base16, base10)), BindParserError::NumericLiteral)(input)
 }
 
+// Parses a numeric literal that may exceed u64. Values that fit in a u64 are returned as
+// Value::NumericLiteral so that existing rules compile as before.
+pub fn wide_numeric_literal(input: NomSpan) -> IResult<NomSpan, Value, BindParserError> {
+    let base10 = map_res(digit1, |s: NomSpan| u128::from_str_radix(s.fragment(), 10));
+    let base16 = map_res(preceded(tag("0x"), hex_digit1), |s: NomSpan| {
+        u128::from_str_radix(s.fragment(), 16)
+    });
+    let to_value = |n: u128| match u64::try_from(n) {
+        Ok(n) => Value::NumericLiteral(n),
+        Err(_) => Value::U128Literal(n),
+    };
+    map_err(map(alt((base16, base10)), to_value), BindParserError::NumericLiteral)(input)
+}
+
 pub fn bool_literal(input: NomSpan) -> IResult<NomSpan, bool, BindParserError> {
     let true_ = value(true, tag("true"));
     let false_ = value(false, tag("false"));
@@ -187,7 +205,7 @@ pub fn compound_identifier(
 
 pub fn condition_value(input: NomSpan) -> IResult<NomSpan, Value, BindParserError> {
     let string = map(ws(string_literal), Value::StringLiteral);
-    let number = map(ws(numeric_literal), Value::NumericLiteral);
+    let number = ws(wide_numeric_literal);
     let boolean = map(ws(bool_literal), Value::BoolLiteral);
     let identifer = map(ws(compound_identifier), Value::Identifier);
 
@@ -606,6 +624,31 @@ pub mod test {
             check_result(condition_value(NomSpan::new("123")), "", Value::NumericLiteral(123));
         }
 
+        #[test]
+        fn u128_number() {
+            // Values that fit in a u64 are still numeric literals.
+            check_result(
+                condition_value(NomSpan::new("0xffffffffffffffff")),
+                "",
+                Value::NumericLiteral(u64::MAX),
+            );
+            check_result(
+                condition_value(NomSpan::new("0x10000000000000000")),
+                "",
+                Value::U128Literal(1 << 64),
+            );
+            check_result(
+                condition_value(NomSpan::new("0xfffffffffffffffffffffffffffffffe")),
+                "",
+                Value::U128Literal(u128::MAX - 1),
+            );
+            check_result(
+                condition_value(NomSpan::new("340282366920938463463374607431768211455")),
+                "",
+                Value::U128Literal(u128::MAX),
+            );
+        }
+
         #[test]
         fn identifier() {
             check_result(
@@ -6,3 +6,4 @@ pub mod bind_composite;
 pub mod bind_library;
 pub mod bind_rules;
 pub mod common;
+pub mod syntax;
@@ -0,0 +1,118 @@
+// Copyright 2022 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use crate::parser::bind_composite;
+use crate::parser::bind_rules;
+use crate::parser::common::{BindParserError, Span};
+use std::convert::TryFrom;
+
+/// A syntax error and the location in the source where the parser gave up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic<'a> {
+    pub error: BindParserError,
+    // None if the error doesn't refer to a location in the source.
+    pub span: Option<Span<'a>>,
+}
+
+/// Checks that the source is syntactically valid bind rules or composite bind rules. Unlike
+/// compiling, this doesn't read the libraries named by `using` statements or resolve any
+/// identifiers, so it is fast enough to run on every edit. The parser stops at the first error,
+/// so at most one diagnostic is returned.
+pub fn check_syntax<'a>(source: &'a str) -> Result<(), Vec<Diagnostic<'a>>> {
+    let composite_error = match bind_composite::Ast::try_from(source) {
+        Ok(_) => return Ok(()),
+        Err(error) => error,
+    };
+    let error = match bind_rules::Ast::try_from(source) {
+        Ok(_) => return Ok(()),
+        // Only report the composite parser's error if the source looks like it was meant to be
+        // composite bind rules.
+        Err(error) => match composite_error {
+            BindParserError::CompositeKeyword(_) => error,
+            composite_error => composite_error,
+        },
+    };
+
+    let span = error_input(&error).and_then(|remaining| locate(source, remaining));
+    Err(vec![Diagnostic { error, span }])
+}
+
+// Parser errors hold the input that remained when the error occurred.
+fn error_input(error: &BindParserError) -> Option<&str> {
+    match error {
+        BindParserError::Type(input)
+        | BindParserError::StringLiteral(input)
+        | BindParserError::NumericLiteral(input)
+        | BindParserError::BoolLiteral(input)
+        | BindParserError::Identifier(input)
+        | BindParserError::Semicolon(input)
+        | BindParserError::Assignment(input)
+        | BindParserError::ListStart(input)
+        | BindParserError::ListEnd(input)
+        | BindParserError::ListSeparator(input)
+        | BindParserError::LibraryKeyword(input)
+        | BindParserError::UsingKeyword(input)
+        | BindParserError::AsKeyword(input)
+        | BindParserError::IfBlockStart(input)
+        | BindParserError::IfBlockEnd(input)
+        | BindParserError::IfKeyword(input)
+        | BindParserError::ElseKeyword(input)
+        | BindParserError::ConditionOp(input)
+        | BindParserError::ConditionValue(input)
+        | BindParserError::AcceptKeyword(input)
+        | BindParserError::TrueKeyword(input)
+        | BindParserError::FalseKeyword(input)
+        | BindParserError::PreferKeyword(input)
+        | BindParserError::NoStatements(input)
+        | BindParserError::NoNodes(input)
+        | BindParserError::Eof(input)
+        | BindParserError::CompositeKeyword(input)
+        | BindParserError::NodeKeyword(input)
+        | BindParserError::PrimaryKeyword(input)
+        | BindParserError::OnePrimaryNode(input)
+        | BindParserError::InvalidNodeName(input)
+        | BindParserError::DuplicateNodeName(input)
+        | BindParserError::Unknown(input, _) => Some(input),
+        BindParserError::UnterminatedComment => None,
+    }
+}
+
+// Finds the span of the line starting at the remaining input, which is a suffix of the source.
+fn locate<'a>(source: &'a str, remaining: &str) -> Option<Span<'a>> {
+    if !source.ends_with(remaining) {
+        return None;
+    }
+
+    let offset = source.len() - remaining.len();
+    let line = source[..offset].matches('\n').count() as u32 + 1;
+    let fragment = source[offset..].lines().next().unwrap_or("");
+    Some(Span { offset, line, fragment })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn valid_rules() {
+        // The library isn't available, but that doesn't matter for checking the syntax.
+        let source = "using missing.library;\nmissing.library.KEY == 1;\n";
+        assert_eq!(check_syntax(source), Ok(()));
+
+        let source = "composite c;\nprimary node \"a\" { true; }\nnode \"b\" { true; }\n";
+        assert_eq!(check_syntax(source), Ok(()));
+    }
+
+    #[test]
+    fn syntax_error() {
+        let source = "using missing.library;\nmissing.library.KEY = 1;\n";
+        assert_eq!(
+            check_syntax(source),
+            Err(vec![Diagnostic {
+                error: BindParserError::TrueKeyword("missing.library.KEY = 1;\n".to_string()),
+                span: Some(Span { offset: 23, line: 2, fragment: "missing.library.KEY = 1;" }),
+            }])
+        );
+    }
+}
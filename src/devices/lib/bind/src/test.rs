@@ -71,8 +71,9 @@ impl TestSuite {
     fn run(&self, rules: &str, libraries: &[String]) -> Result<bool, TestError> {
         match &self.specs {
             TestSpec::Bind(test_specs) => {
-                let bind_rules = compiler::compile_bind(rules, libraries, false, false, false)
-                    .map_err(TestError::CompilerError)?;
+                let bind_rules =
+                    compiler::compile_bind(rules, libraries, false, false, false, &mut vec![])
+                        .map_err(TestError::CompilerError)?;
                 run_bind_test_specs(test_specs, &bind_rules.symbol_table, &bind_rules.instructions)
             }
             TestSpec::CompositeBind(test_specs) => {
@@ -155,8 +156,9 @@ fn run_composite_bind_test_specs(
     rules: &str,
     libraries: &[String],
 ) -> Result<bool, TestError> {
-    let composite_bind = compiler::compile_bind_composite(rules, libraries, false, false)
-        .map_err(TestError::CompilerError)?;
+    let composite_bind =
+        compiler::compile_bind_composite(rules, libraries, false, false, &mut vec![])
+            .map_err(TestError::CompilerError)?;
 
     // Map composite bind rules by node name.
     let mut node_map: HashMap<String, Vec<SymbolicInstructionInfo>> = HashMap::new();
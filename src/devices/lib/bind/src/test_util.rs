@@ -0,0 +1,20 @@
+// Copyright 2022 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+// Helpers for building bytecode by hand in unit tests, both of this crate and of tools that
+// consume bind rules.
+
+// The "BIND" magic number followed by version 2.
+pub const BIND_HEADER: [u8; 8] = [0x42, 0x49, 0x4E, 0x44, 0x02, 0, 0, 0];
+
+pub fn append_section_header(bytecode: &mut Vec<u8>, magic_num: u32, sz: u32) {
+    bytecode.extend_from_slice(&magic_num.to_be_bytes());
+    bytecode.extend_from_slice(&sz.to_le_bytes());
+}
+
+pub fn append_symbol(bytecode: &mut Vec<u8>, key: u32, value: &str) {
+    bytecode.extend_from_slice(&key.to_le_bytes());
+    bytecode.extend_from_slice(value.as_bytes());
+    bytecode.push(0);
+}
@@ -628,6 +628,7 @@ pub struct FidlIr {
     // A set of all FIDL types used as a message payload.  Unlike the public members of this struct,
     // this member is an internal value, generated when the build() method is called. It serves to
     // cache lookups for future use.
+    #[serde(skip)]
     message_body_type_names: Option<HashSet<CompoundIdentifier>>,
 }
 
@@ -6,13 +6,16 @@ use {
     super::{
         util::{
             array_bounds, for_banjo_transport, get_base_type_from_alias, get_declarations,
-            get_doc_comment, is_derive_debug, is_namespaced, name_buffer, name_size, not_callback,
-            primitive_type_to_c_str, to_c_name, Decl, ProtocolType,
+            get_doxygen_doc_comment, get_library_version, get_wrapped_doc_comment, integer_range,
+            is_derive_debug, is_namespaced, name_buffer, name_size, non_decimal_literal,
+            not_callback, ordinal_comment, primitive_type_to_c_str, to_c_name,
+            validate_enum_members, with_newline_style, with_trailing_newline, Decl, ProtocolType,
         },
-        Backend,
+        Backend, CodegenOptions, SymbolIndexEntry,
     },
     anyhow::{anyhow, Context, Error},
     fidl_ir_lib::fidl::*,
+    std::collections::HashMap,
     std::io,
     std::iter,
 };
@@ -21,22 +24,46 @@ pub struct CBackend<'a, W: io::Write> {
     // Note: a mutable reference is used here instead of an owned object in
     // order to facilitate testing.
     w: &'a mut W,
+    options: CodegenOptions,
 }
 
 impl<'a, W: io::Write> CBackend<'a, W> {
     pub fn new(w: &'a mut W) -> Self {
-        CBackend { w }
+        Self::with_options(w, CodegenOptions::default())
     }
+
+    pub fn with_options(w: &'a mut W, options: CodegenOptions) -> Self {
+        CBackend { w, options }
+    }
+}
+
+/// Returns the C name of a declaration. Declarations from the library being
+/// generated are prefixed with the symbol prefix, while declarations from
+/// dependencies are left alone as they come from separately generated headers.
+fn decl_c_name(id: &CompoundIdentifier, ir: &FidlIr, options: &CodegenOptions) -> String {
+    let name = to_c_name(id.get_name());
+    if id.0.split("/").next() == Some(ir.name.0.as_str()) {
+        format!("{}{}", options.symbol_prefix, name)
+    } else {
+        name
+    }
+}
+
+/// Returns the suffix of the macro defining an enum or bits member, which
+/// follows the declaration's upper-cased C name. Unlike declaration names,
+/// member names aren't converted to snake case, so `kOne` becomes `KONE`.
+fn member_c_name(name: &Identifier) -> String {
+    name.0.trim().to_uppercase()
 }
 
 fn integer_type_to_c_str(ty: &IntegerType) -> Result<String, Error> {
     primitive_type_to_c_str(&ty.to_primitive())
 }
 
-fn type_to_c_str(ty: &Type, ir: &FidlIr) -> Result<String, Error> {
+fn type_to_c_str(ty: &Type, ir: &FidlIr, options: &CodegenOptions) -> Result<String, Error> {
     match ty {
-        Type::Array { ref element_type, .. } => type_to_c_str(element_type, ir),
-        Type::Vector { ref element_type, .. } => type_to_c_str(element_type, ir),
+        Type::Array { ref element_type, .. } => type_to_c_str(element_type, ir, options),
+        Type::Vector { ref element_type, .. } => type_to_c_str(element_type, ir, options),
         Type::Str { .. } => Ok(String::from("char*")),
         Type::Primitive { ref subtype } => primitive_type_to_c_str(subtype),
         Type::Identifier { identifier, .. } => match ir
@@ -44,10 +71,10 @@ fn type_to_c_str(ty: &Type, ir: &FidlIr) -> Result<String, Error> {
             .expect(&format!("Could not find declaration for {:?}", identifier))
         {
             Declaration::Struct | Declaration::Table | Declaration::Union | Declaration::Enum => {
-                Ok(format!("{}_t", to_c_name(&identifier.get_name())))
+                Ok(format!("{}_t", decl_c_name(identifier, ir, options)))
             }
             Declaration::Interface => {
-                let c_name = to_c_name(&identifier.get_name());
+                let c_name = decl_c_name(identifier, ir, options);
                 if not_callback(identifier, ir)? {
                     return Ok(format!("{}_protocol_t", c_name));
                 } else {
@@ -61,9 +88,13 @@ fn type_to_c_str(ty: &Type, ir: &FidlIr) -> Result<String, Error> {
     }
 }
 
-fn protocol_to_ops_c_str(id: &CompoundIdentifier, ir: &FidlIr) -> Result<String, Error> {
+fn protocol_to_ops_c_str(
+    id: &CompoundIdentifier,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<String, Error> {
     if ir.is_protocol(id) {
-        return Ok(to_c_name(id.get_name()) + "_protocol_ops_t");
+        return Ok(decl_c_name(id, ir, options) + "_protocol_ops_t");
     }
     Err(anyhow!("Identifier does not represent a protocol: {:?}", id))
 }
@@ -76,15 +107,136 @@ fn integer_constant_to_c_str(
     constant_to_c_str(&ty.to_type(), constant, ir)
 }
 
+/// Returns a `{c_name}_{suffix}()` function mapping each value in `cases`,
+/// given as the name of its #define, to the associated string.
+fn enum_to_str_func(
+    c_name: &str,
+    suffix: &str,
+    param: &str,
+    cases: &[(String, String)],
+    unknown: &str,
+) -> String {
+    let guard = format!("FUNC_{}_{}_", c_name.to_uppercase(), suffix.to_uppercase());
+    let mut lines = vec![
+        // #ifndef used since fidlgen_banjo does not strictly generate code
+        // once for each declaration.
+        format!("#ifndef {}", guard),
+        format!("#define {}", guard),
+        format!(
+            "static inline const char* {c_name}_{suffix}({c_name}_t {param}) {{",
+            c_name = c_name,
+            suffix = suffix,
+            param = param
+        ),
+        format!("  switch ({}) {{", param),
+    ];
+    for (define, string) in cases {
+        lines.push(format!("    case {}:\n      return \"{}\";", define, string));
+    }
+    lines.push("  }".to_string());
+    lines.push(format!("  return \"{}\";", unknown));
+    lines.push("}".to_string());
+    lines.push("#endif".to_string());
+    lines.join("\n")
+}
+
+/// Returns whether values of `ty` have no padding or indirection, so arrays
+/// of them can be compared with memcmp().
+fn is_scalar_type(ty: &Type, ir: &FidlIr) -> Result<bool, Error> {
+    Ok(match ty {
+        Type::Primitive { .. } | Type::Handle { .. } => true,
+        Type::Array { element_type, .. } => is_scalar_type(element_type, ir)?,
+        Type::Identifier { identifier, nullable: false } => {
+            matches!(ir.get_declaration(identifier)?, Declaration::Enum | Declaration::Bits)
+        }
+        _ => false,
+    })
+}
+
+/// Returns a C expression comparing the `name` fields of the structs `a` and
+/// `b`, or `None` if fields of type `ty` can't be compared by value.
+fn field_eq_expr(
+    name: &str,
+    ty: &Type,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<Option<String>, Error> {
+    if let Type::Identifier { identifier, nullable: false } = ty {
+        if let (Declaration::Struct, false) =
+            (ir.get_declaration(identifier)?, ir.is_external_decl(identifier)?)
+        {
+            let data = ir.get_struct(identifier)?;
+            return Ok(struct_eq_func(data, ir, options)?.map(|_| {
+                format!(
+                    "{}_eq(&a->{name}, &b->{name})",
+                    decl_c_name(identifier, ir, options),
+                    name = name
+                )
+            }));
+        }
+    }
+    Ok(match ty {
+        Type::Str { maybe_element_count: Some(_), .. } => {
+            Some(format!("strncmp(a->{name}, b->{name}, sizeof(a->{name})) == 0", name = name))
+        }
+        Type::Array { .. } if is_scalar_type(ty, ir)? => {
+            Some(format!("memcmp(a->{name}, b->{name}, sizeof(a->{name})) == 0", name = name))
+        }
+        _ if is_scalar_type(ty, ir)? => Some(format!("a->{name} == b->{name}", name = name)),
+        _ => None,
+    })
+}
+
+/// Returns a `{c_name}_eq()` function comparing two structs field by field,
+/// or `None` if some field can't be compared by value.
+fn struct_eq_func(
+    data: &Struct,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<Option<String>, Error> {
+    let preserve_names = data.maybe_attributes.has("PreserveCNames");
+    let mut comparisons = vec![];
+    for member in &data.members {
+        let name = if preserve_names { member.name.0.clone() } else { to_c_name(&member.name.0) };
+        match field_eq_expr(&name, &member._type, ir, options)? {
+            Some(comparison) => comparisons.push(comparison),
+            None => return Ok(None),
+        }
+    }
+    let c_name = decl_c_name(&data.name, ir, options);
+    let guard = format!("FUNC_{}_EQ_", c_name.to_uppercase());
+    let body = if comparisons.is_empty() {
+        "true".to_string()
+    } else {
+        comparisons.join(" &&\n         ")
+    };
+    Ok(Some(
+        [
+            format!("#ifndef {}", guard),
+            format!("#define {}", guard),
+            format!(
+                "static inline bool {c_name}_eq(const {c_name}_t* a, const {c_name}_t* b) {{",
+                c_name = c_name
+            ),
+            format!("  return {};", body),
+            "}".to_string(),
+            "#endif".to_string(),
+        ]
+        .join("\n"),
+    ))
+}
+
 fn constant_to_c_str(ty: &Type, constant: &Constant, ir: &FidlIr) -> Result<String, Error> {
     let value = match constant {
-        Constant::Identifier { value, .. } => value,
+        Constant::Identifier { identifier, value, .. } => {
+            non_decimal_literal(identifier, ir).unwrap_or(value)
+        }
         Constant::Literal { expression, .. } => expression,
         Constant::BinaryOperator { value, .. } => value,
     };
     match ty {
         Type::Primitive { subtype } => match subtype {
-            PrimitiveSubtype::Bool => Ok(value.clone()),
+            PrimitiveSubtype::Bool => Ok(value.to_string()),
             PrimitiveSubtype::Int8 => Ok(String::from(format!("INT8_C({})", value))),
             PrimitiveSubtype::Int16 => Ok(String::from(format!("INT16_C({})", value))),
             PrimitiveSubtype::Int32 => Ok(String::from(format!("INT32_C({})", value))),
@@ -113,6 +265,91 @@ fn constant_to_c_str(ty: &Type, constant: &Constant, ir: &FidlIr) -> Result<Stri
     }
 }
 
+// Returns the doc comment in the style selected by the options, or nothing if doc comments are
+// turned off.
+fn doc_comment(
+    maybe_attributes: &Option<Vec<Attribute>>,
+    indent: &str,
+    options: &CodegenOptions,
+) -> String {
+    if !options.doc_comments {
+        "".to_string()
+    } else if options.doxygen_doc_comments {
+        get_doxygen_doc_comment(maybe_attributes, indent, options.doc_comment_column)
+    } else {
+        get_wrapped_doc_comment(maybe_attributes, indent, options.doc_comment_column)
+    }
+}
+
+// Identifiers that are reserved in C++ but not in C, so fields named after them can't be used from
+// C++.
+const CPLUSPLUS_KEYWORDS: &[&str] = &[
+    "alignas",
+    "alignof",
+    "and",
+    "and_eq",
+    "asm",
+    "bitand",
+    "bitor",
+    "bool",
+    "catch",
+    "char8_t",
+    "char16_t",
+    "char32_t",
+    "class",
+    "co_await",
+    "co_return",
+    "co_yield",
+    "compl",
+    "concept",
+    "const_cast",
+    "consteval",
+    "constexpr",
+    "constinit",
+    "decltype",
+    "delete",
+    "dynamic_cast",
+    "explicit",
+    "export",
+    "false",
+    "friend",
+    "mutable",
+    "namespace",
+    "new",
+    "noexcept",
+    "not",
+    "not_eq",
+    "nullptr",
+    "operator",
+    "or",
+    "or_eq",
+    "private",
+    "protected",
+    "public",
+    "reinterpret_cast",
+    "requires",
+    "static_assert",
+    "static_cast",
+    "template",
+    "this",
+    "thread_local",
+    "throw",
+    "true",
+    "try",
+    "typeid",
+    "typename",
+    "using",
+    "virtual",
+    "wchar_t",
+    "xor",
+    "xor_eq",
+];
+
+// Returns one level of indentation.
+fn indent(options: &CodegenOptions) -> String {
+    " ".repeat(options.indent_width)
+}
+
 fn struct_attrs_to_c_str(maybe_attributes: &Option<Vec<Attribute>>) -> String {
     if let Some(attributes) = maybe_attributes {
         attributes
@@ -128,6 +365,15 @@ fn struct_attrs_to_c_str(maybe_attributes: &Option<Vec<Attribute>>) -> String {
     }
 }
 
+// Returns a comment describing the width, signedness and range of an integer field.
+fn field_width_comment(ty: &Type) -> Option<String> {
+    let (name, min, max) = match ty {
+        Type::Primitive { subtype } => integer_range(subtype)?,
+        _ => return None,
+    };
+    Some(format!("// {}: {}..{}", name, min, max))
+}
+
 fn field_to_c_str(
     maybe_attributes: &Option<Vec<Attribute>>,
     ty: &Type,
@@ -136,11 +382,15 @@ fn field_to_c_str(
     preserve_names: bool,
     alias: &Option<FieldTypeConstructor>,
     ir: &FidlIr,
+    options: &CodegenOptions,
 ) -> Result<String, Error> {
     let mut accum = String::new();
 
-    accum.push_str(get_doc_comment(maybe_attributes, 1).as_str());
+    accum.push_str(doc_comment(maybe_attributes, indent, options).as_str());
     let c_name = if preserve_names { String::from(&ident.0) } else { to_c_name(&ident.0) };
+    if options.cplusplus_compatible && CPLUSPLUS_KEYWORDS.contains(&c_name.as_str()) {
+        return Err(anyhow!("Field name {} is a C++ keyword", c_name));
+    }
     if let Some(arg_type) = get_base_type_from_alias(&alias.as_ref().map(|t| &t.name)) {
         accum.push_str(
             format!("{indent}{ty} {c_name};", indent = indent, ty = arg_type, c_name = c_name)
@@ -149,7 +399,7 @@ fn field_to_c_str(
         return Ok(accum);
     }
     let prefix = if maybe_attributes.has("Mutable") { "" } else { "const " };
-    let ty_name = type_to_c_str(&ty, ir)?;
+    let ty_name = type_to_c_str(&ty, ir, options)?;
     match ty {
         Type::Str { maybe_element_count, .. } => {
             if let Some(count) = maybe_element_count {
@@ -224,7 +474,11 @@ fn field_to_c_str(
     Ok(accum)
 }
 
-fn get_first_param(method: &Method, ir: &FidlIr) -> Result<(bool, String), Error> {
+fn get_first_param(
+    method: &Method,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<(bool, String), Error> {
     if let Some(response) = &method.response_parameters(ir)? {
         if let Some(param) = response.get(0) {
             if let Some(arg_type) = get_base_type_from_alias(
@@ -233,14 +487,19 @@ fn get_first_param(method: &Method, ir: &FidlIr) -> Result<(bool, String), Error
                 return Ok((true, arg_type));
             }
             if param._type.is_primitive(ir)? {
-                return Ok((true, type_to_c_str(&param._type, ir)?));
+                return Ok((true, type_to_c_str(&param._type, ir, options)?));
             }
         }
     }
     Ok((false, "void".to_string()))
 }
 
-fn get_in_params(m: &Method, transform: bool, ir: &FidlIr) -> Result<Vec<String>, Error> {
+fn get_in_params(
+    m: &Method,
+    transform: bool,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<Vec<String>, Error> {
     m.request_parameters(ir)?
         .as_ref()
         .unwrap()
@@ -252,7 +511,7 @@ fn get_in_params(m: &Method, transform: bool, ir: &FidlIr) -> Result<Vec<String>
             ) {
                 return Ok(format!("{} {}", arg_type, c_name));
             }
-            let ty_name = type_to_c_str(&param._type, ir)?;
+            let ty_name = type_to_c_str(&param._type, ir, options)?;
             match &param._type {
                 Type::Identifier { identifier, .. } => {
                     if identifier.is_base_type() {
@@ -261,7 +520,8 @@ fn get_in_params(m: &Method, transform: bool, ir: &FidlIr) -> Result<Vec<String>
                     match ir.get_declaration(identifier).unwrap() {
                         Declaration::Interface => {
                             if transform && not_callback(identifier, ir)? {
-                                let ty_name = protocol_to_ops_c_str(identifier, ir).unwrap();
+                                let ty_name =
+                                    protocol_to_ops_c_str(identifier, ir, options).unwrap();
                                 Ok(format!(
                                     "void* {name}_ctx, {ty_name}* {name}_ops",
                                     ty_name = ty_name,
@@ -312,8 +572,12 @@ fn get_in_params(m: &Method, transform: bool, ir: &FidlIr) -> Result<Vec<String>
         .collect()
 }
 
-fn get_out_params(name: &str, m: &Method, ir: &FidlIr) -> Result<(Vec<String>, String), Error> {
-    let protocol_name = to_c_name(name);
+fn get_out_params(
+    protocol_name: &str,
+    m: &Method,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<(Vec<String>, String), Error> {
     let method_name = to_c_name(&m.name.0);
     if m.maybe_attributes.has("Async") {
         return Ok((
@@ -329,7 +593,7 @@ fn get_out_params(name: &str, m: &Method, ir: &FidlIr) -> Result<(Vec<String>, S
         ));
     }
 
-    let (skip, return_param) = get_first_param(m, ir)?;
+    let (skip, return_param) = get_first_param(m, ir, options)?;
     let skip_amt = if skip { 1 } else { 0 };
 
     Ok((
@@ -343,7 +607,7 @@ fn get_out_params(name: &str, m: &Method, ir: &FidlIr) -> Result<(Vec<String>, S
                     ) {
                         return format!("{}* out_{}", arg_type, c_name);
                     }
-                    let ty_name = type_to_c_str(&param._type, ir).unwrap();
+                    let ty_name = type_to_c_str(&param._type, ir, options).unwrap();
                     match &param._type {
                         Type::Identifier { nullable, .. } => {
                             let nullable_str = if *nullable { "*" } else { "" };
@@ -404,12 +668,16 @@ fn get_in_args(m: &Method, _ir: &FidlIr) -> Result<Vec<String>, Error> {
     Ok(Vec::new())
 }
 
-fn get_out_args(m: &Method, ir: &FidlIr) -> Result<(Vec<String>, bool), Error> {
+fn get_out_args(
+    m: &Method,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<(Vec<String>, bool), Error> {
     if m.maybe_attributes.has("Async") {
         return Ok((vec!["callback".to_string(), "cookie".to_string()], false));
     }
 
-    let (skip, _) = get_first_param(m, ir)?;
+    let (skip, _) = get_first_param(m, ir, options)?;
     let skip_amt = if skip { 1 } else { 0 };
     Ok((
         m.response_parameters(ir)?.as_ref().map_or(Vec::new(), |response| {
@@ -452,31 +720,51 @@ fn get_out_args(m: &Method, ir: &FidlIr) -> Result<(Vec<String>, bool), Error> {
 
 impl<'a, W: io::Write> CBackend<'a, W> {
     fn codegen_enum_decl(&self, data: &Enum, ir: &FidlIr) -> Result<String, Error> {
-        let name = &data.name.get_name();
-        let c_name_lowercase = to_c_name(&name);
-        let c_name_uppercase = to_c_name(&name).to_uppercase();
+        validate_enum_members(data)?;
+        let c_name_lowercase = decl_c_name(&data.name, ir, &self.options);
+        let c_name_uppercase = c_name_lowercase.to_uppercase();
 
         struct EnumParts {
+            name: String,
             v_name: String,
             c_size: String,
+            doc: String,
         }
         let enum_parts_list = data
             .members
             .iter()
             .map(|v| {
                 Ok(EnumParts {
-                    v_name: v.name.0.to_uppercase().trim().to_string(),
+                    name: v.name.0.trim().to_string(),
+                    v_name: member_c_name(&v.name),
                     c_size: integer_constant_to_c_str(&data._type, &v.value, ir)?,
+                    doc: doc_comment(&v.maybe_attributes, "", &self.options),
                 })
             })
             .collect::<Result<Vec<_>, Error>>()?;
 
+        // Members whose names only differ in case would be #defined twice.
+        let mut seen: HashMap<&str, &str> = HashMap::new();
+        for enum_parts in &enum_parts_list {
+            if let Some(other) = seen.insert(&enum_parts.v_name, &enum_parts.name) {
+                return Err(anyhow!(
+                    "Members {} and {} of enum {} are both emitted as {}_{}",
+                    other,
+                    enum_parts.name,
+                    data.name.0,
+                    c_name_uppercase,
+                    enum_parts.v_name
+                ));
+            }
+        }
+
         // #define each enum value
         let enum_defines = enum_parts_list
             .iter()
             .map(|enum_parts| {
                 Ok(format!(
-                    "#define {c_name}_{v_name} {c_size}",
+                    "{doc}#define {c_name}_{v_name} {c_size}",
+                    doc = enum_parts.doc,
                     c_name = c_name_uppercase,
                     v_name = enum_parts.v_name,
                     c_size = enum_parts.c_size,
@@ -486,57 +774,76 @@ impl<'a, W: io::Write> CBackend<'a, W> {
             .join("\n");
 
         let declarations = format!(
-            "typedef {ty} {c_name}_t;\n{enum_defines}",
+            "{doc}typedef {ty} {c_name}_t;\n{enum_defines}",
+            doc = doc_comment(&data.maybe_attributes, "", &self.options),
             c_name = c_name_lowercase,
             ty = integer_type_to_c_str(&data._type)?,
             enum_defines = enum_defines,
         );
 
-        if !is_derive_debug(&data.maybe_attributes)? {
-            return Ok(declarations);
+        let mut accum = vec![declarations];
+
+        // Define {C_NAME}_COUNT and list every member in {c_name}_values[] so
+        // that consumers can iterate over the valid values.
+        if self.options.enum_values {
+            accum.push(format!(
+                "#define {C_NAME}_COUNT {count}\n\
+                 static const {c_name}_t {c_name}_values[] = {{ {values} }};",
+                C_NAME = c_name_uppercase,
+                c_name = c_name_lowercase,
+                count = enum_parts_list.len(),
+                values = enum_parts_list
+                    .iter()
+                    .map(|enum_parts| format!("{}_{}", c_name_uppercase, enum_parts.v_name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+        }
+
+        // List the name of every member in {c_name}_names[], in the same order
+        // as {c_name}_values[]. Values may have gaps, so this is indexed by the
+        // member's position rather than by its value.
+        if self.options.enum_names {
+            accum.push(format!(
+                "static const char* const {c_name}_names[] = {{ {names} }};",
+                c_name = c_name_lowercase,
+                names = enum_parts_list
+                    .iter()
+                    .map(|enum_parts| format!("\"{}\"", enum_parts.name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
         }
 
         // Define {c_name}_to_str() helper function to translate enum values
         // into strings.
-        let enum_to_str_prologue = vec![
-            // #ifndef used since fidlgen_banjo does not strictly generate code
-            // once for each declaration.
-            format!("#ifndef FUNC_{c_name}_TO_STR_", c_name = c_name_uppercase),
-            format!("#define FUNC_{c_name}_TO_STR_", c_name = c_name_uppercase),
-            format!(
-                "static inline const char* {c_name}_to_str({c_name}_t value) {{",
-                c_name = c_name_lowercase
-            ),
-            format!("  switch (value) {{"),
-        ];
-        let mut enum_to_str_body = enum_parts_list
-            .iter()
-            .map(|enum_parts| {
-                Ok(format!(
-                    "    case {c_name}_{v_name}:\n      return \"{c_name}_{v_name}\";",
-                    c_name = c_name_uppercase,
-                    v_name = enum_parts.v_name,
-                ))
-            })
-            .collect::<Result<Vec<_>, Error>>()?;
-        let mut enum_to_str_epilogue = vec![
-            format!("  }}"),
-            format!("  return \"UNKNOWN\";"),
-            format!("}}"),
-            format!("#endif"),
-        ];
-        let mut enum_to_str = enum_to_str_prologue;
-        enum_to_str.append(&mut enum_to_str_body);
-        enum_to_str.append(&mut enum_to_str_epilogue);
-        let enum_to_str = enum_to_str.join("\n");
+        if is_derive_debug(&data.maybe_attributes)? {
+            let cases = enum_parts_list
+                .iter()
+                .map(|enum_parts| {
+                    let define = format!("{}_{}", c_name_uppercase, enum_parts.v_name);
+                    (define.clone(), define)
+                })
+                .collect::<Vec<_>>();
+            accum.push(enum_to_str_func(&c_name_lowercase, "to_str", "value", &cases, "UNKNOWN"));
+        }
 
-        Ok(format!("{}\n{}", declarations, enum_to_str))
-    }
+        // Define {c_name}_to_string() to translate enum values into member
+        // names for logging.
+        if self.options.enum_to_string {
+            let cases = enum_parts_list
+                .iter()
+                .map(|enum_parts| {
+                    (format!("{}_{}", c_name_uppercase, enum_parts.v_name), enum_parts.name.clone())
+                })
+                .collect::<Vec<_>>();
+            accum.push(enum_to_str_func(&c_name_lowercase, "to_string", "v", &cases, "(unknown)"));
+        }
 
-    fn codegen_constant_decl(&self, data: &Const, ir: &FidlIr) -> Result<String, Error> {
-        let mut accum = String::new();
-        accum.push_str(get_doc_comment(&data.maybe_attributes, 0).as_str());
+        Ok(accum.join("\n"))
+    }
 
+    fn constant_c_name(&self, data: &Const, ir: &FidlIr) -> Result<String, Error> {
         let name = data.name.get_name().to_string();
         let namespaced = is_namespaced(&data.maybe_attributes)
             .context(format!("Looking for namespaced attribute on constant {}", name))?;
@@ -545,6 +852,14 @@ impl<'a, W: io::Write> CBackend<'a, W> {
         } else {
             name
         };
+        Ok(format!("{}{}", self.options.symbol_prefix.to_uppercase(), name))
+    }
+
+    fn codegen_constant_decl(&self, data: &Const, ir: &FidlIr) -> Result<String, Error> {
+        let mut accum = String::new();
+        accum.push_str(doc_comment(&data.maybe_attributes, "", &self.options).as_str());
+
+        let name = self.constant_c_name(data, ir)?;
 
         accum.push_str(
             format!(
@@ -558,9 +873,8 @@ impl<'a, W: io::Write> CBackend<'a, W> {
     }
 
     fn codegen_bits_decl(&self, data: &Bits, ir: &FidlIr) -> Result<String, Error> {
-        let name = &data.name.get_name();
-        let c_name_lowercase = to_c_name(&name);
-        let c_name_uppercase = to_c_name(&name).to_uppercase();
+        let c_name_lowercase = decl_c_name(&data.name, ir, &self.options);
+        let c_name_uppercase = c_name_lowercase.to_uppercase();
 
         struct BitsParts {
             v_name: String,
@@ -571,7 +885,7 @@ impl<'a, W: io::Write> CBackend<'a, W> {
             .iter()
             .map(|v| {
                 Ok(BitsParts {
-                    v_name: v.name.0.to_uppercase().trim().to_string(),
+                    v_name: member_c_name(&v.name),
                     c_size: constant_to_c_str(&data._type, &v.value, ir)?,
                 })
             })
@@ -590,15 +904,18 @@ impl<'a, W: io::Write> CBackend<'a, W> {
         let declarations = format!(
             "typedef {ty} {c_name}_t;\n{bits_defines}",
             c_name = c_name_lowercase,
-            ty = type_to_c_str(&data._type, ir)?,
+            ty = type_to_c_str(&data._type, ir, &self.options)?,
             bits_defines = bits_defines,
         );
 
         Ok(declarations)
     }
 
-    fn codegen_union_decl(&self, data: &Union) -> Result<String, Error> {
-        Ok(format!("typedef union {c_name} {c_name}_t;", c_name = to_c_name(&data.name.get_name())))
+    fn codegen_union_decl(&self, data: &Union, ir: &FidlIr) -> Result<String, Error> {
+        Ok(format!(
+            "typedef union {c_name} {c_name}_t;",
+            c_name = decl_c_name(&data.name, ir, &self.options)
+        ))
     }
 
     fn codegen_union_def(&self, data: &Union, ir: &FidlIr) -> Result<String, Error> {
@@ -616,10 +933,11 @@ impl<'a, W: io::Write> CBackend<'a, W> {
                             &f.maybe_attributes,
                             &ty,
                             &f.name.as_ref().unwrap(),
-                            "    ",
+                            &indent(&self.options),
                             false,
                             &f.experimental_maybe_from_type_alias,
                             ir,
+                            &self.options,
                         )),
                     }
                 } else {
@@ -629,11 +947,11 @@ impl<'a, W: io::Write> CBackend<'a, W> {
             .collect::<Result<Vec<_>, Error>>()?
             .join("\n");
         let mut accum = String::new();
-        accum.push_str(get_doc_comment(&data.maybe_attributes, 0).as_str());
+        accum.push_str(doc_comment(&data.maybe_attributes, "", &self.options).as_str());
         accum.push_str(
             format!(
                 include_str!("templates/c/struct.h"),
-                c_name = to_c_name(&data.name.get_name()),
+                c_name = decl_c_name(&data.name, ir, &self.options),
                 decl = "union",
                 attrs = if attrs.is_empty() { "".to_string() } else { format!(" {}", attrs) },
                 members = members
@@ -643,38 +961,51 @@ impl<'a, W: io::Write> CBackend<'a, W> {
         Ok(accum)
     }
 
-    fn codegen_struct_decl(&self, data: &Struct) -> Result<String, Error> {
+    fn codegen_struct_decl(&self, data: &Struct, ir: &FidlIr) -> Result<String, Error> {
         Ok(format!(
             "typedef struct {c_name} {c_name}_t;",
-            c_name = to_c_name(&data.name.get_name())
+            c_name = decl_c_name(&data.name, ir, &self.options)
         ))
     }
 
     fn codegen_struct_def(&self, data: &Struct, ir: &FidlIr) -> Result<String, Error> {
-        let attrs = struct_attrs_to_c_str(&data.maybe_attributes);
+        let mut attrs = struct_attrs_to_c_str(&data.maybe_attributes);
+        if self.options.bindgen_layout && !data.maybe_attributes.has("Packed") {
+            // bindgen turns the alignment into #[repr(align)], so the Rust layout doesn't depend
+            // on it inferring the alignment from the members.
+            let aligned = format!("__attribute__ ((aligned({})))", data.type_shape_v1.alignment.0);
+            attrs = if attrs.is_empty() { aligned } else { format!("{} {}", attrs, aligned) };
+        }
         let preserve_names = data.maybe_attributes.has("PreserveCNames");
         let members = data
             .members
             .iter()
             .map(|f| {
-                field_to_c_str(
+                let field = field_to_c_str(
                     &f.maybe_attributes,
                     &f._type,
                     &f.name,
-                    "    ",
+                    &indent(&self.options),
                     preserve_names,
                     &f.experimental_maybe_from_type_alias,
                     ir,
-                )
+                    &self.options,
+                )?;
+                match field_width_comment(&f._type) {
+                    Some(comment) if self.options.field_width_comments => {
+                        Ok(format!("{} {}", field, comment))
+                    }
+                    _ => Ok(field),
+                }
             })
             .collect::<Result<Vec<_>, Error>>()?
             .join("\n");
         let mut accum = String::new();
-        accum.push_str(get_doc_comment(&data.maybe_attributes, 0).as_str());
+        accum.push_str(doc_comment(&data.maybe_attributes, "", &self.options).as_str());
         accum.push_str(
             format!(
                 include_str!("templates/c/struct.h"),
-                c_name = to_c_name(&data.name.get_name()),
+                c_name = decl_c_name(&data.name, ir, &self.options),
                 decl = "struct",
                 attrs = if attrs.is_empty() { "".to_string() } else { format!(" {}", attrs) },
                 members = members
@@ -684,10 +1015,10 @@ impl<'a, W: io::Write> CBackend<'a, W> {
         Ok(accum)
     }
 
-    fn codegen_table_decl(&self, data: &Table) -> Result<String, Error> {
+    fn codegen_table_decl(&self, data: &Table, ir: &FidlIr) -> Result<String, Error> {
         Ok(format!(
             "typedef struct {c_name} {c_name}_t;",
-            c_name = to_c_name(&data.name.get_name())
+            c_name = decl_c_name(&data.name, ir, &self.options)
         ))
     }
 
@@ -704,20 +1035,21 @@ impl<'a, W: io::Write> CBackend<'a, W> {
                     &f.maybe_attributes,
                     &f._type.as_ref().expect(&format!("Missing type on table field {:?}", f)),
                     &f.name.as_ref().expect(&format!("Missing name on table field {:?}", f)),
-                    "    ",
+                    &indent(&self.options),
                     preserve_names,
                     &None,
                     ir,
+                    &self.options,
                 )
             })
             .collect::<Result<Vec<_>, Error>>()?
             .join("\n");
         let mut accum = String::new();
-        accum.push_str(get_doc_comment(&data.maybe_attributes, 0).as_str());
+        accum.push_str(doc_comment(&data.maybe_attributes, "", &self.options).as_str());
         accum.push_str(
             format!(
                 include_str!("templates/c/struct.h"),
-                c_name = to_c_name(&data.name.get_name()),
+                c_name = decl_c_name(&data.name, ir, &self.options),
                 decl = "struct",
                 attrs = if attrs.is_empty() { "".to_string() } else { format!(" {}", attrs) },
                 members = members
@@ -729,15 +1061,15 @@ impl<'a, W: io::Write> CBackend<'a, W> {
 
     fn codegen_protocol_def2(
         &self,
-        name: &str,
+        c_name: &str,
         methods: &Vec<Method>,
         ir: &FidlIr,
     ) -> Result<String, Error> {
         let fns = methods
             .iter()
             .map(|m| {
-                let (out_params, return_param) = get_out_params(name, &m, ir)?;
-                let in_params = get_in_params(&m, false, ir)?;
+                let (out_params, return_param) = get_out_params(&c_name, &m, ir, &self.options)?;
+                let in_params = get_in_params(&m, false, ir, &self.options)?;
 
                 let params = iter::once("void* ctx".to_string())
                     .chain(in_params)
@@ -745,7 +1077,8 @@ impl<'a, W: io::Write> CBackend<'a, W> {
                     .collect::<Vec<_>>()
                     .join(", ");
                 Ok(format!(
-                    "    {return_param} (*{fn_name})({params});",
+                    "{indent}{return_param} (*{fn_name})({params});",
+                    indent = indent(&self.options),
                     return_param = return_param,
                     params = params,
                     fn_name = to_c_name(&m.name.0)
@@ -753,7 +1086,7 @@ impl<'a, W: io::Write> CBackend<'a, W> {
             })
             .collect::<Result<Vec<_>, Error>>()?
             .join("\n");
-        Ok(format!(include_str!("templates/c/protocol_ops.h"), c_name = to_c_name(name), fns = fns))
+        Ok(format!(include_str!("templates/c/protocol_ops.h"), c_name = c_name, fns = fns))
     }
 
     fn codegen_protocol_helper(&self, data: &'a Interface, ir: &FidlIr) -> Result<String, Error> {
@@ -762,17 +1095,17 @@ impl<'a, W: io::Write> CBackend<'a, W> {
         {
             return Ok("".to_string());
         }
-        let name = data.name.get_name();
+        let c_name = decl_c_name(&data.name, ir, &self.options);
         data.methods
             .iter()
             .map(|m| {
                 let mut accum = String::new();
-                accum.push_str(get_doc_comment(&m.maybe_attributes, 0).as_str());
+                accum.push_str(doc_comment(&m.maybe_attributes, "", &self.options).as_str());
 
-                let (out_params, return_param) = get_out_params(name, &m, ir)?;
-                let in_params = get_in_params(&m, true, ir)?;
+                let (out_params, return_param) = get_out_params(&c_name, &m, ir, &self.options)?;
+                let in_params = get_in_params(&m, true, ir, &self.options)?;
 
-                let first_param = format!("const {}_protocol_t* proto", to_c_name(name));
+                let first_param = format!("const {}_protocol_t* proto", c_name);
 
                 let params = iter::once(first_param)
                     .chain(in_params)
@@ -785,13 +1118,13 @@ impl<'a, W: io::Write> CBackend<'a, W> {
                         "static inline {return_param} {protocol_name}_{fn_name}({params}) {{\n",
                         return_param = return_param,
                         params = params,
-                        protocol_name = to_c_name(name),
+                        protocol_name = c_name,
                         fn_name = to_c_name(&m.name.0)
                     )
                     .as_str(),
                 );
 
-                let (out_args, skip) = get_out_args(&m, ir)?;
+                let (out_args, skip) = get_out_args(&m, ir, &self.options)?;
                 let in_args = get_in_args(&m, ir)?;
 
                 if let Some(request) = &m.request_parameters(ir)? {
@@ -802,7 +1135,7 @@ impl<'a, W: io::Write> CBackend<'a, W> {
                                 if not_callback(identifier, ir).ok()? {
                                     return Some((
                                         to_c_name(&param.name.0),
-                                        type_to_c_str(&param._type, ir).unwrap(),
+                                        type_to_c_str(&param._type, ir, &self.options).unwrap(),
                                     ));
                                 }
                             }
@@ -813,6 +1146,7 @@ impl<'a, W: io::Write> CBackend<'a, W> {
                         accum.push_str(
                             format!(
                                 include_str!("templates/c/proto_transform.h"),
+                                indent = indent(&self.options),
                                 ty = ty,
                                 name = name
                             )
@@ -831,7 +1165,8 @@ impl<'a, W: io::Write> CBackend<'a, W> {
 
                 accum.push_str(
                     format!(
-                        "    {return_statement}proto->ops->{fn_name}({args});\n",
+                        "{indent}{return_statement}proto->ops->{fn_name}({args});\n",
+                        indent = indent(&self.options),
                         return_statement = return_statement,
                         args = args,
                         fn_name = to_c_name(&m.name.0)
@@ -849,17 +1184,18 @@ impl<'a, W: io::Write> CBackend<'a, W> {
         if !for_banjo_transport(&data.maybe_attributes) {
             return Ok("".to_string());
         }
-        let name = data.name.get_name();
+        let c_name = decl_c_name(&data.name, ir, &self.options);
         Ok(match ProtocolType::from(&data.maybe_attributes) {
             ProtocolType::Interface | ProtocolType::Protocol => format!(
                 include_str!("templates/c/protocol.h"),
-                protocol_name = to_c_name(name),
-                protocol_def = self.codegen_protocol_def2(name, &data.methods, ir)?,
+                indent = indent(&self.options),
+                protocol_name = c_name,
+                protocol_def = self.codegen_protocol_def2(&c_name, &data.methods, ir)?,
             ),
             ProtocolType::Callback => {
                 let m = data.methods.get(0).ok_or(anyhow!("callback has no methods"))?;
-                let (out_params, return_param) = get_out_params(name, &m, ir)?;
-                let in_params = get_in_params(&m, false, ir)?;
+                let (out_params, return_param) = get_out_params(&c_name, &m, ir, &self.options)?;
+                let in_params = get_in_params(&m, false, ir, &self.options)?;
 
                 let params = iter::once("void* ctx".to_string())
                     .chain(in_params)
@@ -874,7 +1210,7 @@ impl<'a, W: io::Write> CBackend<'a, W> {
                 );
                 format!(
                     include_str!("templates/c/callback.h"),
-                    callback_name = to_c_name(name),
+                    callback_name = c_name,
                     callback = method,
                 )
             }
@@ -894,7 +1230,7 @@ impl<'a, W: io::Write> CBackend<'a, W> {
                 let mut temp_method = method.clone();
                 temp_method.maybe_request_payload = method.maybe_response_payload.clone();
                 temp_method.maybe_response_payload = None;
-                let in_params = get_in_params(&temp_method, true, ir)?;
+                let in_params = get_in_params(&temp_method, true, ir, &self.options)?;
                 let params = iter::once("void* ctx".to_string())
                     .chain(in_params)
                     .collect::<Vec<_>>()
@@ -914,7 +1250,7 @@ impl<'a, W: io::Write> CBackend<'a, W> {
         if !for_banjo_transport(&data.maybe_attributes) {
             return Ok("".to_string());
         }
-        let name = to_c_name(&data.name.get_name());
+        let name = decl_c_name(&data.name, ir, &self.options);
         Ok(match ProtocolType::from(&data.maybe_attributes) {
             ProtocolType::Interface | ProtocolType::Protocol => format!(
                 "{async_decls}typedef struct {c_name}_protocol {c_name}_protocol_t;\n\
@@ -926,26 +1262,31 @@ impl<'a, W: io::Write> CBackend<'a, W> {
         })
     }
 
-    fn codegen_alias_decl(&self, data: &TypeAlias, _ir: &FidlIr) -> Result<String, Error> {
+    fn codegen_alias_decl(&self, data: &TypeAlias, ir: &FidlIr) -> Result<String, Error> {
         match data.partial_type_ctor.name.as_str() {
             "array" | "string" | "vector" => Ok("".to_string()),
             _ => Ok(format!(
                 "typedef {from}_t {to}_t;",
-                to = to_c_name(&data.name.get_name()),
-                from =
-                    to_c_name(&CompoundIdentifier(data.partial_type_ctor.name.clone()).get_name()),
+                to = decl_c_name(&data.name, ir, &self.options),
+                from = decl_c_name(
+                    &CompoundIdentifier(data.partial_type_ctor.name.clone()),
+                    ir,
+                    &self.options
+                ),
             )),
         }
     }
 
     fn codegen_includes(&self, ir: &FidlIr) -> Result<String, Error> {
-        Ok(ir
-            .library_dependencies
-            .iter()
-            .map(|l| &l.name.0)
+        // Sorted so that the output doesn't depend on the order of the dependencies in the IR.
+        let mut names = ir.library_dependencies.iter().map(|l| &l.name.0).collect::<Vec<_>>();
+        names.sort();
+        Ok(names
+            .into_iter()
             .filter(|n| *n != "zx")
             .map(|n| n.replace('.', "/") + "/c/banjo")
             .map(|n| format!("#include <{}.h>", n))
+            .chain(self.options.struct_eq.then(|| "#include <string.h>".to_string()))
             .collect::<Vec<_>>()
             .join("\n"))
     }
@@ -953,26 +1294,45 @@ impl<'a, W: io::Write> CBackend<'a, W> {
 
 impl<'a, W: io::Write> Backend<'a, W> for CBackend<'a, W> {
     fn codegen(&mut self, ir: FidlIr) -> Result<(), Error> {
-        self.w.write_fmt(format_args!(
+        let mut output = format!(
             include_str!("templates/c/header.h"),
             includes = self.codegen_includes(&ir)?,
-            primary_namespace = ir.name.0
-        ))?;
+            primary_namespace = ir.name.0,
+            symbol_prefix_note = if self.options.symbol_prefix.is_empty() {
+                "".to_string()
+            } else {
+                format!("\n// Declarations are prefixed with \"{}\"", self.options.symbol_prefix)
+            },
+        );
 
         let decl_order = get_declarations(&ir)?;
 
-        let declarations = decl_order
-            .iter()
-            .filter_map(|decl| match decl {
-                Decl::Const { data } => Some(self.codegen_constant_decl(data, &ir)),
-                Decl::Enum { data } => Some(self.codegen_enum_decl(data, &ir)),
-                Decl::Bits { data } => Some(self.codegen_bits_decl(data, &ir)),
-                Decl::Interface { data } => Some(self.codegen_protocol_decl(data, &ir)),
-                Decl::Struct { data } => Some(self.codegen_struct_decl(data)),
-                Decl::Table { data } => Some(self.codegen_table_decl(data)),
-                Decl::TypeAlias { data } => Some(self.codegen_alias_decl(data, &ir)),
-                Decl::Union { data } => Some(self.codegen_union_decl(data)),
-            })
+        let version = get_library_version(&ir).map(|version| {
+            Ok(format!(
+                "#define {}LIBRARY_VERSION \"{}\"",
+                self.options.symbol_prefix.to_uppercase(),
+                version
+            ))
+        });
+        let declarations = version
+            .into_iter()
+            .chain(decl_order.iter().map(|decl| {
+                let declaration = match decl {
+                    Decl::Const { data } => self.codegen_constant_decl(data, &ir),
+                    Decl::Enum { data } => self.codegen_enum_decl(data, &ir),
+                    Decl::Bits { data } => self.codegen_bits_decl(data, &ir),
+                    Decl::Interface { data } => self.codegen_protocol_decl(data, &ir),
+                    Decl::Struct { data } => self.codegen_struct_decl(data, &ir),
+                    Decl::Table { data } => self.codegen_table_decl(data, &ir),
+                    Decl::TypeAlias { data } => self.codegen_alias_decl(data, &ir),
+                    Decl::Union { data } => self.codegen_union_decl(data, &ir),
+                }?;
+                if self.options.declaration_ordinals && !declaration.is_empty() {
+                    Ok(ordinal_comment(decl.name()) + &declaration)
+                } else {
+                    Ok(declaration)
+                }
+            }))
             .collect::<Result<Vec<_>, Error>>()?
             .join("\n");
 
@@ -992,17 +1352,77 @@ impl<'a, W: io::Write> Backend<'a, W> for CBackend<'a, W> {
             .iter()
             .filter_map(|decl| match decl {
                 Decl::Interface { data } => Some(self.codegen_protocol_helper(data, &ir)),
+                Decl::Struct { data } if self.options.struct_eq => {
+                    struct_eq_func(data, &ir, &self.options).transpose()
+                }
                 _ => None,
             })
             .collect::<Result<Vec<_>, Error>>()?
             .join("\n");
 
-        self.w.write_fmt(format_args!(
+        let suppress_warnings = !self.options.suppressed_warnings.is_empty();
+        if suppress_warnings {
+            output.push_str("#pragma GCC diagnostic push\n");
+            for warning in &self.options.suppressed_warnings {
+                output.push_str(&format!("#pragma GCC diagnostic ignored \"{}\"\n", warning));
+            }
+            output.push('\n');
+        }
+        let (begin_cdecls, end_cdecls) = if self.options.cplusplus_compatible {
+            (
+                "#ifdef __cplusplus\nextern \"C\" {\n#endif",
+                "#ifdef __cplusplus\n}  // extern \"C\"\n#endif",
+            )
+        } else {
+            ("__BEGIN_CDECLS", "__END_CDECLS")
+        };
+        output.push_str(&format!(
             include_str!("templates/c/body.h"),
+            begin_cdecls = begin_cdecls,
+            end_cdecls = end_cdecls,
             declarations = declarations,
             definitions = definitions,
             helpers = helpers,
-        ))?;
+        ));
+        if suppress_warnings {
+            output.push_str("\n#pragma GCC diagnostic pop\n");
+        }
+        let output = with_newline_style(with_trailing_newline(output), self.options.newline_style);
+        self.w.write_all(output.as_bytes())?;
         Ok(())
     }
+
+    fn symbol_index(&self, ir: &FidlIr) -> Result<Vec<SymbolIndexEntry>, Error> {
+        let mut index = Vec::new();
+        for decl in get_declarations(ir)? {
+            let symbol = match decl {
+                Decl::Const { data } => self.constant_c_name(data, ir)?,
+                Decl::Enum { data } => format!("{}_t", decl_c_name(&data.name, ir, &self.options)),
+                Decl::Bits { data } => format!("{}_t", decl_c_name(&data.name, ir, &self.options)),
+                Decl::Interface { data } => {
+                    if !for_banjo_transport(&data.maybe_attributes) {
+                        continue;
+                    }
+                    let c_name = decl_c_name(&data.name, ir, &self.options);
+                    match ProtocolType::from(&data.maybe_attributes) {
+                        ProtocolType::Callback => format!("{}_t", c_name),
+                        _ => format!("{}_protocol_t", c_name),
+                    }
+                }
+                Decl::Struct { data } => {
+                    format!("{}_t", decl_c_name(&data.name, ir, &self.options))
+                }
+                Decl::Table { data } => format!("{}_t", decl_c_name(&data.name, ir, &self.options)),
+                Decl::TypeAlias { data } => match data.partial_type_ctor.name.as_str() {
+                    "array" | "string" | "vector" => continue,
+                    _ => format!("{}_t", decl_c_name(&data.name, ir, &self.options)),
+                },
+                Decl::Union { data } => format!("{}_t", decl_c_name(&data.name, ir, &self.options)),
+            };
+            if let Some(location) = decl.location() {
+                index.push(SymbolIndexEntry { symbol, location: location.clone() });
+            }
+        }
+        Ok(index)
+    }
 }
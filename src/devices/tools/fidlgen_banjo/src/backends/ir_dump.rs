@@ -0,0 +1,25 @@
+// Copyright 2022 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+use {super::Backend, anyhow::Error, fidl_ir_lib::fidl::FidlIr, std::io};
+
+/// Writes the whole IR, exactly as the other backends receive it, as
+/// pretty-printed JSON, for debugging code generation.
+pub struct IrDumpBackend<'a, W: io::Write> {
+    w: &'a mut W,
+}
+
+impl<'a, W: io::Write> IrDumpBackend<'a, W> {
+    pub fn new(w: &'a mut W) -> Self {
+        IrDumpBackend { w }
+    }
+}
+
+impl<'a, W: io::Write> Backend<'a, W> for IrDumpBackend<'a, W> {
+    fn codegen(&mut self, ir: FidlIr) -> Result<(), Error> {
+        serde_json::to_writer_pretty(&mut *self.w, &ir)?;
+        self.w.write_all(b"\n")?;
+        Ok(())
+    }
+}
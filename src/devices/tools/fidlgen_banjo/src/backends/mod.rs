@@ -2,21 +2,225 @@
 // Use of this source code is governed by a BSD-style license that can be
 // found in the LICENSE file.
 
-use {anyhow::Error, fidl_ir_lib::fidl::FidlIr, std::io};
+use {
+    anyhow::Error,
+    fidl_ir_lib::fidl::{FidlIr, Location},
+    std::{io, str::FromStr},
+};
 
 pub use self::c::CBackend;
 pub use self::cpp::CppBackend;
 pub use self::cpp_internal::CppInternalBackend;
 pub use self::cpp_mock::CppMockBackend;
+pub use self::ir_dump::IrDumpBackend;
 pub use self::rust::RustBackend;
 
 mod c;
 mod cpp;
 mod cpp_internal;
 mod cpp_mock;
+mod ir_dump;
 mod rust;
 mod util;
 
+/// The path through which the Rust backend refers to zircon types by default.
+pub const DEFAULT_ZIRCON_PATH: &str = "zircon_types";
+
+/// The path through which the Rust backend refers to the `fuchsia_zircon`
+/// wrappers (`Status`, `Channel` etc.) by default.
+pub const DEFAULT_ZIRCON_WRAPPER_PATH: &str = "zircon";
+
+/// The warnings the C backend disables around its output when asked to without
+/// naming any.
+pub const DEFAULT_SUPPRESSED_WARNINGS: &[&str] = &["-Wunused", "-Wsign-conversion"];
+
+/// The line endings of the generated code.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NewlineStyle {
+    /// `\n`, as used on Fuchsia and other Unix systems.
+    Lf,
+    /// `\r\n`, for consumers on Windows that expect it.
+    CrLf,
+}
+
+impl FromStr for NewlineStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "lf" => Ok(NewlineStyle::Lf),
+            "crlf" => Ok(NewlineStyle::CrLf),
+            _ => Err(format!("Unrecognized newline style {}. Valid ones are: lf, crlf", s)),
+        }
+    }
+}
+
+/// How the Rust backend represents banjo enums.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RustEnumStyle {
+    /// A newtype over the underlying integer with an associated constant per
+    /// member. It can hold values that aren't members, such as those received
+    /// from a newer peer.
+    Newtype,
+    /// A `#[repr(uN)]` enum with an explicit discriminant per member, which
+    /// allows exhaustive matching. Rust enums can't have two variants with the
+    /// same discriminant, so enums with aliased members are rejected. Values
+    /// that aren't members must never be stored in one.
+    NativeEnum,
+}
+
+impl FromStr for RustEnumStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "newtype" => Ok(RustEnumStyle::Newtype),
+            "native" => Ok(RustEnumStyle::NativeEnum),
+            _ => {
+                Err(format!("Unrecognized Rust enum style {}. Valid ones are: newtype, native", s))
+            }
+        }
+    }
+}
+
+/// Options that adjust the generated code. The defaults produce the standard
+/// output of each backend.
+#[derive(Debug, Clone)]
+pub struct CodegenOptions {
+    /// Prepended to the name of every typedef, constant, and enum declared by
+    /// the library in the C backend, so that headers for libraries with
+    /// clashing names can be included together. Upper-cased for macros.
+    pub symbol_prefix: String,
+    /// The path of the module providing the zircon types (`zx_handle_t` etc.)
+    /// in the Rust backend, for crates that re-export them elsewhere.
+    pub zircon_path: String,
+    /// The path of the module providing the `fuchsia_zircon` wrappers
+    /// (`Status`, `Channel` etc.) in the Rust backend, used for typed handles
+    /// and status results.
+    pub zircon_wrapper_path: String,
+    /// How the Rust backend represents enums.
+    pub rust_enum_style: RustEnumStyle,
+    /// Whether the C backend emits a `{name}_to_string()` function for every
+    /// enum, mapping each value to the name of its member for logging.
+    pub enum_to_string: bool,
+    /// Whether the C backend emits a `{NAME}_COUNT` define and a
+    /// `{name}_values[]` array listing the members of every enum.
+    pub enum_values: bool,
+    /// Whether the C backend emits a `{name}_names[]` array naming the members
+    /// of every enum, in the same order as `{name}_values[]` rather than
+    /// indexed by value, so that enums with gaps between values are covered.
+    pub enum_names: bool,
+    /// The column at which the C backend wraps long doc comment lines, or
+    /// `None` to keep each line of the doc comment as written.
+    pub doc_comment_column: Option<usize>,
+    /// Whether the C backend emits doc comments as Doxygen `/** ... */` blocks
+    /// tagged with `@brief` rather than `//` comments.
+    pub doxygen_doc_comments: bool,
+    /// Whether the C backend emits doc comments at all.
+    pub doc_comments: bool,
+    /// The number of spaces the C backend indents struct fields, protocol
+    /// methods and the bodies of protocol wrappers by.
+    pub indent_width: usize,
+    /// The line endings of the C and Rust backends' output, including the
+    /// files the Rust backend splits its output into.
+    pub newline_style: NewlineStyle,
+    /// Whether the C backend wraps its declarations in explicit
+    /// `extern "C"` guards rather than `__BEGIN_CDECLS`, and rejects struct,
+    /// union and table fields named after C++ keywords.
+    pub cplusplus_compatible: bool,
+    /// Whether the Rust backend emits the value fidlc computed for constants
+    /// defined by a binary operator expression, e.g. `16` for `1 << 4`,
+    /// rather than the expression as written. The C backend always does.
+    pub evaluate_binary_operators: bool,
+    /// Whether the C backend marks every struct with its alignment, so that
+    /// bindgen reproduces the layout from the IR rather than inferring it.
+    pub bindgen_layout: bool,
+    /// Whether the C backend follows each integer struct field with a comment
+    /// giving its width, signedness and range, e.g. `// uint16: 0..65535`.
+    pub field_width_comments: bool,
+    /// Whether the C backend emits a `{name}_eq()` function for every struct
+    /// whose fields can all be compared by value.
+    pub struct_eq: bool,
+    /// Whether the Rust backend emits an ops table and a client wrapper for
+    /// each banjo protocol. Off by default.
+    pub rust_protocols: bool,
+    /// Whether the Rust protocol wrappers return `Result<(), zircon::Status>`
+    /// for methods returning a `zx_status_t` instead of the raw status.
+    pub status_results: bool,
+    /// Whether the Rust backend types handles with the `fuchsia_zircon`
+    /// wrapper for their subtype, e.g. `zircon::Channel`, rather than a raw
+    /// `zx_handle_t`. Structs and tables holding such handles own them, so
+    /// they don't derive `Copy` or `Clone`.
+    pub typed_handles: bool,
+    /// The Cargo feature gating the safe wrappers the Rust backend emits for
+    /// protocol methods taking raw pointers, or `None` to not emit them.
+    pub safe_wrappers_feature: Option<String>,
+    /// The Cargo feature gating the async wrappers the Rust backend emits for
+    /// protocol methods, which run the blocking call on a thread pool, or
+    /// `None` to not emit them.
+    pub async_wrappers_feature: Option<String>,
+    /// The Cargo feature gating serde `Serialize` and `Deserialize` derives on
+    /// the structs the Rust backend emits that hold no raw pointers, or `None`
+    /// to not emit them.
+    pub serde_feature: Option<String>,
+    /// Whether both backends precede each top-level declaration with a comment
+    /// giving its ordinal, a hash of its fully-qualified name that stays the
+    /// same across runs.
+    pub declaration_ordinals: bool,
+    /// The warnings the C backend disables by wrapping its output in
+    /// `#pragma GCC diagnostic push` and `pop`, e.g. `-Wunused`. The output is
+    /// left unwrapped when empty.
+    pub suppressed_warnings: Vec<String>,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        CodegenOptions {
+            symbol_prefix: String::new(),
+            zircon_path: DEFAULT_ZIRCON_PATH.to_string(),
+            zircon_wrapper_path: DEFAULT_ZIRCON_WRAPPER_PATH.to_string(),
+            rust_enum_style: RustEnumStyle::Newtype,
+            enum_to_string: false,
+            enum_values: false,
+            enum_names: false,
+            doc_comment_column: None,
+            doxygen_doc_comments: false,
+            doc_comments: true,
+            indent_width: 4,
+            newline_style: NewlineStyle::Lf,
+            cplusplus_compatible: false,
+            evaluate_binary_operators: false,
+            bindgen_layout: false,
+            field_width_comments: false,
+            struct_eq: false,
+            rust_protocols: false,
+            status_results: false,
+            typed_handles: false,
+            safe_wrappers_feature: None,
+            async_wrappers_feature: None,
+            serde_feature: None,
+            declaration_ordinals: false,
+            suppressed_warnings: Vec::new(),
+        }
+    }
+}
+
+/// A symbol in the generated code and the location of the banjo declaration
+/// it was generated from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolIndexEntry {
+    pub symbol: String,
+    pub location: Location,
+}
+
 pub trait Backend<'a, W: io::Write> {
     fn codegen(&mut self, ir: FidlIr) -> Result<(), Error>;
+
+    /// Lists the symbols that `codegen` declares for the library along with
+    /// the source location of each one, so that tools can map generated code
+    /// back to the banjo source. Declarations without a location in the IR
+    /// are left out. Backends that don't support this return an empty index.
+    fn symbol_index(&self, _ir: &FidlIr) -> Result<Vec<SymbolIndexEntry>, Error> {
+        Ok(Vec::new())
+    }
 }
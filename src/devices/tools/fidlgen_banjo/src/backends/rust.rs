@@ -4,22 +4,84 @@
 
 use {
     super::{
-        util::{get_declarations, name_buffer, name_size, to_c_name, Decl},
-        Backend,
+        util::{
+            for_banjo_transport, get_declarations, get_library_version, integer_range, name_buffer,
+            name_size, ordinal_comment, to_c_name, validate_enum_members, with_newline_style,
+            with_trailing_newline, Decl, ProtocolType,
+        },
+        Backend, CodegenOptions, RustEnumStyle, SymbolIndexEntry, DEFAULT_ZIRCON_PATH,
     },
     anyhow::{anyhow, Error},
     fidl_ir_lib::fidl::*,
-    std::collections::HashSet,
+    std::collections::{HashMap, HashSet},
+    std::fs,
     std::io,
+    std::path::Path,
 };
 
 pub struct RustBackend<'a, W: io::Write> {
     w: &'a mut W,
+    options: CodegenOptions,
 }
 
 impl<'a, W: io::Write> RustBackend<'a, W> {
     pub fn new(w: &'a mut W) -> Self {
-        RustBackend { w }
+        Self::with_options(w, CodegenOptions::default())
+    }
+
+    pub fn with_options(w: &'a mut W, options: CodegenOptions) -> Self {
+        RustBackend { w, options }
+    }
+
+    /// Writes the bindings into `dir` rather than the backend's writer, split
+    /// into `constants.rs`, `enums.rs`, `structs.rs` and `protocols.rs` by the
+    /// kind of declaration, along with a `mod.rs` re-exporting them. Bits are
+    /// written with the enums, and tables and unions with the structs. Files
+    /// that would be empty are left out.
+    pub fn codegen_split(&self, ir: FidlIr, dir: &Path) -> Result<(), Error> {
+        let decl_order = get_declarations(&ir)?;
+        let mut modules = vec![];
+        if ir.name.0 != "zx" {
+            modules.push(("constants", self.codegen_const_decl(&decl_order, &ir)?));
+            modules.push((
+                "enums",
+                [
+                    self.codegen_enum_decl(&decl_order, &ir)?,
+                    self.codegen_bits_decl(&decl_order, &ir)?,
+                ]
+                .join("\n"),
+            ));
+            modules.push((
+                "structs",
+                [
+                    self.codegen_struct_decl(&decl_order, &ir)?,
+                    self.codegen_table_decl(&decl_order, &ir)?,
+                    self.codegen_union_decl(&decl_order, &ir)?,
+                ]
+                .join("\n"),
+            ));
+            if self.options.rust_protocols {
+                modules.push(("protocols", self.codegen_protocol_decl(&decl_order, &ir)?));
+            }
+        }
+        modules.retain(|(_, decls)| !decls.trim().is_empty());
+
+        fs::create_dir_all(dir)?;
+        let mut mod_rs = self.codegen_header(&ir)?;
+        for (name, decls) in &modules {
+            let file = format!(
+                include_str!("templates/rust/split.rs"),
+                primary_namespace = ir.name.0,
+                decls = decls.trim_end(),
+            );
+            fs::write(
+                dir.join(format!("{}.rs", name)),
+                with_newline_style(file, self.options.newline_style),
+            )?;
+            mod_rs.push_str(&format!("\nmod {name};\npub use {name}::*;\n", name = name));
+        }
+        fs::write(dir.join("mod.rs"), with_newline_style(mod_rs, self.options.newline_style))?;
+        Ok(())
     }
 }
 
@@ -120,6 +182,61 @@ fn can_derive_partialeq(
     }
 }
 
+// Returns whether serde can derive Serialize and Deserialize for a struct field of the given type,
+// i.e. whether it's plain old data: no raw pointers, nothing serde leaves out like unions, and no
+// arrays longer than serde supports.
+fn can_derive_serde(
+    ty: &Type,
+    parents: &mut HashSet<CompoundIdentifier>,
+    ir: &FidlIr,
+) -> Result<bool, Error> {
+    match ty {
+        Type::Array { ref element_type, ref element_count } => {
+            Ok(element_count.0 <= 32 && can_derive_serde(element_type, parents, ir)?)
+        }
+        Type::Str { maybe_element_count: Some(count), .. } => Ok(count.0 <= 32),
+        Type::Handle { .. } => Ok(true),
+        Type::Primitive { .. } => Ok(true),
+        Type::Identifier { identifier: type_id, .. } => {
+            if type_id.is_base_type() {
+                return Ok(true);
+            }
+            if ir.is_external_decl(type_id)? {
+                return Ok(false);
+            }
+            match ir.get_declaration(type_id)? {
+                Declaration::Struct => {
+                    let decl = ir.get_struct(type_id)?;
+                    // Fields of packed structs can't be borrowed to serialize them.
+                    if decl.maybe_attributes.has("Packed") || !parents.insert(type_id.clone()) {
+                        return Ok(false);
+                    }
+                    for field in &decl.members {
+                        if !can_derive_serde(&field._type, parents, ir)? {
+                            return Ok(false);
+                        }
+                    }
+                    parents.remove(type_id);
+                    Ok(true)
+                }
+                Declaration::TypeAlias { .. } => {
+                    let decl = ir.get_type_alias(type_id)?;
+                    let ident = CompoundIdentifier(decl.partial_type_ctor.name.clone());
+                    can_derive_serde(
+                        &Type::Identifier { identifier: ident, nullable: false },
+                        parents,
+                        ir,
+                    )
+                }
+                // Serde derives are only emitted for structs.
+                _ => Ok(false),
+            }
+        }
+        // Vectors and unbounded strings are raw pointers.
+        _ => Ok(false),
+    }
+}
+
 fn primitive_type_to_rust_str(ty: &PrimitiveSubtype) -> Result<String, Error> {
     match ty {
         PrimitiveSubtype::Bool => Ok(String::from("bool")),
@@ -140,18 +257,21 @@ fn type_to_rust_str(
     ty: &Type,
     maybe_attributes: &Option<Vec<Attribute>>,
     ir: &FidlIr,
+    options: &CodegenOptions,
 ) -> Result<String, Error> {
     match ty {
+        // fidlc resolves array sizes, so they're emitted as the literal count rather than the
+        // constant they were declared with, which keeps nested arrays valid.
         Type::Array { element_type, element_count } => Ok(format!(
-            "[{ty}; {size} as usize]",
-            ty = type_to_rust_str(element_type, maybe_attributes, ir)?,
-            size = element_count.0.to_string().to_uppercase()
+            "[{ty}; {size}]",
+            ty = type_to_rust_str(element_type, maybe_attributes, ir, options)?,
+            size = element_count.0
         )),
         Type::Vector { ref element_type, .. } => {
-            type_to_rust_str(element_type, maybe_attributes, ir)
+            type_to_rust_str(element_type, maybe_attributes, ir, options)
         }
         Type::Str { maybe_element_count, .. } => match maybe_element_count {
-            Some(count) => Ok(format!("[u8; {count} as usize]", count = count.0)),
+            Some(count) => Ok(format!("[u8; {count}]", count = count.0)),
             None => {
                 let mutable = if maybe_attributes.has("InOut") || maybe_attributes.has("Mutable") {
                     "mut"
@@ -164,15 +284,18 @@ fn type_to_rust_str(
         Type::Primitive { ref subtype } => primitive_type_to_rust_str(subtype),
         Type::Identifier { identifier, nullable } => {
             if identifier.is_base_type() {
-                return Ok(format!("zircon_types::zx_{}_t", identifier.get_name()));
+                return Ok(format!(
+                    "{zircon_path}::zx_{name}_t",
+                    zircon_path = options.zircon_path,
+                    name = identifier.get_name()
+                ));
             }
             match ir.get_declaration(identifier)? {
                 Declaration::Const => {
                     let decl = ir.get_const(identifier)?;
-                    type_to_rust_str(&decl._type, maybe_attributes, ir)
+                    type_to_rust_str(&decl._type, maybe_attributes, ir, options)
                 }
-                Declaration::Enum => Ok(format!("{}", name = identifier.get_name())),
-                Declaration::Bits => Ok(format!("{}", name = identifier.get_name())),
+                Declaration::Enum | Declaration::Bits => Ok(identifier.get_name().to_string()),
                 // Protocols are not generated, but this supports some tests.
                 Declaration::Interface => return Ok(to_c_name(identifier.get_name())),
                 Declaration::Struct | Declaration::Table | Declaration::Union => {
@@ -185,12 +308,152 @@ fn type_to_rust_str(
                 _ => Err(anyhow!("Can't handle declaration of {:?}", identifier)),
             }
         }
-        Type::Handle { .. } => Ok(format!("zircon_types::zx_handle_t")),
+        Type::Handle { subtype, .. } => match typed_handle(subtype, options) {
+            Some(handle) => Ok(format!("{}::{}", options.zircon_wrapper_path, handle)),
+            None => Ok(format!("{}::zx_handle_t", options.zircon_path)),
+        },
         _ => Err(anyhow!("Can't handle type {:?}", ty)),
     }
 }
 
-fn field_to_rust_str(field: &StructMember, ir: &FidlIr) -> Result<String, Error> {
+// Returns the fuchsia_zircon wrapper for handles of the given subtype if typed handles are enabled
+// and there is one.
+fn typed_handle(subtype: &HandleSubtype, options: &CodegenOptions) -> Option<&'static str> {
+    if !options.typed_handles {
+        return None;
+    }
+    match subtype {
+        HandleSubtype::Bti => Some("Bti"),
+        HandleSubtype::Channel => Some("Channel"),
+        HandleSubtype::Clock => Some("Clock"),
+        HandleSubtype::Debuglog => Some("DebugLog"),
+        HandleSubtype::Event => Some("Event"),
+        HandleSubtype::EventPair => Some("EventPair"),
+        HandleSubtype::Fifo => Some("Fifo"),
+        HandleSubtype::Guest => Some("Guest"),
+        HandleSubtype::Interrupt => Some("Interrupt"),
+        HandleSubtype::Job => Some("Job"),
+        HandleSubtype::Pager => Some("Pager"),
+        HandleSubtype::Port => Some("Port"),
+        HandleSubtype::Process => Some("Process"),
+        HandleSubtype::Profile => Some("Profile"),
+        HandleSubtype::Resource => Some("Resource"),
+        HandleSubtype::Socket => Some("Socket"),
+        HandleSubtype::Stream => Some("Stream"),
+        HandleSubtype::Thread => Some("Thread"),
+        HandleSubtype::Timer => Some("Timer"),
+        HandleSubtype::Vcpu => Some("Vcpu"),
+        HandleSubtype::Vmar => Some("Vmar"),
+        HandleSubtype::Vmo => Some("Vmo"),
+        _ => None,
+    }
+}
+
+// Returns whether a value of the given type owns a typed handle, which keeps the struct or table
+// holding it from deriving Copy and Clone. Vectors are raw pointers, so they don't own anything.
+fn holds_typed_handle(
+    ty: &Type,
+    parents: &mut HashSet<CompoundIdentifier>,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<bool, Error> {
+    match ty {
+        Type::Handle { subtype, .. } => Ok(typed_handle(subtype, options).is_some()),
+        Type::Array { ref element_type, .. } => {
+            holds_typed_handle(element_type, parents, ir, options)
+        }
+        Type::Identifier { identifier: type_id, nullable: false } => {
+            if !options.typed_handles
+                || type_id.is_base_type()
+                || ir.is_external_decl(type_id)?
+                || !parents.insert(type_id.clone())
+            {
+                return Ok(false);
+            }
+            let fields = match ir.get_declaration(type_id)? {
+                Declaration::Struct => {
+                    ir.get_struct(type_id)?.members.iter().map(|field| &field._type).collect()
+                }
+                Declaration::Table => ir
+                    .get_table(type_id)?
+                    .members
+                    .iter()
+                    .filter_map(|field| field._type.as_ref())
+                    .collect(),
+                Declaration::Union => ir
+                    .get_union(type_id)?
+                    .members
+                    .iter()
+                    .filter_map(|field| field._type.as_ref())
+                    .collect(),
+                _ => vec![],
+            };
+            for field in fields {
+                if holds_typed_handle(field, parents, ir, options)? {
+                    return Ok(true);
+                }
+            }
+            parents.remove(type_id);
+            Ok(false)
+        }
+        _ => Ok(false),
+    }
+}
+
+// Returns whether a field of the given type is passed by raw pointer: vectors, unbounded strings,
+// nullable structs, tables and unions, and arrays of any of these.
+fn is_pointer(ty: &Type, ir: &FidlIr) -> Result<bool, Error> {
+    match ty {
+        Type::Vector { .. } => Ok(true),
+        Type::Str { maybe_element_count, .. } => Ok(maybe_element_count.is_none()),
+        Type::Array { element_type, .. } => is_pointer(element_type, ir),
+        Type::Identifier { identifier, nullable } if !identifier.is_base_type() => {
+            match ir.get_declaration(identifier)? {
+                Declaration::Const => is_pointer(&ir.get_const(identifier)?._type, ir),
+                Declaration::Struct | Declaration::Table | Declaration::Union => Ok(*nullable),
+                _ => Ok(false),
+            }
+        }
+        _ => Ok(false),
+    }
+}
+
+// Returns an error if the declaration is packed and the field is a raw pointer. Fields of packed
+// declarations may be unaligned, and reading an unaligned pointer is undefined behavior.
+fn check_packed_field(
+    name: &CompoundIdentifier,
+    maybe_attributes: &Option<Vec<Attribute>>,
+    field_name: &str,
+    ty: &Type,
+    ir: &FidlIr,
+) -> Result<(), Error> {
+    if maybe_attributes.has("Packed") && is_pointer(ty, ir)? {
+        return Err(anyhow!(
+            "Packed declaration {} can't hold pointer field {}",
+            name.get_name(),
+            field_name
+        ));
+    }
+    Ok(())
+}
+
+// Returns a doc comment naming the subtype of a raw handle field, which zx_handle_t loses.
+fn handle_subtype_comment(ty: &Type, options: &CodegenOptions) -> String {
+    match ty {
+        Type::Handle { subtype, .. }
+            if *subtype != HandleSubtype::Handle && typed_handle(subtype, options).is_none() =>
+        {
+            format!("    /// Handle subtype: {}.\n", format!("{:?}", subtype).to_lowercase())
+        }
+        _ => String::new(),
+    }
+}
+
+fn field_to_rust_str(
+    field: &StructMember,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<String, Error> {
     let c_name = &field.name.0;
     let maybe_attributes = &field.maybe_attributes;
 
@@ -200,9 +463,10 @@ fn field_to_rust_str(field: &StructMember, ir: &FidlIr) -> Result<String, Error>
         | Type::Primitive { .. }
         | Type::Identifier { .. }
         | Type::Handle { .. } => Ok(format!(
-            "    pub {c_name}: {ty},",
+            "{subtype}    pub {c_name}: {ty},",
+            subtype = handle_subtype_comment(&field._type, options),
             c_name = c_name,
-            ty = type_to_rust_str(&field._type, maybe_attributes, ir)?
+            ty = type_to_rust_str(&field._type, maybe_attributes, ir, options)?
         )),
         Type::Vector { ref element_type, .. } => {
             let out_of_line = if maybe_attributes.has("OutOfLineContents") { "*mut " } else { "" };
@@ -216,14 +480,18 @@ fn field_to_rust_str(field: &StructMember, ir: &FidlIr) -> Result<String, Error>
                 mutable = mutable,
                 out_of_line = out_of_line,
                 c_name = c_name,
-                ty = type_to_rust_str(element_type, maybe_attributes, ir)?
+                ty = type_to_rust_str(element_type, maybe_attributes, ir, options)?
             ))
         }
         _ => Err(anyhow!("Can't handle type {:?}", field._type)),
     }
 }
 
-fn table_field_to_rust_str(field: &TableMember, ir: &FidlIr) -> Result<String, Error> {
+fn table_field_to_rust_str(
+    field: &TableMember,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<String, Error> {
     let c_name = &field.name.as_ref().expect("Missing name on table field").0;
     let maybe_attributes = &field.maybe_attributes;
 
@@ -233,9 +501,10 @@ fn table_field_to_rust_str(field: &TableMember, ir: &FidlIr) -> Result<String, E
         | Type::Primitive { .. }
         | Type::Identifier { .. }
         | Type::Handle { .. } => Ok(format!(
-            "    pub {c_name}: {ty},",
+            "{subtype}    pub {c_name}: {ty},",
+            subtype = handle_subtype_comment(field._type.as_ref().unwrap(), options),
             c_name = c_name,
-            ty = type_to_rust_str(&field._type.as_ref().unwrap(), maybe_attributes, ir)?
+            ty = type_to_rust_str(&field._type.as_ref().unwrap(), maybe_attributes, ir, options)?
         )),
         Type::Vector { ref element_type, .. } => {
             let out_of_line = if maybe_attributes.has("OutOfLineContents") { "*mut " } else { "" };
@@ -249,23 +518,415 @@ fn table_field_to_rust_str(field: &TableMember, ir: &FidlIr) -> Result<String, E
                 mutable = mutable,
                 out_of_line = out_of_line,
                 c_name = c_name,
-                ty = type_to_rust_str(element_type, maybe_attributes, ir)?
+                ty = type_to_rust_str(element_type, maybe_attributes, ir, options)?
             ))
         }
         ty => Err(anyhow!("Can't handle type {:?}", ty)),
     }
 }
 
-fn get_base_type_from_alias(alias: &Option<&String>) -> Option<String> {
+fn get_base_type_from_alias(alias: &Option<&String>, options: &CodegenOptions) -> Option<String> {
     if let Some(name) = alias {
         if name.starts_with("zx/") {
-            return Some(format!("zircon_types::zx_{}_t", &name[3..]));
+            return Some(format!("{}::zx_{}_t", options.zircon_path, &name[3..]));
         }
     }
     None
 }
 
+// Returns the Rust type of a value returned directly by a protocol method, mirroring the C
+// backend: the first response parameter is returned if it's a primitive or a zircon alias.
+fn protocol_return_type(
+    method: &Method,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<Option<String>, Error> {
+    if let Some(response) = &method.response_parameters(ir)? {
+        if let Some(param) = response.first() {
+            if let Some(arg_type) = get_base_type_from_alias(
+                &param.experimental_maybe_from_type_alias.as_ref().map(|t| &t.name),
+                options,
+            ) {
+                return Ok(Some(arg_type));
+            }
+            if param._type.is_primitive(ir)? {
+                return Ok(Some(type_to_rust_str(
+                    &param._type,
+                    param.maybe_attributes,
+                    ir,
+                    options,
+                )?));
+            }
+        }
+    }
+    Ok(None)
+}
+
+// A protocol method parameter along with its C name and, if it was declared with a zircon alias,
+// the Rust type that the alias refers to.
+struct ProtocolParam<'b> {
+    name: String,
+    alias_type: Option<String>,
+    param: MethodParameter<'b>,
+}
+
+// The parameters of a protocol method. The first response parameter is left out of `outs` if the
+// method returns it directly.
+struct ProtocolParams<'b> {
+    ins: Vec<ProtocolParam<'b>>,
+    outs: Vec<ProtocolParam<'b>>,
+}
+
+fn protocol_params<'b>(
+    method: &'b Method,
+    skip_first: bool,
+    ir: &'b FidlIr,
+    options: &CodegenOptions,
+) -> Result<ProtocolParams<'b>, Error> {
+    let resolve = |param: MethodParameter<'b>| ProtocolParam {
+        name: to_c_name(&param.name.0),
+        alias_type: get_base_type_from_alias(
+            &param.experimental_maybe_from_type_alias.map(|t| &t.name),
+            options,
+        ),
+        param,
+    };
+    Ok(ProtocolParams {
+        ins: method.request_parameters(ir)?.unwrap_or_default().into_iter().map(resolve).collect(),
+        outs: method
+            .response_parameters(ir)?
+            .unwrap_or_default()
+            .into_iter()
+            .skip(if skip_first { 1 } else { 0 })
+            .map(resolve)
+            .collect(),
+    })
+}
+
+// Returns the names and types of the raw parameters that a protocol method's request parameters
+// are passed as. Vectors are passed as a buffer and a size.
+fn protocol_in_params(
+    method: &Method,
+    params: &ProtocolParams<'_>,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<Vec<(String, String)>, Error> {
+    let mut raw = Vec::new();
+    for ProtocolParam { name, alias_type, param } in &params.ins {
+        let attrs = param.maybe_attributes;
+        if let Some(arg_type) = alias_type {
+            raw.push((name.clone(), arg_type.clone()));
+            continue;
+        }
+        let mutable = if attrs.has("InOut") || attrs.has("Mutable") { "mut" } else { "const" };
+        match &param._type {
+            Type::Identifier { identifier, .. } if !identifier.is_base_type() => {
+                let ty = type_to_rust_str(&param._type, attrs, ir, options)?;
+                match ir.get_declaration(identifier)? {
+                    Declaration::Struct | Declaration::Table | Declaration::Union => {
+                        raw.push((name.clone(), format!("*{} {}", mutable, ty)))
+                    }
+                    Declaration::Enum | Declaration::Bits => raw.push((name.clone(), ty)),
+                    decl => {
+                        return Err(anyhow!(
+                            "Can't handle {:?} parameter {} in protocol method {}",
+                            decl,
+                            name,
+                            method.name.0
+                        ))
+                    }
+                }
+            }
+            Type::Str { .. } => {
+                raw.push((name.clone(), "*const std::os::raw::c_char".to_string()));
+            }
+            Type::Array { element_type, .. } => raw.push((
+                name.clone(),
+                format!("*const {}", type_to_rust_str(element_type, attrs, ir, options)?),
+            )),
+            Type::Vector { element_type, .. } => {
+                let ty = type_to_rust_str(element_type, attrs, ir, options)?;
+                raw.push((format!("{}_{}", name, name_buffer(attrs)), format!("*const {}", ty)));
+                raw.push((format!("{}_{}", name, name_size(attrs)), "usize".to_string()));
+            }
+            ty => raw.push((name.clone(), type_to_rust_str(ty, attrs, ir, options)?)),
+        }
+    }
+    Ok(raw)
+}
+
+// Returns the names and types of the raw parameters that a protocol method's response parameters
+// are passed as. They're all written through pointers, and strings and vectors also pass the
+// capacity of their buffer.
+fn protocol_out_params(
+    params: &ProtocolParams<'_>,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<Vec<(String, String)>, Error> {
+    let mut raw = Vec::new();
+    for ProtocolParam { name, alias_type, param } in &params.outs {
+        let attrs = param.maybe_attributes;
+        if let Some(arg_type) = alias_type {
+            raw.push((format!("out_{}", name), format!("*mut {}", arg_type)));
+            continue;
+        }
+        match &param._type {
+            Type::Str { .. } => {
+                raw.push((format!("out_{}", name), "*mut std::os::raw::c_char".to_string()));
+                raw.push((format!("{}_capacity", name), "usize".to_string()));
+            }
+            Type::Array { element_type, .. } => raw.push((
+                format!("out_{}", name),
+                format!("*mut {}", type_to_rust_str(element_type, attrs, ir, options)?),
+            )),
+            Type::Vector { element_type, .. } => {
+                let ty = type_to_rust_str(element_type, attrs, ir, options)?;
+                let buffer = format!("out_{}_{}", name, name_buffer(attrs));
+                let size = format!("{}_{}", name, name_size(attrs));
+                if attrs.has("CalleeAllocated") {
+                    raw.push((buffer, format!("*mut *mut {}", ty)));
+                    raw.push((size, "*mut usize".to_string()));
+                } else {
+                    raw.push((buffer, format!("*mut {}", ty)));
+                    raw.push((size, "usize".to_string()));
+                    raw.push((format!("out_{}_actual", name), "*mut usize".to_string()));
+                }
+            }
+            ty => raw.push((
+                format!("out_{}", name),
+                format!("*mut {}", type_to_rust_str(ty, attrs, ir, options)?),
+            )),
+        }
+    }
+    Ok(raw)
+}
+
+// Returns whether a protocol method passes any of its parameters by raw pointer, which all of its
+// out parameters are.
+fn protocol_takes_pointers(params: &ProtocolParams<'_>, ir: &FidlIr) -> Result<bool, Error> {
+    if !params.outs.is_empty() {
+        return Ok(true);
+    }
+    for ProtocolParam { alias_type, param, .. } in &params.ins {
+        if alias_type.is_some() {
+            continue;
+        }
+        let pointer = match &param._type {
+            Type::Identifier { identifier, .. } if !identifier.is_base_type() => matches!(
+                ir.get_declaration(identifier)?,
+                Declaration::Struct | Declaration::Table | Declaration::Union
+            ),
+            Type::Str { .. } | Type::Array { .. } | Type::Vector { .. } => true,
+            _ => false,
+        };
+        if pointer {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// Returns the parameters of a safe wrapper around a protocol method, with references and slices in
+// place of raw pointers, along with the arguments that pass them on to the raw method. Returns
+// None if some parameter has no safe equivalent, e.g. a callee-allocated buffer.
+fn protocol_safe_params(
+    params: &ProtocolParams<'_>,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<Option<(Vec<String>, Vec<String>)>, Error> {
+    let mut safe_params = Vec::new();
+    let mut args = Vec::new();
+    for ProtocolParam { name, alias_type, param } in &params.ins {
+        let name = name.clone();
+        let attrs = param.maybe_attributes;
+        if let Some(arg_type) = alias_type {
+            safe_params.push(format!("{}: {}", name, arg_type));
+            args.push(name);
+            continue;
+        }
+        match &param._type {
+            Type::Identifier { identifier, .. } if !identifier.is_base_type() => {
+                let ty = type_to_rust_str(&param._type, attrs, ir, options)?;
+                match ir.get_declaration(identifier)? {
+                    Declaration::Struct | Declaration::Table | Declaration::Union => {
+                        let mutable = attrs.has("InOut") || attrs.has("Mutable");
+                        safe_params.push(format!(
+                            "{}: &{}{}",
+                            name,
+                            if mutable { "mut " } else { "" },
+                            ty
+                        ));
+                    }
+                    Declaration::Enum | Declaration::Bits => {
+                        safe_params.push(format!("{}: {}", name, ty));
+                    }
+                    _ => return Ok(None),
+                }
+                args.push(name);
+            }
+            Type::Str { .. } => {
+                safe_params.push(format!("{}: &std::ffi::CStr", name));
+                args.push(format!("{}.as_ptr()", name));
+            }
+            Type::Array { element_type, element_count } => {
+                safe_params.push(format!(
+                    "{}: &[{}; {}]",
+                    name,
+                    type_to_rust_str(element_type, attrs, ir, options)?,
+                    element_count.0
+                ));
+                args.push(format!("{}.as_ptr()", name));
+            }
+            Type::Vector { element_type, .. } => {
+                safe_params.push(format!(
+                    "{}: &[{}]",
+                    name,
+                    type_to_rust_str(element_type, attrs, ir, options)?
+                ));
+                args.push(format!("{}.as_ptr()", name));
+                args.push(format!("{}.len()", name));
+            }
+            ty => {
+                safe_params.push(format!(
+                    "{}: {}",
+                    name,
+                    type_to_rust_str(ty, attrs, ir, options)?
+                ));
+                args.push(name);
+            }
+        }
+    }
+    for ProtocolParam { name, alias_type, param } in &params.outs {
+        let name = format!("out_{}", name);
+        let attrs = param.maybe_attributes;
+        if let Some(arg_type) = alias_type {
+            safe_params.push(format!("{}: &mut {}", name, arg_type));
+            args.push(name);
+            continue;
+        }
+        match &param._type {
+            Type::Str { .. } => {
+                safe_params.push(format!("{}: &mut [std::os::raw::c_char]", name));
+                args.push(format!("{}.as_mut_ptr()", name));
+                args.push(format!("{}.len()", name));
+            }
+            Type::Array { element_type, element_count } => {
+                safe_params.push(format!(
+                    "{}: &mut [{}; {}]",
+                    name,
+                    type_to_rust_str(element_type, attrs, ir, options)?,
+                    element_count.0
+                ));
+                args.push(format!("{}.as_mut_ptr()", name));
+            }
+            Type::Vector { .. } if attrs.has("CalleeAllocated") => return Ok(None),
+            Type::Vector { element_type, .. } => {
+                safe_params.push(format!(
+                    "{}: &mut [{}], {}_actual: &mut usize",
+                    name,
+                    type_to_rust_str(element_type, attrs, ir, options)?,
+                    name
+                ));
+                args.push(format!("{}.as_mut_ptr()", name));
+                args.push(format!("{}.len()", name));
+                args.push(format!("{}_actual", name));
+            }
+            ty => {
+                safe_params.push(format!(
+                    "{}: &mut {}",
+                    name,
+                    type_to_rust_str(ty, attrs, ir, options)?
+                ));
+                args.push(name);
+            }
+        }
+    }
+    Ok(Some((safe_params, args)))
+}
+
+// The parameters of an async wrapper, the arguments passing them on, and its out parameters.
+type AsyncParams = (Vec<String>, Vec<String>, Vec<(String, String)>);
+
+// Returns the parameters of an async wrapper around a protocol method, which are all owned so that
+// the blocking call can run on another thread, along with the arguments that pass them on to the
+// raw method and the names and types of the out parameters the wrapper returns instead. Returns
+// None if some parameter has no owned equivalent, e.g. a string or a buffer.
+fn protocol_async_params(
+    params: &ProtocolParams<'_>,
+    ir: &FidlIr,
+    options: &CodegenOptions,
+) -> Result<Option<AsyncParams>, Error> {
+    let mut async_params = Vec::new();
+    let mut args = Vec::new();
+    for ProtocolParam { name, alias_type, param } in &params.ins {
+        let name = name.clone();
+        let attrs = param.maybe_attributes;
+        if let Some(arg_type) = alias_type {
+            async_params.push(format!("{}: {}", name, arg_type));
+            args.push(name);
+            continue;
+        }
+        match &param._type {
+            Type::Identifier { identifier, .. } if !identifier.is_base_type() => {
+                let ty = type_to_rust_str(&param._type, attrs, ir, options)?;
+                match ir.get_declaration(identifier)? {
+                    // Structs are passed by pointer, so the wrapper takes a copy and lends it.
+                    Declaration::Struct | Declaration::Table | Declaration::Union
+                        if !attrs.has("InOut") && !attrs.has("Mutable") =>
+                    {
+                        args.push(format!("&{}", name));
+                    }
+                    Declaration::Enum | Declaration::Bits => args.push(name.clone()),
+                    _ => return Ok(None),
+                }
+                async_params.push(format!("{}: {}", name, ty));
+            }
+            Type::Str { .. } | Type::Array { .. } | Type::Vector { .. } => return Ok(None),
+            ty => {
+                async_params.push(format!(
+                    "{}: {}",
+                    name,
+                    type_to_rust_str(ty, attrs, ir, options)?
+                ));
+                args.push(name);
+            }
+        }
+    }
+    let mut outs = Vec::new();
+    for ProtocolParam { name, alias_type, param } in &params.outs {
+        let name = format!("out_{}", name);
+        let ty = match alias_type {
+            Some(arg_type) => arg_type.clone(),
+            None => match &param._type {
+                Type::Str { .. } | Type::Array { .. } | Type::Vector { .. } => return Ok(None),
+                ty => type_to_rust_str(ty, param.maybe_attributes, ir, options)?,
+            },
+        };
+        args.push(format!("{}.as_mut_ptr()", name));
+        outs.push((name, ty));
+    }
+    Ok(Some((async_params, args, outs)))
+}
+
 impl<'a, W: io::Write> RustBackend<'a, W> {
+    fn codegen_header(&self, ir: &FidlIr) -> Result<String, Error> {
+        // A custom zircon path is expected to already be in scope wherever the
+        // output is included.
+        let zircon_include =
+            if has_zircon_dep(ir) && self.options.zircon_path == DEFAULT_ZIRCON_PATH {
+                "use fuchsia_zircon_types as zircon_types;"
+            } else {
+                ""
+            };
+
+        Ok(format!(
+            include_str!("templates/rust/header.rs"),
+            zircon_include = zircon_include,
+            includes = self.codegen_includes(ir)?,
+            primary_namespace = ir.name.0,
+        ))
+    }
+
     fn codegen_enum_decl(
         &self,
         declarations: &Vec<Decl<'_>>,
@@ -278,8 +939,19 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
                 _ => None,
             })
             .map(|data| {
+                validate_enum_members(data)?;
+                if self.options.rust_enum_style == RustEnumStyle::NativeEnum {
+                    return self.codegen_native_enum(data, ir);
+                }
                 let mut enum_defines = Vec::new();
-                let ty = type_to_rust_str(&data._type.to_type(), &data.maybe_attributes, ir)?;
+                let mut known_arms = Vec::new();
+                let mut known_values = HashSet::new();
+                let ty = type_to_rust_str(
+                    &data._type.to_type(),
+                    &data.maybe_attributes,
+                    ir,
+                    &self.options,
+                )?;
                 for v in &data.members {
                     let c_name = v.name.0.as_str().to_uppercase();
                     let name = if c_name.chars().next().unwrap().is_numeric() {
@@ -297,10 +969,23 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
                         name = name,
                         val = value,
                     ));
+                    // Aliases share an arm with the first member holding their value, as a second
+                    // arm would be unreachable.
+                    if known_values.insert(v.value.value_string()) {
+                        known_arms.push(format!("            Self::{} => true,", name));
+                    }
                 }
 
+                // Values that arrive over FFI are held as-is by the newtype, so unknown values
+                // never panic. is_known() lets callers check against the declared members.
+                enum_defines.push(format!(
+                    include_str!("templates/rust/enum_is_known.rs"),
+                    known_arms = known_arms.join("\n")
+                ));
+
                 Ok(format!(
                     include_str!("templates/rust/enum.rs"),
+                    ordinal = self.ordinal(&data.name),
                     ty = ty,
                     name = data.name.get_name(),
                     enum_decls = enum_defines.join("\n")
@@ -310,6 +995,44 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
             .join(""))
     }
 
+    fn codegen_native_enum(&self, data: &Enum, ir: &FidlIr) -> Result<String, Error> {
+        let mut values: HashMap<&str, &str> = HashMap::new();
+        let mut variants = Vec::new();
+        for v in &data.members {
+            if let Some(other) = values.insert(v.value.value_string(), &v.name.0) {
+                return Err(anyhow!(
+                    "Members {} and {} of enum {} share the value {}, which a native Rust enum \
+                     can't represent",
+                    other,
+                    v.name.0,
+                    data.name.0,
+                    v.value.value_string()
+                ));
+            }
+            let c_name = v.name.0.as_str().to_uppercase();
+            let name = if c_name.chars().next().unwrap().is_numeric() {
+                format!("_{}", c_name)
+            } else {
+                c_name
+            };
+            let value = match v.value {
+                Constant::Identifier { ref expression, .. } => expression,
+                Constant::Literal { ref expression, .. } => expression,
+                Constant::BinaryOperator { ref expression, .. } => expression,
+            };
+            variants.push(format!("    {name} = {val},", name = name, val = value));
+        }
+
+        Ok(format!(
+            include_str!("templates/rust/native_enum.rs"),
+            ordinal = self.ordinal(&data.name),
+            ty =
+                type_to_rust_str(&data._type.to_type(), &data.maybe_attributes, ir, &self.options)?,
+            name = data.name.get_name(),
+            variants = variants.join("\n")
+        ))
+    }
+
     fn codegen_bits_decl(
         &self,
         declarations: &Vec<Decl<'_>>,
@@ -323,7 +1046,7 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
             })
             .map(|data| {
                 let mut enum_defines = Vec::new();
-                let ty = type_to_rust_str(&data._type, &data.maybe_attributes, ir)?;
+                let ty = type_to_rust_str(&data._type, &data.maybe_attributes, ir, &self.options)?;
                 for v in &data.members {
                     let c_name = v.name.0.as_str().to_uppercase();
                     let name = if c_name.chars().next().unwrap().is_numeric() {
@@ -345,6 +1068,7 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
 
                 Ok(format!(
                     include_str!("templates/rust/enum.rs"),
+                    ordinal = self.ordinal(&data.name),
                     ty = ty,
                     name = data.name.get_name(),
                     enum_decls = enum_defines.join("\n")
@@ -366,18 +1090,47 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
                 _ => None,
             })
             .map(|data| {
+                // Rust rejects integer literals out of their type's range, so catch them here
+                // rather than in the generated code.
+                if let (
+                    Type::Primitive { subtype },
+                    Constant::Literal { literal: Literal::Numeric { value, .. }, .. },
+                ) = (&data._type, &data.value)
+                {
+                    if let Some((ty, min, max)) = integer_range(subtype) {
+                        if !matches!(value.parse::<i128>(), Ok(v) if v >= min && v <= max) {
+                            return Err(anyhow!(
+                                "Constant {} has value {}, which doesn't fit in {}",
+                                data.name.get_name(),
+                                value,
+                                ty
+                            ));
+                        }
+                    }
+                }
                 let value = match &data.value {
                     Constant::Identifier { expression, .. } => expression,
                     Constant::Literal { expression, .. } => expression,
+                    Constant::BinaryOperator { value, .. }
+                        if self.options.evaluate_binary_operators =>
+                    {
+                        value
+                    }
                     Constant::BinaryOperator { expression, .. } => expression,
                 };
                 Ok(format!(
-                    "pub const {name}: {ty} = {val};",
+                    "{ordinal}pub const {name}: {ty} = {val};",
+                    ordinal = self.ordinal(&data.name),
                     name = data.name.get_name().to_uppercase(),
-                    ty = type_to_rust_str(&data._type, &data.maybe_attributes, ir)?,
+                    ty = type_to_rust_str(&data._type, &data.maybe_attributes, ir, &self.options)?,
                     val = value,
                 ))
             })
+            .chain(
+                get_library_version(ir).map(|version| {
+                    Ok(format!("pub const LIBRARY_VERSION: &str = \"{}\";", version))
+                }),
+            )
             .collect::<Result<Vec<_>, Error>>()?
             .join("\n"))
     }
@@ -406,6 +1159,7 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
                     }
                     if let Some(arg_type) = get_base_type_from_alias(
                         &field.experimental_maybe_from_type_alias.as_ref().map(|a| &a.name),
+                        &self.options,
                     ) {
                         field_str.push(format!(
                             "    pub {c_name}: {ty},",
@@ -413,13 +1167,44 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
                             ty = arg_type
                         ));
                     } else {
-                        field_str.push(field_to_rust_str(&field, ir)?);
+                        field_str.push(field_to_rust_str(&field, ir, &self.options)?);
                     };
+                    check_packed_field(
+                        &data.name,
+                        &data.maybe_attributes,
+                        &field.name.0,
+                        &field._type,
+                        ir,
+                    )?;
                 }
+                let copy = !holds_typed_handle(
+                    &Type::Identifier { identifier: data.name.clone(), nullable: false },
+                    &mut HashSet::new(),
+                    ir,
+                    &self.options,
+                )?;
+                let serde = match &self.options.serde_feature {
+                    Some(feature)
+                        if can_derive_serde(
+                            &Type::Identifier { identifier: data.name.clone(), nullable: false },
+                            &mut HashSet::new(),
+                            ir,
+                        )? =>
+                    {
+                        format!(
+                            "#[cfg_attr(feature = \"{}\", \
+                             derive(serde::Serialize, serde::Deserialize))]\n",
+                            feature
+                        )
+                    }
+                    _ => String::new(),
+                };
                 Ok(format!(
                     include_str!("templates/rust/struct.rs"),
-                    debug = ", Debug",
+                    ordinal = self.ordinal(&data.name),
+                    copy = if copy { "Copy, Clone, " } else { "" },
                     partial_eq = if partial_eq { ", PartialEq" } else { "" },
+                    serde = serde,
                     name = data.name.get_name(),
                     struct_fields = field_str.join("\n"),
                     alignment = alignment,
@@ -455,12 +1240,27 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
                     if !can_derive_partialeq(&field._type.as_ref().unwrap(), &mut parents, ir)? {
                         partial_eq = false;
                     }
-                    field_str.push(table_field_to_rust_str(&field, ir)?);
+                    field_str.push(table_field_to_rust_str(&field, ir, &self.options)?);
+                    check_packed_field(
+                        &data.name,
+                        &data.maybe_attributes,
+                        &field.name.as_ref().unwrap().0,
+                        field._type.as_ref().unwrap(),
+                        ir,
+                    )?;
                 }
+                let copy = !holds_typed_handle(
+                    &Type::Identifier { identifier: data.name.clone(), nullable: false },
+                    &mut HashSet::new(),
+                    ir,
+                    &self.options,
+                )?;
                 Ok(format!(
                     include_str!("templates/rust/struct.rs"),
-                    debug = ", Debug",
+                    ordinal = self.ordinal(&data.name),
+                    copy = if copy { "Copy, Clone, " } else { "" },
                     partial_eq = if partial_eq { ", PartialEq" } else { "" },
+                    serde = "",
                     name = data.name.get_name(),
                     struct_fields = field_str.join("\n"),
                     alignment = alignment,
@@ -483,6 +1283,18 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
             })
             .map(|data| {
                 let alignment = if data.maybe_attributes.has("Packed") { "C, packed" } else { "C" };
+                // Unions always derive Copy, so they can't own handles.
+                if holds_typed_handle(
+                    &Type::Identifier { identifier: data.name.clone(), nullable: false },
+                    &mut HashSet::new(),
+                    ir,
+                    &self.options,
+                )? {
+                    return Err(anyhow!(
+                        "Union {} holds typed handles, which can't be copied",
+                        data.name.get_name()
+                    ));
+                }
 
                 let field_str = data
                     .members
@@ -491,6 +1303,7 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
                     .map(|field| {
                         let ty = if let Some(arg_type) = get_base_type_from_alias(
                             &field.experimental_maybe_from_type_alias.as_ref().map(|a| &a.name),
+                            &self.options,
                         ) {
                             arg_type
                         } else {
@@ -498,19 +1311,25 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
                                 &field._type.as_ref().unwrap(),
                                 &field.maybe_attributes,
                                 ir,
+                                &self.options,
                             )?
                         };
-                        Ok(format!(
-                            "    pub {c_name}: {ty},",
-                            c_name = to_c_name(&field.name.as_ref().unwrap().0),
-                            ty = ty
-                        ))
+                        let c_name = to_c_name(&field.name.as_ref().unwrap().0);
+                        check_packed_field(
+                            &data.name,
+                            &data.maybe_attributes,
+                            &c_name,
+                            field._type.as_ref().unwrap(),
+                            ir,
+                        )?;
+                        Ok(format!("    pub {c_name}: {ty},", c_name = c_name, ty = ty))
                     })
                     .collect::<Result<Vec<_>, Error>>()?
                     .join("\n");
 
                 Ok(format!(
                     include_str!("templates/rust/union.rs"),
+                    ordinal = self.ordinal(&data.name),
                     name = data.name.get_name(),
                     union_fields = field_str,
                     alignment = alignment,
@@ -520,6 +1339,232 @@ impl<'a, W: io::Write> RustBackend<'a, W> {
             .join("\n"))
     }
 
+    fn codegen_protocol_decl(
+        &self,
+        declarations: &Vec<Decl<'_>>,
+        ir: &FidlIr,
+    ) -> Result<String, Error> {
+        Ok(declarations
+            .iter()
+            .filter_map(|decl| match decl {
+                Decl::Interface { data } => Some(data),
+                _ => None,
+            })
+            .filter(|data| {
+                ProtocolType::from(&data.maybe_attributes) != ProtocolType::Callback
+                    && for_banjo_transport(&data.maybe_attributes)
+            })
+            .map(|data| {
+                let mut protocol_fns = Vec::new();
+                let mut safe_protocol_fns = Vec::new();
+                for m in &data.methods {
+                    if m.maybe_attributes.has("Async") {
+                        return Err(anyhow!(
+                            "Async method {} of {} isn't supported by the Rust backend",
+                            m.name.0,
+                            data.name.get_name()
+                        ));
+                    }
+                    let fn_name = to_c_name(&m.name.0);
+                    let return_type = protocol_return_type(m, ir, &self.options)?;
+                    let method_params =
+                        protocol_params(m, return_type.is_some(), ir, &self.options)?;
+                    let raw_params = protocol_in_params(m, &method_params, ir, &self.options)?
+                        .into_iter()
+                        .chain(protocol_out_params(&method_params, ir, &self.options)?)
+                        .collect::<Vec<_>>();
+                    let args = raw_params
+                        .iter()
+                        .map(|(name, _)| name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let params = raw_params
+                        .iter()
+                        .map(|(name, ty)| format!("{}: {}", name, ty))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let ret =
+                        return_type.as_ref().map_or(String::new(), |ty| format!(" -> {}", ty));
+
+                    protocol_fns.push(format!(
+                        "    pub {fn_name}: unsafe extern \"C\" fn(ctx: *mut u8{sep}{params})\
+                         {ret},",
+                        fn_name = fn_name,
+                        sep = if params.is_empty() { "" } else { ", " },
+                        params = params,
+                        ret = ret,
+                    ));
+
+                    let status_type = format!("{}::zx_status_t", self.options.zircon_path);
+                    let call = format!(
+                        "((*self.ops).{fn_name})(self.ctx{sep}{args})",
+                        fn_name = fn_name,
+                        sep = if args.is_empty() { "" } else { ", " },
+                        args = args,
+                    );
+                    let status_result =
+                        self.options.status_results && return_type.as_ref() == Some(&status_type);
+                    let (ret, call) = if status_result {
+                        let wrapper_path = &self.options.zircon_wrapper_path;
+                        (
+                            format!(" -> Result<(), {}::Status>", wrapper_path),
+                            format!("{}::Status::ok({})", wrapper_path, call),
+                        )
+                    } else {
+                        (ret, call)
+                    };
+                    // Raw pointers can't be checked here, so such methods are left unsafe.
+                    let takes_pointers = protocol_takes_pointers(&method_params, ir)?;
+                    let (qualifier, body) = if takes_pointers {
+                        ("unsafe ", call)
+                    } else {
+                        ("", format!("unsafe {{ {} }}", call))
+                    };
+                    safe_protocol_fns.push(format!(
+                        "    pub {qualifier}fn {fn_name}(&self{sep}{params}){ret} {{\n        \
+                         {body}\n    }}",
+                        qualifier = qualifier,
+                        fn_name = fn_name,
+                        sep = if params.is_empty() { "" } else { ", " },
+                        params = params,
+                        ret = ret,
+                        body = body,
+                    ));
+
+                    if let Some(feature) = &self.options.async_wrappers_feature {
+                        if let Some(async_fn) = self.codegen_protocol_async_fn(
+                            &method_params,
+                            &fn_name,
+                            return_type.as_ref(),
+                            status_result,
+                            takes_pointers,
+                            ir,
+                        )? {
+                            safe_protocol_fns.push(format!(
+                                "    #[cfg(feature = \"{}\")]\n{}",
+                                feature, async_fn
+                            ));
+                        }
+                    }
+
+                    // References and slices guarantee what the raw pointers can't, so the safe
+                    // wrapper just forwards them.
+                    let feature = match &self.options.safe_wrappers_feature {
+                        Some(feature) if takes_pointers => feature,
+                        _ => continue,
+                    };
+                    let (safe_params, safe_args) =
+                        match protocol_safe_params(&method_params, ir, &self.options)? {
+                            Some(safe) => safe,
+                            None => continue,
+                        };
+                    safe_protocol_fns.push(format!(
+                        "    #[cfg(feature = \"{feature}\")]\n    \
+                         pub fn {fn_name}_safe(&self{sep}{params}){ret} {{\n        \
+                         unsafe {{ self.{fn_name}({args}) }}\n    }}",
+                        feature = feature,
+                        fn_name = fn_name,
+                        sep = if safe_params.is_empty() { "" } else { ", " },
+                        params = safe_params.join(", "),
+                        ret = ret,
+                        args = safe_args.join(", "),
+                    ));
+                }
+                Ok(format!(
+                    include_str!("templates/rust/protocol.rs"),
+                    ordinal = self.ordinal(&data.name),
+                    protocol_name = data.name.get_name(),
+                    protocol_name_upper = to_c_name(data.name.get_name()).to_uppercase(),
+                    zircon_wrapper_path = self.options.zircon_wrapper_path,
+                    protocol_fns = protocol_fns.join("\n"),
+                    safe_protocol_fns = safe_protocol_fns.join("\n\n"),
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?
+            .join("\n"))
+    }
+
+    // Returns an async wrapper around a protocol method that runs the blocking call on a thread
+    // pool and returns the method's out parameters along with its return value, or None if the
+    // method takes parameters the wrapper can't own.
+    fn codegen_protocol_async_fn(
+        &self,
+        method_params: &ProtocolParams<'_>,
+        fn_name: &str,
+        return_type: Option<&String>,
+        status_result: bool,
+        takes_pointers: bool,
+        ir: &FidlIr,
+    ) -> Result<Option<String>, Error> {
+        let (params, args, outs) = match protocol_async_params(method_params, ir, &self.options)? {
+            Some(async_params) => async_params,
+            None => return Ok(None),
+        };
+        let call = format!("proto.{}({})", fn_name, args.join(", "));
+
+        // The values the wrapper returns, as expressions and types.
+        let values = return_type
+            .filter(|_| !status_result)
+            .map(|ty| ("ret".to_string(), ty.clone()))
+            .into_iter()
+            .chain(outs.iter().map(|(name, ty)| (format!("{}.assume_init()", name), ty.clone())))
+            .collect::<Vec<_>>();
+        let (value, value_type) = match values.len() {
+            0 => ("()".to_string(), "()".to_string()),
+            1 => values[0].clone(),
+            _ => (
+                format!("({})", values.iter().map(|v| v.0.as_str()).collect::<Vec<_>>().join(", ")),
+                format!("({})", values.iter().map(|v| v.1.as_str()).collect::<Vec<_>>().join(", ")),
+            ),
+        };
+        let (ret, value) = if status_result {
+            (
+                format!(" -> Result<{}, {}::Status>", value_type, self.options.zircon_wrapper_path),
+                format!("ret.map(|()| {})", value),
+            )
+        } else if values.is_empty() {
+            (String::new(), value)
+        } else {
+            (format!(" -> {}", value_type), value)
+        };
+
+        let unsafe_block = if takes_pointers || !outs.is_empty() { "unsafe " } else { "" };
+        let closure = if outs.is_empty() {
+            format!("{}{{ {} }}", unsafe_block, call)
+        } else {
+            let mut lines = outs
+                .iter()
+                .map(|(name, _)| format!("let mut {} = core::mem::MaybeUninit::uninit();", name))
+                .collect::<Vec<_>>();
+            if return_type.is_some() {
+                lines.push(format!("let ret = {};", call));
+            } else {
+                lines.push(format!("{};", call));
+            }
+            lines.push(value);
+            format!("{}{{\n            {}\n        }}", unsafe_block, lines.join("\n            "))
+        };
+        Ok(Some(format!(
+            "    pub async fn {fn_name}_async(&self{sep}{params}){ret} {{\n        \
+             let proto = Self {{ ops: self.ops, ctx: self.ctx }};\n        \
+             fuchsia_async::unblock(move || {closure}).await\n    }}",
+            fn_name = fn_name,
+            sep = if params.is_empty() { "" } else { ", " },
+            params = params.join(", "),
+            ret = ret,
+            closure = closure,
+        )))
+    }
+
+    // Returns the ordinal comment to place before the named declaration, if enabled.
+    fn ordinal(&self, name: &CompoundIdentifier) -> String {
+        if self.options.declaration_ordinals {
+            ordinal_comment(name)
+        } else {
+            String::new()
+        }
+    }
+
     fn codegen_includes(&self, ir: &FidlIr) -> Result<String, Error> {
         Ok(ir
             .library_dependencies
@@ -541,18 +1586,10 @@ impl<'a, W: io::Write> Backend<'a, W> for RustBackend<'a, W> {
     fn codegen(&mut self, ir: FidlIr) -> Result<(), Error> {
         let decl_order = get_declarations(&ir)?;
 
-        let zircon_include =
-            if has_zircon_dep(&ir) { "use fuchsia_zircon_types as zircon_types;" } else { "" };
-
-        self.w.write_fmt(format_args!(
-            include_str!("templates/rust/header.rs"),
-            zircon_include = zircon_include,
-            includes = self.codegen_includes(&ir)?,
-            primary_namespace = ir.name.0,
-        ))?;
+        let mut output = self.codegen_header(&ir)?;
 
         if ir.name.0 != "zx" {
-            self.w.write_fmt(format_args!(
+            output.push_str(&format!(
                 include_str!("templates/rust/body.rs"),
                 enum_decls = self.codegen_enum_decl(&decl_order, &ir)?,
                 bits_decls = self.codegen_bits_decl(&decl_order, &ir)?,
@@ -560,9 +1597,40 @@ impl<'a, W: io::Write> Backend<'a, W> for RustBackend<'a, W> {
                 struct_decls = self.codegen_struct_decl(&decl_order, &ir)?,
                 table_decls = self.codegen_table_decl(&decl_order, &ir)?,
                 union_decls = self.codegen_union_decl(&decl_order, &ir)?,
-            ))?;
+            ));
+            if self.options.rust_protocols {
+                let protocol_decls = self.codegen_protocol_decl(&decl_order, &ir)?;
+                if !protocol_decls.is_empty() {
+                    output.push_str(&format!("{}\n", protocol_decls));
+                }
+            }
         }
 
+        let output = with_newline_style(with_trailing_newline(output), self.options.newline_style);
+        self.w.write_all(output.as_bytes())?;
         Ok(())
     }
+
+    fn symbol_index(&self, ir: &FidlIr) -> Result<Vec<SymbolIndexEntry>, Error> {
+        if ir.name.0 == "zx" {
+            return Ok(Vec::new());
+        }
+        let mut index = Vec::new();
+        for decl in get_declarations(ir)? {
+            let symbol = match decl {
+                Decl::Const { data } => data.name.get_name().to_uppercase(),
+                Decl::Enum { data } => data.name.get_name().to_string(),
+                Decl::Bits { data } => data.name.get_name().to_string(),
+                Decl::Struct { data } => data.name.get_name().to_string(),
+                Decl::Table { data } => data.name.get_name().to_string(),
+                Decl::Union { data } => data.name.get_name().to_string(),
+                // Protocols and aliases aren't generated.
+                Decl::Interface { .. } | Decl::TypeAlias { .. } => continue,
+            };
+            if let Some(location) = decl.location() {
+                index.push(SymbolIndexEntry { symbol, location: location.clone() });
+            }
+        }
+        Ok(index)
+    }
 }
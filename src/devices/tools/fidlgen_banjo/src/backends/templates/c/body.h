@@ -1,4 +1,4 @@
-__BEGIN_CDECLS
+{begin_cdecls}
 
 // Forward declarations
 {declarations}
@@ -9,4 +9,4 @@ __BEGIN_CDECLS
 // Helpers
 {helpers}
 
-__END_CDECLS
+{end_cdecls}
@@ -3,7 +3,7 @@
 // found in the LICENSE file.
 
 // WARNING: THIS FILE IS MACHINE GENERATED. DO NOT EDIT.
-// Generated from the {primary_namespace} banjo file
+// Generated from the {primary_namespace} banjo file{symbol_prefix_note}
 
 #pragma once
 
@@ -1,5 +1,5 @@
-    const {ty} {name}2 = {{
-        .ops = {name}_ops,
-        .ctx = {name}_ctx,
-    }};
-    const {ty}* {name} = &{name}2;
+{indent}const {ty} {name}2 = {{
+{indent}{indent}.ops = {name}_ops,
+{indent}{indent}.ctx = {name}_ctx,
+{indent}}};
+{indent}const {ty}* {name} = &{name}2;
@@ -1,6 +1,6 @@
 {protocol_def}
 
 struct {protocol_name}_protocol {{
-    {protocol_name}_protocol_ops_t* ops;
-    void* ctx;
+{indent}{protocol_name}_protocol_ops_t* ops;
+{indent}void* ctx;
 }};
@@ -1,4 +1,4 @@
-#[repr(C)]
+{ordinal}#[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct {name}(pub {ty});
 
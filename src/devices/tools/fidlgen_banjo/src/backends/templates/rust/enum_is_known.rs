@@ -0,0 +1,7 @@
+
+    pub fn is_known(&self) -> bool {{
+        match *self {{
+{known_arms}
+            _ => false,
+        }}
+    }}
\ No newline at end of file
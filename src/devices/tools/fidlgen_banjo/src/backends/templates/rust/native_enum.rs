@@ -0,0 +1,5 @@
+{ordinal}#[repr({ty})]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum {name} {{
+{variants}
+}}
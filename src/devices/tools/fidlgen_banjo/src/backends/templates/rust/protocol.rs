@@ -1,4 +1,4 @@
-#[repr(C)]
+{ordinal}#[repr(C)]
 pub struct {protocol_name}_ops_t {{
 {protocol_fns}
 }}
@@ -19,14 +19,14 @@ impl Default for {protocol_name}Protocol {{
 }}
 
 impl {protocol_name}Protocol {{
-    pub fn from_device<Ctx>(parent_device: &ddk::Device<Ctx>) -> Result<Self, zircon::Status> {{
+    pub fn from_device<Ctx>(parent_device: &ddk::Device<Ctx>) -> Result<Self, {zircon_wrapper_path}::Status> {{
         let mut ret = Self::default();
         unsafe {{
             let resp = ddk::sys::device_get_protocol(
                 parent_device.get_ptr(),
                 ddk::sys::ZX_PROTOCOL_{protocol_name_upper},
                 &mut ret as *mut _ as *mut libc::c_void);
-            zircon::Status::ok(resp).map(|_| ret)
+            {zircon_wrapper_path}::Status::ok(resp).map(|_| ret)
         }}
     }}
 
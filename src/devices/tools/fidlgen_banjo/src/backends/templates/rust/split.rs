@@ -0,0 +1,12 @@
+// Copyright 2018 The Fuchsia Authors. All rights reserved.
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+// WARNING: THIS FILE IS MACHINE GENERATED. DO NOT EDIT.
+// Generated from the {primary_namespace} banjo file
+
+#![allow(unused_imports, non_camel_case_types)]
+
+use super::*;
+
+{decls}
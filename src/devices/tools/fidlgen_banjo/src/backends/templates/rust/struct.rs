@@ -1,5 +1,5 @@
-#[repr({alignment})]
-#[derive(Copy, Clone{debug}{partial_eq})]
-pub struct {name} {{
+{ordinal}#[repr({alignment})]
+#[derive({copy}Debug{partial_eq})]
+{serde}pub struct {name} {{
 {struct_fields}
 }}
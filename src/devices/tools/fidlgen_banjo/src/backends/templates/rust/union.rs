@@ -1,4 +1,4 @@
-#[repr({alignment})]
+{ordinal}#[repr({alignment})]
 #[derive(Copy, Clone)]
 pub union {name} {{
 {union_fields}
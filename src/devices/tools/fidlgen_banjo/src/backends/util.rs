@@ -3,6 +3,7 @@
 // found in the LICENSE file.
 
 use {
+    super::NewlineStyle,
     anyhow::{anyhow, Error},
     fidl_ir_lib::fidl::{self, *},
     heck::SnakeCase,
@@ -13,6 +14,7 @@ static ATTR_NAME_DERIVE_DEBUG: &'static str = "derive_debug";
 static ATTR_NAME_DOC: &'static str = "doc";
 static ATTR_NAME_NAMESPACED: &'static str = "namespaced";
 static ATTR_NAME_TRANSPORT: &'static str = "transport";
+static ATTR_NAME_VERSION: &'static str = "version";
 
 pub enum Decl<'a> {
     Const { data: &'a fidl::Const },
@@ -25,6 +27,69 @@ pub enum Decl<'a> {
     Union { data: &'a fidl::Union },
 }
 
+impl<'a> Decl<'a> {
+    /// Returns the location of the declaration in the banjo source, if the IR
+    /// recorded one.
+    pub fn location(&self) -> Option<&'a Location> {
+        match self {
+            Decl::Const { data } => data.location.as_ref(),
+            Decl::Enum { data } => data.location.as_ref(),
+            Decl::Bits { data } => data.location.as_ref(),
+            Decl::Interface { data } => data.location.as_ref(),
+            Decl::Struct { data } => data.location.as_ref(),
+            Decl::Table { data } => data.location.as_ref(),
+            Decl::TypeAlias { data } => Some(&data.location),
+            Decl::Union { data } => data.location.as_ref(),
+        }
+    }
+
+    /// Returns the fully-qualified name of the declaration.
+    pub fn name(&self) -> &'a CompoundIdentifier {
+        match self {
+            Decl::Const { data } => &data.name,
+            Decl::Enum { data } => &data.name,
+            Decl::Bits { data } => &data.name,
+            Decl::Interface { data } => &data.name,
+            Decl::Struct { data } => &data.name,
+            Decl::Table { data } => &data.name,
+            Decl::TypeAlias { data } => &data.name,
+            Decl::Union { data } => &data.name,
+        }
+    }
+}
+
+/// Returns a stable ordinal for the declaration with the given fully-qualified
+/// name. It is the 64-bit FNV-1a hash of the name, so it doesn't depend on the
+/// order of declarations or change between runs.
+pub fn decl_ordinal(name: &CompoundIdentifier) -> u64 {
+    name.0
+        .bytes()
+        .fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Returns the generated code ending in exactly one newline, however many the
+/// templates left at the end.
+pub fn with_trailing_newline(mut code: String) -> String {
+    code.truncate(code.trim_end_matches('\n').len());
+    code.push('\n');
+    code
+}
+
+/// Returns the generated code with its line endings converted to the given
+/// style. The templates and the code built from them only use `\n`.
+pub fn with_newline_style(code: String, newline_style: NewlineStyle) -> String {
+    match newline_style {
+        NewlineStyle::Lf => code,
+        NewlineStyle::CrLf => code.replace('\n', "\r\n"),
+    }
+}
+
+/// Returns a comment line giving the ordinal of the declaration with the given
+/// name, to be placed before it.
+pub fn ordinal_comment(name: &CompoundIdentifier) -> String {
+    format!("// ordinal: {:#018x}\n", decl_ordinal(name))
+}
+
 pub fn get_declarations<'b>(ir: &'b FidlIr) -> Result<Vec<Decl<'b>>, Error> {
     ir.declaration_order
         .iter()
@@ -128,29 +193,101 @@ pub fn filter_interface<'b>(declaration: &Decl<'b>) -> Option<&'b Interface> {
 }
 
 pub fn get_doc_comment(maybe_attrs: &Option<Vec<Attribute>>, tabs: usize) -> String {
-    if let Some(attrs) = maybe_attrs {
-        for attr in attrs.iter() {
-            if to_lower_snake_case(&attr.name) == ATTR_NAME_DOC {
-                return match attr.get_standalone() {
-                    Ok(constant) => {
-                        let value = constant.value_string();
-                        if value.is_empty() {
-                            "".to_string()
-                        } else {
-                            let tabs: String = iter::repeat(' ').take(tabs * 4).collect();
-                            value
-                                .trim_end()
-                                .split("\n")
-                                .map(|line| format!("{}//{}\n", tabs, line))
-                                .collect()
-                        }
-                    }
-                    Err(_) => "".to_string(),
-                };
-            }
+    get_wrapped_doc_comment(maybe_attrs, &" ".repeat(tabs * 4), None)
+}
+
+/// Like `get_doc_comment`, but each line is preceded by `indent` rather than
+/// a number of four-space tabs, and lines longer than `wrap_column` are
+/// wrapped at word boundaries so that they fit within the column where
+/// possible. Words longer than the column are left on a line of their own.
+pub fn get_wrapped_doc_comment(
+    maybe_attrs: &Option<Vec<Attribute>>,
+    indent: &str,
+    wrap_column: Option<usize>,
+) -> String {
+    match get_doc_value(maybe_attrs) {
+        Some(value) => doc_comment_lines(value, indent, "//", wrap_column),
+        None => "".to_string(),
+    }
+}
+
+/// Like `get_wrapped_doc_comment`, but the doc comment is emitted as a
+/// Doxygen `/** ... */` block whose first line is tagged `@brief`.
+pub fn get_doxygen_doc_comment(
+    maybe_attrs: &Option<Vec<Attribute>>,
+    indent: &str,
+    wrap_column: Option<usize>,
+) -> String {
+    match get_doc_value(maybe_attrs) {
+        Some(value) => {
+            let lines = doc_comment_lines(
+                &format!(" @brief {}", value.trim_start()),
+                indent,
+                " *",
+                wrap_column,
+            );
+            format!("{indent}/**\n{lines}{indent} */\n", indent = indent, lines = lines)
         }
+        None => "".to_string(),
     }
-    "".to_string()
+}
+
+// Returns the value of the doc attribute, or None if there's no doc comment.
+fn get_doc_value(maybe_attrs: &Option<Vec<Attribute>>) -> Option<&str> {
+    let attr = maybe_attrs
+        .as_ref()?
+        .iter()
+        .find(|attr| to_lower_snake_case(&attr.name) == ATTR_NAME_DOC)?;
+    let value = attr.get_standalone().ok()?.value_string();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+// Returns each line of the doc comment preceded by `marker`.
+fn doc_comment_lines(
+    value: &str,
+    indent: &str,
+    marker: &str,
+    wrap_column: Option<usize>,
+) -> String {
+    value
+        .trim_end()
+        .split("\n")
+        .flat_map(|line| match wrap_column {
+            Some(column) => wrap_comment_line(indent, marker, line, column),
+            None => vec![format!("{}{}{}", indent, marker, line)],
+        })
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+// Splits a comment line into lines no wider than `column`, repeating the indentation of the line
+// on every piece.
+fn wrap_comment_line(tabs: &str, marker: &str, line: &str, column: usize) -> Vec<String> {
+    let text = line.trim_start();
+    let prefix = format!("{}{}{}", tabs, marker, &line[..line.len() - text.len()]);
+    if prefix.len() + text.len() <= column {
+        return vec![format!("{}{}", prefix, text)];
+    }
+
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if current.is_empty() {
+            current.push_str(word);
+        } else if prefix.len() + current.len() + 1 + word.len() <= column {
+            current.push(' ');
+            current.push_str(word);
+        } else {
+            lines.push(format!("{}{}", prefix, current));
+            current = word.to_string();
+        }
+    }
+    lines.push(format!("{}{}", prefix, current));
+    lines
 }
 
 /// Returns an `Option` containing the value returned by `func` when applied to
@@ -264,6 +401,13 @@ pub fn for_banjo_transport(maybe_attrs: &Option<Vec<Attribute>>) -> bool {
     .unwrap_or(false)
 }
 
+/// Returns the value of the `@version` attribute on the library, if it has a
+/// non-empty one.
+pub fn get_library_version(ir: &FidlIr) -> Option<String> {
+    apply_to_attr(&ir.maybe_attributes, ATTR_NAME_VERSION, |_, attr_value| attr_value.to_string())
+        .filter(|version| !version.is_empty())
+}
+
 //---------------------------------------------
 // Utilities shared by the four C/C++ backends.
 
@@ -305,6 +449,64 @@ pub fn primitive_type_to_c_str(ty: &PrimitiveSubtype) -> Result<String, Error> {
     }
 }
 
+/// Returns the name and the smallest and largest values of an integer type, or
+/// None if the type isn't an integer.
+pub fn integer_range(ty: &PrimitiveSubtype) -> Option<(&'static str, i128, i128)> {
+    match ty {
+        PrimitiveSubtype::Int8 => Some(("int8", i8::MIN as i128, i8::MAX as i128)),
+        PrimitiveSubtype::Int16 => Some(("int16", i16::MIN as i128, i16::MAX as i128)),
+        PrimitiveSubtype::Int32 => Some(("int32", i32::MIN as i128, i32::MAX as i128)),
+        PrimitiveSubtype::Int64 => Some(("int64", i64::MIN as i128, i64::MAX as i128)),
+        PrimitiveSubtype::Uint8 => Some(("uint8", 0, u8::MAX as i128)),
+        PrimitiveSubtype::Uint16 => Some(("uint16", 0, u16::MAX as i128)),
+        PrimitiveSubtype::Uint32 => Some(("uint32", 0, u32::MAX as i128)),
+        PrimitiveSubtype::Uint64 => Some(("uint64", 0, u64::MAX as i128)),
+        _ => None,
+    }
+}
+
+/// Returns the literal that a constant identifier ultimately refers to if it
+/// was written in a radix other than decimal, e.g. `0xFF00`, so that generated
+/// code can keep the original form rather than the decimal value from the IR.
+pub fn non_decimal_literal<'b>(identifier: &CompoundIdentifier, ir: &'b FidlIr) -> Option<&'b str> {
+    let decl = ir.const_declarations.iter().find(|decl| decl.name == *identifier)?;
+    match &decl.value {
+        Constant::Literal { expression, .. } => {
+            let digits = expression.trim_start_matches('-').to_lowercase();
+            if digits.starts_with("0x") || digits.starts_with("0b") {
+                Some(expression)
+            } else {
+                None
+            }
+        }
+        Constant::Identifier { identifier, .. } => non_decimal_literal(identifier, ir),
+        Constant::BinaryOperator { .. } => None,
+    }
+}
+
+/// Checks that the value of each member of the enum fits in the enum's
+/// underlying integer type.
+pub fn validate_enum_members(data: &Enum) -> Result<(), Error> {
+    let (name, min, max) =
+        integer_range(&data._type.to_primitive()).expect("enums have an integer type");
+    for member in &data.members {
+        let value = member.value.value_string();
+        match value.parse::<i128>() {
+            Ok(v) if v >= min && v <= max => {}
+            _ => {
+                return Err(anyhow!(
+                    "Value {} of member {} of enum {} doesn't fit in {}",
+                    value,
+                    member.name.0,
+                    data.name.0,
+                    name
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn not_callback(id: &CompoundIdentifier, ir: &FidlIr) -> Result<bool, Error> {
     if ir.is_external_decl(id)? {
         // This is a workaround for the fact that FidlIr doesn't contain attributes for external
@@ -706,4 +908,45 @@ mod tests {
             Some(vec![string_literal_attribute("Namespaced", "value", "foo")]);
         is_namespaced(&maybe_attrs_with_namespaced).expect_err("is_namespaced should fail");
     }
+
+    #[test]
+    fn wrapped_doc_comment() {
+        let maybe_attrs = Some(vec![string_literal_attribute(
+            "doc",
+            "value",
+            " The quick brown fox jumps over the lazy dog.\n Short line.\n",
+        )]);
+
+        assert_eq!(
+            get_wrapped_doc_comment(&maybe_attrs, "    ", Some(30)),
+            "    // The quick brown fox\n    // jumps over the lazy\n    // dog.\n    // Short line.\n"
+        );
+        assert_eq!(
+            get_wrapped_doc_comment(&maybe_attrs, "", None),
+            get_doc_comment(&maybe_attrs, 0)
+        );
+        assert_eq!(
+            get_doc_comment(&maybe_attrs, 0),
+            "// The quick brown fox jumps over the lazy dog.\n// Short line.\n"
+        );
+    }
+
+    #[test]
+    fn doxygen_doc_comment() {
+        let maybe_attrs = Some(vec![string_literal_attribute(
+            "doc",
+            "value",
+            " The quick brown fox jumps over the lazy dog.\n Short line.\n",
+        )]);
+
+        assert_eq!(
+            get_doxygen_doc_comment(&maybe_attrs, "    ", None),
+            "    /**\n     * @brief The quick brown fox jumps over the lazy dog.\n     * Short line.\n     */\n"
+        );
+        assert_eq!(
+            get_doxygen_doc_comment(&maybe_attrs, "", Some(30)),
+            "/**\n * @brief The quick brown fox\n * jumps over the lazy dog.\n * Short line.\n */\n"
+        );
+        assert_eq!(get_doxygen_doc_comment(&None, "", None), "");
+    }
 }
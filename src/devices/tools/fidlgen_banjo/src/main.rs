@@ -3,10 +3,15 @@
 // found in the LICENSE file.
 
 use {
-    anyhow::Error,
+    anyhow::{anyhow, Error},
     fidl_ir_lib::fidl::*,
     fidlgen_banjo_lib::backends::*,
-    std::{fs::File, io::BufReader, path::PathBuf, str::FromStr},
+    std::{
+        fs::File,
+        io::{self, BufReader},
+        path::PathBuf,
+        str::FromStr,
+    },
     structopt::StructOpt,
 };
 
@@ -16,6 +21,7 @@ enum BackendName {
     Cpp,
     CppInternal,
     CppMock,
+    IrDump,
     Rust,
 }
 
@@ -28,10 +34,11 @@ impl FromStr for BackendName {
             "cpp" => Ok(BackendName::Cpp),
             "cpp_internal" => Ok(BackendName::CppInternal),
             "cpp_mock" => Ok(BackendName::CppMock),
+            "ir_dump" => Ok(BackendName::IrDump),
             "rust" => Ok(BackendName::Rust),
             _ => Err(format!(
                 "Unrecognized backend for fidlgen_banjo. \
-                 Current valid ones are: c, cpp, cpp_internal, cpp_mock, rust"
+                 Current valid ones are: c, cpp, cpp_internal, cpp_mock, ir_dump, rust"
             )),
         }
     }
@@ -46,21 +53,209 @@ struct Flags {
     #[structopt(short = "b", long = "backend")]
     backend: BackendName,
 
-    #[structopt(short = "o", long = "output")]
-    output: PathBuf,
+    /// The file to write the bindings to. Not used with --rust-split-dir.
+    #[structopt(
+        short = "o",
+        long = "output",
+        required_unless = "rust-split-dir",
+        conflicts_with = "rust-split-dir"
+    )]
+    output: Option<PathBuf>,
+
+    /// Prefix for the names declared by the C backend.
+    #[structopt(long = "symbol-prefix", default_value = "")]
+    symbol_prefix: String,
+
+    /// Path of the module providing the zircon types for the Rust backend.
+    #[structopt(long = "zircon-path", default_value = "zircon_types")]
+    zircon_path: String,
+
+    /// Path of the module providing the fuchsia_zircon wrappers for the Rust backend.
+    #[structopt(long = "zircon-wrapper-path", default_value = "zircon")]
+    zircon_wrapper_path: String,
+
+    /// How the Rust backend represents enums: newtype or native.
+    #[structopt(long = "rust-enum-style", default_value = "newtype")]
+    rust_enum_style: RustEnumStyle,
+
+    /// Emit a function mapping enum values to member names in the C backend.
+    #[structopt(long = "enum-to-string")]
+    enum_to_string: bool,
+
+    /// Emit a member count and an array of member values for each C enum.
+    #[structopt(long = "enum-values")]
+    enum_values: bool,
+
+    /// Emit an array of member names, parallel to the array of values, for each C enum.
+    #[structopt(long = "enum-names")]
+    enum_names: bool,
+
+    /// Wrap long doc comment lines in the C backend.
+    #[structopt(long = "wrap-doc-comments")]
+    wrap_doc_comments: bool,
+
+    /// The column at which doc comments are wrapped.
+    #[structopt(long = "doc-comment-column", default_value = "80")]
+    doc_comment_column: usize,
+
+    /// Emit C doc comments as Doxygen blocks.
+    #[structopt(long = "doxygen")]
+    doxygen: bool,
+
+    /// Leave doc comments out of the C backend.
+    #[structopt(long = "no-doc-comments")]
+    no_doc_comments: bool,
+
+    /// The number of spaces to indent by in the C backend.
+    #[structopt(long = "indent-width", default_value = "4")]
+    indent_width: usize,
+
+    /// Line endings of the C and Rust output: lf or crlf.
+    #[structopt(long = "newline-style", default_value = "lf")]
+    newline_style: NewlineStyle,
+
+    /// Make the C backend's output usable from C++.
+    #[structopt(long = "cplusplus-compatible")]
+    cplusplus_compatible: bool,
+
+    /// Emit the computed value of binary operator constants in the Rust backend.
+    #[structopt(long = "evaluate-binary-operators")]
+    evaluate_binary_operators: bool,
+
+    /// Mark C structs with their alignment for bindgen.
+    #[structopt(long = "bindgen-layout")]
+    bindgen_layout: bool,
+
+    /// Follow integer fields of C structs with a comment giving their width and range.
+    #[structopt(long = "field-width-comments")]
+    field_width_comments: bool,
+
+    /// Emit a field-wise comparison function for each C struct that supports one.
+    #[structopt(long = "struct-eq")]
+    struct_eq: bool,
+
+    /// Emit ops tables and client wrappers for protocols in the Rust backend.
+    #[structopt(long = "rust-protocols")]
+    rust_protocols: bool,
+
+    /// Return zx_status_t from Rust protocol wrappers as Result<(), zircon::Status>.
+    #[structopt(long = "status-results")]
+    status_results: bool,
+
+    /// Type Rust handles with their fuchsia_zircon wrapper, e.g. zircon::Channel.
+    #[structopt(long = "typed-handles")]
+    typed_handles: bool,
+
+    /// Emit safe wrappers for Rust protocol methods taking raw pointers, gated on this feature.
+    #[structopt(long = "safe-wrappers-feature")]
+    safe_wrappers_feature: Option<String>,
+
+    /// Emit async wrappers for Rust protocol methods, gated on this feature.
+    #[structopt(long = "async-wrappers-feature")]
+    async_wrappers_feature: Option<String>,
+
+    /// Derive serde traits for Rust structs without raw pointers, gated on this feature.
+    #[structopt(long = "serde-feature")]
+    serde_feature: Option<String>,
+
+    /// Precede each declaration with a comment giving its stable ordinal.
+    #[structopt(long = "declaration-ordinals")]
+    declaration_ordinals: bool,
+
+    /// Wrap the C backend output in pragmas disabling the suppressed warnings.
+    #[structopt(long = "suppress-warnings")]
+    suppress_warnings: bool,
+
+    /// A warning to disable with --suppress-warnings, e.g. -Wunused. May be repeated.
+    #[structopt(long = "suppressed-warning", number_of_values = 1, allow_hyphen_values = true)]
+    suppressed_warning: Vec<String>,
+
+    /// Optional JSON file listing the generated symbols and their source locations.
+    #[structopt(long = "symbol-index")]
+    symbol_index: Option<PathBuf>,
+
+    /// Directory to write the Rust bindings into, split into a file for each
+    /// kind of declaration along with a mod.rs re-exporting them.
+    #[structopt(long = "rust-split-dir")]
+    rust_split_dir: Option<PathBuf>,
 }
 
 fn main() -> Result<(), Error> {
     let flags = Flags::from_args();
-    let mut output = File::create(flags.output)?;
+    if flags.rust_split_dir.is_some() && !matches!(flags.backend, BackendName::Rust) {
+        return Err(anyhow!("--rust-split-dir is only supported by the rust backend"));
+    }
+    let mut output: Box<dyn io::Write> = match flags.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::sink()),
+    };
+    let options = CodegenOptions {
+        symbol_prefix: flags.symbol_prefix,
+        zircon_path: flags.zircon_path,
+        zircon_wrapper_path: flags.zircon_wrapper_path,
+        rust_enum_style: flags.rust_enum_style,
+        enum_to_string: flags.enum_to_string,
+        enum_values: flags.enum_values,
+        enum_names: flags.enum_names,
+        doc_comment_column: if flags.wrap_doc_comments {
+            Some(flags.doc_comment_column)
+        } else {
+            None
+        },
+        doxygen_doc_comments: flags.doxygen,
+        doc_comments: !flags.no_doc_comments,
+        indent_width: flags.indent_width,
+        newline_style: flags.newline_style,
+        cplusplus_compatible: flags.cplusplus_compatible,
+        evaluate_binary_operators: flags.evaluate_binary_operators,
+        bindgen_layout: flags.bindgen_layout,
+        field_width_comments: flags.field_width_comments,
+        struct_eq: flags.struct_eq,
+        rust_protocols: flags.rust_protocols,
+        status_results: flags.status_results,
+        typed_handles: flags.typed_handles,
+        safe_wrappers_feature: flags.safe_wrappers_feature,
+        async_wrappers_feature: flags.async_wrappers_feature,
+        serde_feature: flags.serde_feature,
+        declaration_ordinals: flags.declaration_ordinals,
+        suppressed_warnings: if !flags.suppress_warnings {
+            Vec::new()
+        } else if flags.suppressed_warning.is_empty() {
+            DEFAULT_SUPPRESSED_WARNINGS.iter().map(|w| w.to_string()).collect()
+        } else {
+            flags.suppressed_warning
+        },
+    };
+    let split_options = options.clone();
     let mut backend: Box<dyn Backend<'_, _>> = match flags.backend {
-        BackendName::C => Box::new(CBackend::new(&mut output)),
+        BackendName::C => Box::new(CBackend::with_options(&mut output, options)),
         BackendName::Cpp => Box::new(CppBackend::new(&mut output)),
         BackendName::CppInternal => Box::new(CppInternalBackend::new(&mut output)),
         BackendName::CppMock => Box::new(CppMockBackend::new(&mut output)),
-        BackendName::Rust => Box::new(RustBackend::new(&mut output)),
+        BackendName::IrDump => Box::new(IrDumpBackend::new(&mut output)),
+        BackendName::Rust => Box::new(RustBackend::with_options(&mut output, options)),
     };
     let mut ir: FidlIr = serde_json::from_reader(BufReader::new(File::open(flags.ir)?))?;
     ir.build()?;
-    backend.codegen(ir)
+    if let Some(path) = flags.symbol_index {
+        let entries = backend
+            .symbol_index(&ir)?
+            .into_iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "symbol": entry.symbol,
+                    "file": entry.location.filename,
+                    "line": entry.location.line,
+                    "column": entry.location.column,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_writer_pretty(File::create(path)?, &entries)?;
+    }
+    match flags.rust_split_dir {
+        Some(dir) => {
+            RustBackend::with_options(&mut io::sink(), split_options).codegen_split(ir, &dir)
+        }
+        None => backend.codegen(ir),
+    }
 }
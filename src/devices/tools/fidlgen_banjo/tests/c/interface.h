@@ -14,9 +14,13 @@
 __BEGIN_CDECLS
 
 // Forward declarations
+// Types of cookies CookieMaker can prep.
 typedef uint32_t cookie_kind_t;
+// Universal favorite.
 #define COOKIE_KIND_CHOCOLATE UINT32_C(0)
+// Holiday favorite.
 #define COOKIE_KIND_GINGERBREAD UINT32_C(1)
+// Test-writer's favorite.
 #define COOKIE_KIND_SNICKERDOODLE UINT32_C(2)
 typedef void (*cookie_maker_prep_callback)(void* ctx, uint64_t token);
 typedef void (*cookie_maker_bake_callback)(void* ctx, zx_status_t s);
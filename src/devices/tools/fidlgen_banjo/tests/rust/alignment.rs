@@ -45,8 +45,3 @@ pub struct packing_0 {
     pub i32_0: i32,
     pub i16_1: i16,
 }
-
-
-
-
-
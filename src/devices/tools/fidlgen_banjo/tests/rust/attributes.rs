@@ -58,4 +58,3 @@ impl std::fmt::Debug for none_union {
         write!(f, "<none_union>")
     }
 }
-
@@ -10,8 +10,3 @@
 
 
 pub const BINARY_NUMBER: u32 = 0b100100100;
-
-
-
-
-
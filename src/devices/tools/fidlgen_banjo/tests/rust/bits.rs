@@ -219,5 +219,3 @@ impl std::ops::BitXorAssign for uint16_bits {
         *self = Self(self.0 ^ rhs.0)
     }
 }
-
-
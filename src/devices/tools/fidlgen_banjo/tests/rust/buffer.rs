@@ -16,8 +16,3 @@ pub struct SomeData {
     pub one_buffer: *const u8,
     pub one_size: usize,
 }
-
-
-
-
-
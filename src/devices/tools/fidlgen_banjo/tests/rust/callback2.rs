@@ -6,12 +6,3 @@
 // Generated from the banjo.examples.callback2 banjo file
 
 #![allow(unused_imports, non_camel_case_types)]
-
-
-
-
-
-
-
-
-
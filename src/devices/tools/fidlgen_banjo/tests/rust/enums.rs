@@ -22,6 +22,17 @@ impl uint8_enum {
     pub const KTHREE: Self = Self(3);
     pub const KFOUR: Self = Self(4);
     pub const KFIVE: Self = Self(5);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::KONE => true,
+            Self::KTWO => true,
+            Self::KTHREE => true,
+            Self::KFOUR => true,
+            Self::KFIVE => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for uint8_enum {
@@ -75,6 +86,20 @@ impl uint64_enum {
     pub const KSIX: Self = Self(6);
     pub const KSEVEN: Self = Self(7);
     pub const KEIGHT: Self = Self(8);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::KONE => true,
+            Self::KTWO => true,
+            Self::KTHREE => true,
+            Self::KFOUR => true,
+            Self::KFIVE => true,
+            Self::KSIX => true,
+            Self::KSEVEN => true,
+            Self::KEIGHT => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for uint64_enum {
@@ -127,6 +152,19 @@ impl uint32_enum {
     pub const KFIVE: Self = Self(5);
     pub const KSIX: Self = Self(6);
     pub const KSEVEN: Self = Self(7);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::KONE => true,
+            Self::KTWO => true,
+            Self::KTHREE => true,
+            Self::KFOUR => true,
+            Self::KFIVE => true,
+            Self::KSIX => true,
+            Self::KSEVEN => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for uint32_enum {
@@ -178,6 +216,18 @@ impl uint16_enum {
     pub const KFOUR: Self = Self(4);
     pub const KFIVE: Self = Self(5);
     pub const KSIX: Self = Self(6);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::KONE => true,
+            Self::KTWO => true,
+            Self::KTHREE => true,
+            Self::KFOUR => true,
+            Self::KFIVE => true,
+            Self::KSIX => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for uint16_enum {
@@ -225,6 +275,14 @@ pub struct int8_enum(pub i8);
 impl int8_enum {
     pub const KNEGATIVEONE: Self = Self(-1);
     pub const KONE: Self = Self(1);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::KNEGATIVEONE => true,
+            Self::KONE => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for int8_enum {
@@ -275,6 +333,17 @@ impl int64_enum {
     pub const KTWO: Self = Self(2);
     pub const KTHREE: Self = Self(3);
     pub const KFOUR: Self = Self(4);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::KNEGATIVEONE => true,
+            Self::KONE => true,
+            Self::KTWO => true,
+            Self::KTHREE => true,
+            Self::KFOUR => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for int64_enum {
@@ -324,6 +393,16 @@ impl int32_enum {
     pub const KONE: Self = Self(1);
     pub const KTWO: Self = Self(2);
     pub const KTHREE: Self = Self(3);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::KNEGATIVEONE => true,
+            Self::KONE => true,
+            Self::KTWO => true,
+            Self::KTHREE => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for int32_enum {
@@ -372,6 +451,15 @@ impl int16_enum {
     pub const KNEGATIVEONE: Self = Self(-1);
     pub const KONE: Self = Self(1);
     pub const KTWO: Self = Self(2);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::KNEGATIVEONE => true,
+            Self::KONE => true,
+            Self::KTWO => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for int16_enum {
@@ -412,6 +500,3 @@ impl std::ops::BitXorAssign for int16_enum {
         *self = Self(self.0 ^ rhs.0)
     }
 }
-
-
-
@@ -21,8 +21,3 @@ pub struct Bar {
 pub struct Foo {
     pub b: Bar,
 }
-
-
-
-
-
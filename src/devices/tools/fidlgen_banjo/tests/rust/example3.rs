@@ -16,8 +16,3 @@ pub struct Point {
     pub x: i64,
     pub y: i64,
 }
-
-
-
-
-
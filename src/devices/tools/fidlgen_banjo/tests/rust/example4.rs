@@ -23,6 +23,13 @@ pub struct Enum(pub u32);
 
 impl Enum {
     pub const X: Self = Self(23);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::X => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for Enum {
@@ -63,6 +70,3 @@ impl std::ops::BitXorAssign for Enum {
         *self = Self(self.0 ^ rhs.0)
     }
 }
-
-
-
@@ -10,8 +10,3 @@
 
 
 pub const X: i32 = 23;
-
-
-
-
-
@@ -17,8 +17,3 @@ pub struct foo {
     pub u64_0: u64,
     pub u8_1: u8,
 }
-
-
-
-
-
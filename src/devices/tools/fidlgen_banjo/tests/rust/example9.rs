@@ -25,6 +25,14 @@ pub struct EchoMe(pub u32);
 impl EchoMe {
     pub const ZERO: Self = Self(0);
     pub const ONE: Self = Self(1);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::ZERO => true,
+            Self::ONE => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for EchoMe {
@@ -65,6 +73,3 @@ impl std::ops::BitXorAssign for EchoMe {
         *self = Self(self.0 ^ rhs.0)
     }
 }
-
-
-
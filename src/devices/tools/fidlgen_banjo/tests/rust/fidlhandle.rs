@@ -13,10 +13,6 @@ use fuchsia_zircon_types as zircon_types;
 #[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct SomeStruct {
+    /// Handle subtype: vmo.
     pub vmo: zircon_types::zx_handle_t,
 }
-
-
-
-
-
@@ -13,7 +13,7 @@ use fuchsia_zircon_types as zircon_types;
 #[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct CookieJarArgs {
-    pub name: [u8; 100 as usize],
+    pub name: [u8; 100],
 }
 
 
@@ -25,6 +25,15 @@ impl CookieKind {
     pub const CHOCOLATE: Self = Self(0);
     pub const GINGERBREAD: Self = Self(1);
     pub const SNICKERDOODLE: Self = Self(2);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::CHOCOLATE => true,
+            Self::GINGERBREAD => true,
+            Self::SNICKERDOODLE => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for CookieKind {
@@ -79,5 +88,3 @@ impl std::fmt::Debug for ChangeArgs {
         write!(f, "<ChangeArgs>")
     }
 }
-
-
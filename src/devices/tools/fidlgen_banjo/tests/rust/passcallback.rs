@@ -27,6 +27,16 @@ impl Direction {
     pub const DOWN: Self = Self(2);
     pub const LEFT: Self = Self(3);
     pub const RIGHT: Self = Self(4);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::UP => true,
+            Self::DOWN => true,
+            Self::LEFT => true,
+            Self::RIGHT => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for Direction {
@@ -74,6 +84,14 @@ pub struct Action(pub u32);
 impl Action {
     pub const START: Self = Self(0x1);
     pub const STOP: Self = Self(0x2);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::START => true,
+            Self::STOP => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for Action {
@@ -114,6 +132,3 @@ impl std::ops::BitXorAssign for Action {
         *self = Self(self.0 ^ rhs.0)
     }
 }
-
-
-
@@ -16,8 +16,3 @@ pub struct Point {
     pub x: f32,
     pub y: f32,
 }
-
-
-
-
-
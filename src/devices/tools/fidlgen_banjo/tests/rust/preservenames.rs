@@ -17,8 +17,3 @@ pub struct SomeData {
     pub kSomeValue: u32,
     pub one_more_field: *const std::os::raw::c_char,
 }
-
-
-
-
-
@@ -10,8 +10,3 @@
 use fuchsia_zircon_types as zircon_types;
 
 pub const ARRAY_SIZE: u32 = 32;
-
-
-
-
-
@@ -8,10 +8,3 @@
 #![allow(unused_imports, non_camel_case_types)]
 
 use fuchsia_zircon_types as zircon_types;
-
-
-
-
-
-
-
@@ -23,6 +23,13 @@ pub struct ThisIsAnEnum(pub u32);
 
 impl ThisIsAnEnum {
     pub const X: Self = Self(23);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::X => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for ThisIsAnEnum {
@@ -77,4 +84,3 @@ impl std::fmt::Debug for ThisIsAUnion {
         write!(f, "<ThisIsAUnion>")
     }
 }
-
@@ -10,8 +10,3 @@
 use fuchsia_zircon_types as zircon_types;
 
 pub const VECTOR_SIZE: u32 = 32;
-
-
-
-
-
@@ -38,8 +38,3 @@ pub struct MutableField {
     pub some_other_string: *const std::os::raw::c_char,
     pub some_default_string: *const std::os::raw::c_char,
 }
-
-
-
-
-
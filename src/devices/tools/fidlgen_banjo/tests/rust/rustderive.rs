@@ -47,13 +47,13 @@ pub struct EnumGrandParentStruct {
 #[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct SmallArrayStruct {
-    pub small_array: [u8; 8 as usize],
+    pub small_array: [u8; 8],
 }
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct BigArrayStruct {
-    pub big_array: [u8; 2048 as usize],
+    pub big_array: [u8; 2048],
 }
 
 #[repr(C)]
@@ -98,6 +98,14 @@ pub struct SomeEnum(pub u32);
 impl SomeEnum {
     pub const V1: Self = Self(1);
     pub const V2: Self = Self(2);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::V1 => true,
+            Self::V2 => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for SomeEnum {
@@ -143,8 +151,8 @@ impl std::ops::BitXorAssign for SomeEnum {
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub union SomeUnion {
-    pub bytes1: [u8; 8 as usize],
-    pub bytes2: [u8; 16 as usize],
+    pub bytes1: [u8; 8],
+    pub bytes2: [u8; 16],
 }
 
 // unions can't autoderive debug, but it's useful for their parent types to
@@ -153,4 +161,3 @@ impl std::fmt::Debug for SomeUnion {
         write!(f, "<SomeUnion>")
     }
 }
-
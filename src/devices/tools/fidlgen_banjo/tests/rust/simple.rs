@@ -33,6 +33,16 @@ impl Direction {
     pub const DOWN: Self = Self(1);
     pub const LEFT: Self = Self(2);
     pub const RIGHT: Self = Self(3);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::UP => true,
+            Self::DOWN => true,
+            Self::LEFT => true,
+            Self::RIGHT => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for Direction {
@@ -73,6 +83,3 @@ impl std::ops::BitXorAssign for Direction {
         *self = Self(self.0 ^ rhs.0)
     }
 }
-
-
-
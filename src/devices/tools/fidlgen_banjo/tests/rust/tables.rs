@@ -45,7 +45,3 @@ pub struct A {
 pub struct E {
     pub quux: u8,
 }
-
-
-
-
@@ -299,10 +299,10 @@ pub struct structs {
 pub struct strings {
     pub s: *const std::os::raw::c_char,
     pub nullable_s: *const std::os::raw::c_char,
-    pub size_0_s: [u8; 4 as usize],
-    pub size_1_s: [u8; 32 as usize],
-    pub nullable_size_0_s: [u8; 4 as usize],
-    pub nullable_size_1_s: [u8; 32 as usize],
+    pub size_0_s: [u8; 4],
+    pub size_1_s: [u8; 32],
+    pub nullable_size_0_s: [u8; 4],
+    pub nullable_size_1_s: [u8; 32],
 }
 
 #[repr(C)]
@@ -332,48 +332,90 @@ pub struct interfaces {
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct handles {
     pub handle_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: process.
     pub process_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: thread.
     pub thread_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: vmo.
     pub vmo_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: channel.
     pub channel_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: event.
     pub event_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: port.
     pub port_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: interrupt.
     pub interrupt_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: socket.
     pub socket_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: resource.
     pub resource_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: eventpair.
     pub eventpair_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: job.
     pub job_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: vmar.
     pub vmar_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: fifo.
     pub fifo_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: guest.
     pub guest_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: timer.
     pub timer_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: profile.
     pub profile_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: vcpu.
     pub vcpu_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: iommu.
     pub iommu_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: pager.
     pub pager_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: pmt.
     pub pmt_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: clock.
     pub clock_handle: zircon_types::zx_handle_t,
     pub nullable_handle_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: process.
     pub nullable_process_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: thread.
     pub nullable_thread_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: vmo.
     pub nullable_vmo_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: channel.
     pub nullable_channel_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: event.
     pub nullable_event_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: port.
     pub nullable_port_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: interrupt.
     pub nullable_interrupt_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: socket.
     pub nullable_socket_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: resource.
     pub nullable_resource_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: eventpair.
     pub nullable_eventpair_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: job.
     pub nullable_job_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: vmar.
     pub nullable_vmar_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: fifo.
     pub nullable_fifo_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: guest.
     pub nullable_guest_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: timer.
     pub nullable_timer_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: profile.
     pub nullable_profile_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: vcpu.
     pub nullable_vcpu_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: iommu.
     pub nullable_iommu_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: pager.
     pub nullable_pager_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: pmt.
     pub nullable_pmt_handle: zircon_types::zx_handle_t,
+    /// Handle subtype: clock.
     pub nullable_clock_handle: zircon_types::zx_handle_t,
 }
 
@@ -396,42 +438,42 @@ pub struct default_values {
 #[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct arrays {
-    pub b_0: [bool; 1 as usize],
-    pub i8_0: [i8; 1 as usize],
-    pub i16_0: [i16; 1 as usize],
-    pub i32_0: [i32; 1 as usize],
-    pub i64_0: [i64; 1 as usize],
-    pub u8_0: [u8; 1 as usize],
-    pub u16_0: [u16; 1 as usize],
-    pub u32_0: [u32; 1 as usize],
-    pub u64_0: [u64; 1 as usize],
-    pub f32_0: [f32; 1 as usize],
-    pub f64_0: [f64; 1 as usize],
-    pub handle_0: [zircon_types::zx_handle_t; 1 as usize],
-    pub b_1: [bool; 32 as usize],
-    pub i8_1: [i8; 32 as usize],
-    pub i16_1: [i16; 32 as usize],
-    pub i32_1: [i32; 32 as usize],
-    pub i64_1: [i64; 32 as usize],
-    pub u8_1: [u8; 32 as usize],
-    pub u16_1: [u16; 32 as usize],
-    pub u32_1: [u32; 32 as usize],
-    pub u64_1: [u64; 32 as usize],
-    pub f32_1: [f32; 32 as usize],
-    pub f64_1: [f64; 32 as usize],
-    pub handle_1: [zircon_types::zx_handle_t; 32 as usize],
-    pub b_2: [[bool; 4 as usize]; 32 as usize],
-    pub i8_2: [[i8; 4 as usize]; 32 as usize],
-    pub i16_2: [[i16; 4 as usize]; 32 as usize],
-    pub i32_2: [[i32; 4 as usize]; 32 as usize],
-    pub i64_2: [[i64; 4 as usize]; 32 as usize],
-    pub u8_2: [[u8; 4 as usize]; 32 as usize],
-    pub u16_2: [[u16; 4 as usize]; 32 as usize],
-    pub u32_2: [[u32; 4 as usize]; 32 as usize],
-    pub u64_2: [[u64; 4 as usize]; 32 as usize],
-    pub f32_2: [[f32; 4 as usize]; 32 as usize],
-    pub f64_2: [[f64; 4 as usize]; 32 as usize],
-    pub handle_2: [[zircon_types::zx_handle_t; 4 as usize]; 32 as usize],
+    pub b_0: [bool; 1],
+    pub i8_0: [i8; 1],
+    pub i16_0: [i16; 1],
+    pub i32_0: [i32; 1],
+    pub i64_0: [i64; 1],
+    pub u8_0: [u8; 1],
+    pub u16_0: [u16; 1],
+    pub u32_0: [u32; 1],
+    pub u64_0: [u64; 1],
+    pub f32_0: [f32; 1],
+    pub f64_0: [f64; 1],
+    pub handle_0: [zircon_types::zx_handle_t; 1],
+    pub b_1: [bool; 32],
+    pub i8_1: [i8; 32],
+    pub i16_1: [i16; 32],
+    pub i32_1: [i32; 32],
+    pub i64_1: [i64; 32],
+    pub u8_1: [u8; 32],
+    pub u16_1: [u16; 32],
+    pub u32_1: [u32; 32],
+    pub u64_1: [u64; 32],
+    pub f32_1: [f32; 32],
+    pub f64_1: [f64; 32],
+    pub handle_1: [zircon_types::zx_handle_t; 32],
+    pub b_2: [[bool; 4]; 32],
+    pub i8_2: [[i8; 4]; 32],
+    pub i16_2: [[i16; 4]; 32],
+    pub i32_2: [[i32; 4]; 32],
+    pub i64_2: [[i64; 4]; 32],
+    pub u8_2: [[u8; 4]; 32],
+    pub u16_2: [[u16; 4]; 32],
+    pub u32_2: [[u32; 4]; 32],
+    pub u64_2: [[u64; 4]; 32],
+    pub f32_2: [[f32; 4]; 32],
+    pub f64_2: [[f64; 4]; 32],
+    pub handle_2: [[zircon_types::zx_handle_t; 4]; 32],
 }
 
 
@@ -441,6 +483,13 @@ pub struct u8_enum(pub u8);
 
 impl u8_enum {
     pub const X: Self = Self(23);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::X => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for u8_enum {
@@ -487,6 +536,13 @@ pub struct u64_enum(pub u64);
 
 impl u64_enum {
     pub const X: Self = Self(23);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::X => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for u64_enum {
@@ -533,6 +589,13 @@ pub struct u32_enum(pub u32);
 
 impl u32_enum {
     pub const X: Self = Self(23);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::X => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for u32_enum {
@@ -579,6 +642,13 @@ pub struct u16_enum(pub u16);
 
 impl u16_enum {
     pub const X: Self = Self(23);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::X => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for u16_enum {
@@ -625,6 +695,13 @@ pub struct i8_enum(pub i8);
 
 impl i8_enum {
     pub const X: Self = Self(23);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::X => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for i8_enum {
@@ -671,6 +748,13 @@ pub struct i64_enum(pub i64);
 
 impl i64_enum {
     pub const X: Self = Self(23);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::X => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for i64_enum {
@@ -717,6 +801,13 @@ pub struct i32_enum(pub i32);
 
 impl i32_enum {
     pub const X: Self = Self(23);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::X => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for i32_enum {
@@ -763,6 +854,13 @@ pub struct i16_enum(pub i16);
 
 impl i16_enum {
     pub const X: Self = Self(23);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::X => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for i16_enum {
@@ -809,6 +907,13 @@ pub struct default_enum(pub u32);
 
 impl default_enum {
     pub const X: Self = Self(23);
+
+    pub fn is_known(&self) -> bool {
+        match *self {
+            Self::X => true,
+            _ => false,
+        }
+    }
 }
 
 impl std::ops::BitAnd for default_enum {
@@ -878,18 +983,18 @@ pub union union_types {
     pub u64: u64,
     pub f32: f32,
     pub f64: f64,
-    pub b_0: [bool; 1 as usize],
-    pub i8_0: [i8; 1 as usize],
-    pub i16_0: [i16; 1 as usize],
-    pub i32_0: [i32; 1 as usize],
-    pub i64_0: [i64; 1 as usize],
-    pub u8_0: [u8; 1 as usize],
-    pub u16_0: [u16; 1 as usize],
-    pub u32_0: [u32; 1 as usize],
-    pub u64_0: [u64; 1 as usize],
-    pub f32_0: [f32; 1 as usize],
-    pub f64_0: [f64; 1 as usize],
-    pub handle_0: [zircon_types::zx_handle_t; 1 as usize],
+    pub b_0: [bool; 1],
+    pub i8_0: [i8; 1],
+    pub i16_0: [i16; 1],
+    pub i32_0: [i32; 1],
+    pub i64_0: [i64; 1],
+    pub u8_0: [u8; 1],
+    pub u16_0: [u16; 1],
+    pub u32_0: [u32; 1],
+    pub u64_0: [u64; 1],
+    pub f32_0: [f32; 1],
+    pub f64_0: [f64; 1],
+    pub handle_0: [zircon_types::zx_handle_t; 1],
     pub str: *const std::os::raw::c_char,
     pub s: this_is_a_struct,
     pub u: this_is_a_union,
@@ -901,4 +1006,3 @@ impl std::fmt::Debug for union_types {
         write!(f, "<union_types>")
     }
 }
-
@@ -10,10 +10,3 @@
 
 use banjo_banjo_examples_point as banjo_examples_point;
 use banjo_examples_point::*;
-
-
-
-
-
-
-
@@ -36,7 +36,102 @@ macro_rules! codegen_test {
     };
 }
 
+/// Parses the enums IR and sets the value of uint8_enum's first member to
+/// 300, which doesn't fit in the enum's type.
+fn out_of_range_enum_ir() -> Result<fidl_ir_lib::fidl::FidlIr, anyhow::Error> {
+    use fidl_ir_lib::fidl::{Constant, FidlIr};
+
+    let mut ir: FidlIr = serde_json::from_str(test_irs::enums::IR)?;
+    let decl = ir
+        .enum_declarations
+        .iter_mut()
+        .find(|decl| decl.name.get_name() == "uint8_enum")
+        .expect("uint8_enum is declared");
+    decl.members[0].value = Constant::Literal {
+        literal: fidl_ir_lib::fidl::Literal::Numeric {
+            value: "300".to_string(),
+            expression: "300".to_string(),
+        },
+        value: "300".to_string(),
+        expression: "300".to_string(),
+    };
+    ir.build()?;
+    Ok(ir)
+}
+
+/// Parses the constants IR, optionally adding a `@version` attribute to the
+/// library.
+fn versioned_constants_ir(
+    version: Option<&str>,
+) -> Result<fidl_ir_lib::fidl::FidlIr, anyhow::Error> {
+    use fidl_ir_lib::fidl::{Attribute, AttributeArg, Constant, FidlIr, Literal};
+
+    let mut ir: FidlIr = serde_json::from_str(test_irs::constants::IR)?;
+    ir.maybe_attributes = version.map(|version| {
+        vec![Attribute {
+            name: "version".to_string(),
+            arguments: vec![AttributeArg {
+                name: "value".to_string(),
+                value: Constant::Literal {
+                    literal: Literal::Str {
+                        value: version.to_string(),
+                        expression: format!("\"{}\"", version),
+                    },
+                    value: version.to_string(),
+                    expression: format!("\"{}\"", version),
+                },
+            }],
+        }]
+    });
+    ir.build()?;
+    Ok(ir)
+}
+
+/// Returns a doc attribute holding `value`.
+fn doc_attribute(value: &str) -> fidl_ir_lib::fidl::Attribute {
+    use fidl_ir_lib::fidl::{Attribute, AttributeArg, Constant, Literal};
+
+    Attribute {
+        name: "doc".to_string(),
+        arguments: vec![AttributeArg {
+            name: "value".to_string(),
+            value: Constant::Literal {
+                literal: Literal::Str {
+                    value: value.to_string(),
+                    expression: format!("{:?}", value),
+                },
+                value: value.to_string(),
+                expression: format!("{:?}", value),
+            },
+        }],
+    }
+}
+
+/// Parses the enums IR, adding a doc comment to `uint8_enum.kTwo`.
+fn documented_enums_ir() -> Result<fidl_ir_lib::fidl::FidlIr, anyhow::Error> {
+    let mut ir: fidl_ir_lib::fidl::FidlIr = serde_json::from_str(test_irs::enums::IR)?;
+    let member = ir
+        .enum_declarations
+        .iter_mut()
+        .find(|decl| decl.name.get_name() == "uint8_enum")
+        .and_then(|decl| decl.members.iter_mut().find(|member| member.name.0 == "kTwo"))
+        .expect("uint8_enum.kTwo is declared");
+    member.maybe_attributes = Some(vec![doc_attribute(" The second value.\n")]);
+    ir.build()?;
+    Ok(ir)
+}
+
+// The generated enums are self-contained, so the golden is also compiled to exercise them.
+#[allow(dead_code)]
+#[path = "rust/enums.rs"]
+mod enums_golden;
+
 mod c {
+    use {
+        fidl_ir_lib::fidl::FidlIr,
+        fidlgen_banjo_lib::backends::{Backend, CBackend, CodegenOptions, NewlineStyle},
+    };
+
     macro_rules! c_test {
         ( $id:ident ) => {
             codegen_test!($id, CBackend, concat!("c/", stringify!($id), ".h"));
@@ -88,9 +183,433 @@ mod c {
     c_test!(types);
     c_test!(union);
     c_test!(view);
+
+    /// Parses `ir` and generates its C bindings with `options`.
+    fn codegen(ir: &str, options: CodegenOptions) -> Result<String, anyhow::Error> {
+        codegen_ir(serde_json::from_str(ir)?, options)
+    }
+
+    /// Generates the C bindings of an IR that a test has already parsed and adjusted.
+    fn codegen_ir(mut ir: FidlIr, options: CodegenOptions) -> Result<String, anyhow::Error> {
+        ir.build()?;
+        let mut output = vec![];
+        CBackend::with_options(&mut output, options).codegen(ir)?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    #[test]
+    fn symbol_prefix() -> Result<(), anyhow::Error> {
+        let options =
+            || CodegenOptions { symbol_prefix: "test_".to_string(), ..Default::default() };
+
+        let output = codegen(test_irs::enums::IR, options())?;
+        assert!(output.contains("// Declarations are prefixed with \"test_\"\n"));
+        assert!(output.contains("typedef uint8_t test_uint8_enum_t;\n"));
+        assert!(output.contains("#define TEST_UINT8_ENUM_KONE UINT8_C(1)\n"));
+
+        let output = codegen(test_irs::constants::IR, options())?;
+        assert!(output.contains("#define TEST_SOME_VALUE UINT32_C(12345)\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn enum_to_string() -> Result<(), anyhow::Error> {
+        let options = CodegenOptions { enum_to_string: true, ..Default::default() };
+        let output = codegen(test_irs::enums::IR, options)?;
+
+        assert!(output.contains(
+            "static inline const char* uint8_enum_to_string(uint8_enum_t v) {\n  switch (v) {\n"
+        ));
+        for member in &["kOne", "kTwo", "kThree", "kFour", "kFive"] {
+            assert!(output.contains(&format!(
+                "    case UINT8_ENUM_{}:\n      return \"{}\";\n",
+                member.to_uppercase(),
+                member
+            )));
+        }
+        assert!(output.contains("  return \"(unknown)\";\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn enum_values() -> Result<(), anyhow::Error> {
+        let options = CodegenOptions { enum_values: true, ..Default::default() };
+        let output = codegen(test_irs::enums::IR, options)?;
+
+        assert!(output.contains(
+            "#define UINT8_ENUM_COUNT 5\n\
+             static const uint8_enum_t uint8_enum_values[] = { UINT8_ENUM_KONE, UINT8_ENUM_KTWO, \
+             UINT8_ENUM_KTHREE, UINT8_ENUM_KFOUR, UINT8_ENUM_KFIVE };\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn binary_operator_constants() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{
+            Bits, BitsMember, CompoundIdentifier, Const, Constant, Declaration, Identifier,
+            Literal, PrimitiveSubtype, Type,
+        };
+
+        let numeric = |value: &str| Constant::Literal {
+            literal: Literal::Numeric { value: value.to_string(), expression: value.to_string() },
+            value: value.to_string(),
+            expression: value.to_string(),
+        };
+        let flags = CompoundIdentifier("banjo.examples.enums/flags".to_string());
+
+        let mut ir: FidlIr = serde_json::from_str(test_irs::enums::IR)?;
+        ir.bits_declarations.push(Bits {
+            maybe_attributes: None,
+            name: flags.clone(),
+            naming_context: vec!["flags".to_string()],
+            location: None,
+            _type: Type::Primitive { subtype: PrimitiveSubtype::Uint32 },
+            mask: "3".to_string(),
+            members: vec![
+                BitsMember {
+                    name: Identifier("A".to_string()),
+                    location: None,
+                    maybe_attributes: None,
+                    value: numeric("1"),
+                },
+                BitsMember {
+                    name: Identifier("B".to_string()),
+                    location: None,
+                    maybe_attributes: None,
+                    value: numeric("2"),
+                },
+            ],
+            strict: true,
+        });
+        ir.declarations.0.insert(flags.clone(), Declaration::Bits);
+        ir.declaration_order.push(flags.clone());
+
+        let mut add_const = |name: &str, ty: &str, value: Constant| {
+            let name = CompoundIdentifier(format!("banjo.examples.enums/{}", name));
+            ir.const_declarations.push(Const {
+                maybe_attributes: None,
+                name: name.clone(),
+                location: None,
+                _type: Type::Identifier {
+                    identifier: CompoundIdentifier(format!("banjo.examples.enums/{}", ty)),
+                    nullable: false,
+                },
+                value,
+            });
+            ir.declarations.0.insert(name.clone(), Declaration::Const);
+            ir.declaration_order.push(name);
+        };
+        add_const(
+            "BOTH_FLAGS",
+            "flags",
+            Constant::BinaryOperator {
+                value: "3".to_string(),
+                expression: "flags.A | flags.B".to_string(),
+            },
+        );
+        add_const(
+            "DEFAULT_KIND",
+            "uint32_enum",
+            Constant::Identifier {
+                identifier: CompoundIdentifier(
+                    "banjo.examples.enums/uint32_enum.kThree".to_string(),
+                ),
+                value: "3".to_string(),
+                expression: "uint32_enum.kThree".to_string(),
+            },
+        );
+        let output = codegen_ir(ir, CodegenOptions::default())?;
+
+        assert!(output.contains("#define BOTH_FLAGS UINT32_C(3)\n"));
+        assert!(output.contains("#define DEFAULT_KIND UINT32_C(3)\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn enum_member_doc_comments() -> Result<(), anyhow::Error> {
+        let output = codegen_ir(super::documented_enums_ir()?, CodegenOptions::default())?;
+
+        assert!(output.contains(
+            "#define UINT8_ENUM_KONE UINT8_C(1)\n\
+             // The second value.\n\
+             #define UINT8_ENUM_KTWO UINT8_C(2)\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn doxygen_doc_comments() -> Result<(), anyhow::Error> {
+        let options = CodegenOptions { doxygen_doc_comments: true, ..Default::default() };
+        let output = codegen_ir(super::documented_enums_ir()?, options)?;
+
+        assert!(output.contains(
+            "#define UINT8_ENUM_KONE UINT8_C(1)\n\
+             /**\n \
+             * @brief The second value.\n \
+             */\n\
+             #define UINT8_ENUM_KTWO UINT8_C(2)\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn no_doc_comments() -> Result<(), anyhow::Error> {
+        let options = CodegenOptions { doc_comments: false, ..Default::default() };
+        let output = codegen_ir(super::documented_enums_ir()?, options)?;
+
+        assert!(output.contains(
+            "#define UINT8_ENUM_KONE UINT8_C(1)\n\
+             #define UINT8_ENUM_KTWO UINT8_C(2)\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn single_trailing_newline() -> Result<(), anyhow::Error> {
+        for ir in &[test_irs::enums::IR, test_irs::point::IR, test_irs::simple::IR] {
+            for suppressed_warnings in [vec![], vec!["-Wshadow".to_string()]] {
+                let options = CodegenOptions { suppressed_warnings, ..Default::default() };
+                let output = codegen(ir, options)?;
+                assert!(output.ends_with('\n') && !output.ends_with("\n\n"));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn newline_style() -> Result<(), anyhow::Error> {
+        let lf = codegen(test_irs::point::IR, CodegenOptions::default())?;
+        assert!(!lf.contains('\r'));
+
+        let options = CodegenOptions { newline_style: NewlineStyle::CrLf, ..Default::default() };
+        let crlf = codegen(test_irs::point::IR, options)?;
+        assert!(crlf.contains("struct point {\r\n    float x;\r\n"));
+        assert_eq!(crlf.replace("\r\n", "\n"), lf);
+        Ok(())
+    }
+
+    #[test]
+    fn indent_width() -> Result<(), anyhow::Error> {
+        let mut ir: FidlIr = serde_json::from_str(test_irs::point::IR)?;
+        ir.struct_declarations[0].members[0].maybe_attributes =
+            Some(vec![super::doc_attribute(" The horizontal position.\n")]);
+        let options = CodegenOptions { indent_width: 2, ..Default::default() };
+        let output = codegen_ir(ir, options)?;
+
+        assert!(output.contains(
+            "struct point {\n  // The horizontal position.\n  float x;\n  float y;\n};\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn sorted_includes() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{ExternalDeclarationsMap, Library, LibraryIdentifier};
+
+        let mut ir: FidlIr = serde_json::from_str(test_irs::point::IR)?;
+        ir.library_dependencies = ["zx", "banjo.examples.gamma", "banjo.examples.beta", "alpha"]
+            .iter()
+            .map(|name| Library {
+                name: LibraryIdentifier(name.to_string()),
+                declarations: ExternalDeclarationsMap(Default::default()),
+            })
+            .collect();
+        let output = codegen_ir(ir, CodegenOptions::default())?;
+
+        assert!(output.contains(
+            "#include <alpha/c/banjo.h>\n\
+             #include <banjo/examples/beta/c/banjo.h>\n\
+             #include <banjo/examples/gamma/c/banjo.h>\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn colliding_enum_members() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::Identifier;
+
+        let mut ir: FidlIr = serde_json::from_str(test_irs::enums::IR)?;
+        let member = ir
+            .enum_declarations
+            .iter_mut()
+            .find(|decl| decl.name.get_name() == "uint8_enum")
+            .and_then(|decl| decl.members.iter_mut().find(|member| member.name.0 == "kTwo"))
+            .expect("uint8_enum.kTwo is declared");
+        member.name = Identifier("KONE".to_string());
+        let error = codegen_ir(ir, CodegenOptions::default()).unwrap_err().to_string();
+
+        assert!(error.contains("kOne"), "{}", error);
+        assert!(error.contains("KONE"), "{}", error);
+        Ok(())
+    }
+
+    #[test]
+    fn cplusplus_compatible() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::Identifier;
+
+        let codegen = |field: &str| -> Result<String, anyhow::Error> {
+            let mut ir: FidlIr = serde_json::from_str(test_irs::point::IR)?;
+            ir.struct_declarations[0].members[0].name = Identifier(field.to_string());
+            let options = CodegenOptions { cplusplus_compatible: true, ..Default::default() };
+            codegen_ir(ir, options)
+        };
+
+        let output = codegen("x")?;
+        assert!(output.contains(
+            "#include <zircon/types.h>\n\n\
+             #ifdef __cplusplus\nextern \"C\" {\n#endif\n"
+        ));
+        assert!(output.ends_with("#ifdef __cplusplus\n}  // extern \"C\"\n#endif\n"));
+        assert!(!output.contains("__BEGIN_CDECLS"));
+
+        let error = codegen("class").unwrap_err();
+        assert_eq!(error.to_string(), "Field name class is a C++ keyword");
+        Ok(())
+    }
+
+    #[test]
+    fn enum_names() -> Result<(), anyhow::Error> {
+        let options = CodegenOptions { enum_values: true, enum_names: true, ..Default::default() };
+        let output = codegen(test_irs::enums::IR, options)?;
+
+        // The names line up with the values even though the values aren't contiguous.
+        assert!(output.contains(
+            "static const int8_enum_t int8_enum_values[] = \
+             { INT8_ENUM_KNEGATIVEONE, INT8_ENUM_KONE };\n\
+             static const char* const int8_enum_names[] = { \"kNegativeOne\", \"kOne\" };\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn hex_constant() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{CompoundIdentifier, Constant};
+
+        // Make SOME_VALUE refer to FANCY_PANTS, which is written in hex.
+        let mut ir: FidlIr = serde_json::from_str(test_irs::constants::IR)?;
+        let decl = ir
+            .const_declarations
+            .iter_mut()
+            .find(|decl| decl.name.get_name() == "SOME_VALUE")
+            .expect("SOME_VALUE is declared");
+        decl.value = Constant::Identifier {
+            identifier: CompoundIdentifier("banjo.examples.constants/FANCY_PANTS".to_string()),
+            value: "255".to_string(),
+            expression: "FANCY_PANTS".to_string(),
+        };
+        let output = codegen_ir(ir, CodegenOptions::default())?;
+
+        assert!(output.contains("#define FANCY_PANTS INT16_C(0xFF)\n"));
+        assert!(output.contains("#define SOME_VALUE UINT32_C(0xFF)\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn bindgen_layout() -> Result<(), anyhow::Error> {
+        let options = CodegenOptions { bindgen_layout: true, ..Default::default() };
+        let output = codegen(test_irs::point::IR, options)?;
+
+        assert!(output.contains(
+            "struct point {\n    float x;\n    float y;\n} __attribute__ ((aligned(4)));\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn field_width_comments() -> Result<(), anyhow::Error> {
+        let options = CodegenOptions { field_width_comments: true, ..Default::default() };
+        let output = codegen(test_irs::alias::IR, options)?;
+
+        assert!(output.contains("    uint16_t one; // uint16: 0..65535\n"));
+        assert!(output.contains("    uint32_t two; // uint32: 0..4294967295\n"));
+        assert!(output.contains("    const uint8_t* vector_alias_list;\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn struct_eq() -> Result<(), anyhow::Error> {
+        let options = CodegenOptions { struct_eq: true, ..Default::default() };
+        let output = codegen(test_irs::point::IR, options)?;
+
+        assert!(output.contains("#include <string.h>\n"));
+        assert!(output.contains(
+            "static inline bool point_eq(const point_t* a, const point_t* b) {\n  \
+             return a->x == b->x &&\n         a->y == b->y;\n}\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn declaration_ordinals() -> Result<(), anyhow::Error> {
+        let options = || CodegenOptions { declaration_ordinals: true, ..Default::default() };
+
+        let output = codegen(test_irs::point::IR, options())?;
+        assert!(output.contains("// ordinal: 0x92c61db09d17e2a3\ntypedef struct point point_t;\n"));
+        assert_eq!(output, codegen(test_irs::point::IR, options())?);
+        Ok(())
+    }
+
+    #[test]
+    fn suppressed_warnings() -> Result<(), anyhow::Error> {
+        let options = CodegenOptions {
+            suppressed_warnings: vec!["-Wunused".to_string(), "-Wsign-conversion".to_string()],
+            ..Default::default()
+        };
+        let output = codegen(test_irs::point::IR, options)?;
+
+        let (_, wrapped) = output.split_once("#include <zircon/types.h>\n\n").unwrap();
+        assert!(wrapped.starts_with(concat!(
+            "#pragma GCC diagnostic push\n",
+            "#pragma GCC diagnostic ignored \"-Wunused\"\n",
+            "#pragma GCC diagnostic ignored \"-Wsign-conversion\"\n",
+            "\n__BEGIN_CDECLS\n",
+        )));
+        assert!(wrapped.ends_with("__END_CDECLS\n\n#pragma GCC diagnostic pop\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn library_version() -> Result<(), anyhow::Error> {
+        let output =
+            codegen_ir(super::versioned_constants_ir(Some("1.2.0"))?, CodegenOptions::default())?;
+        assert!(output.contains("#define LIBRARY_VERSION \"1.2.0\"\n"));
+
+        let output = codegen_ir(super::versioned_constants_ir(None)?, CodegenOptions::default())?;
+        assert!(!output.contains("LIBRARY_VERSION"));
+        Ok(())
+    }
+
+    #[test]
+    fn enum_value_out_of_range() -> Result<(), anyhow::Error> {
+        let err =
+            codegen_ir(super::out_of_range_enum_ir()?, CodegenOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Value 300 of member kOne of enum banjo.examples.enums/uint8_enum doesn't fit in uint8"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn symbol_index() -> Result<(), anyhow::Error> {
+        let mut ir: FidlIr = serde_json::from_str(test_irs::enums::IR)?;
+        ir.build()?;
+        let mut output = vec![];
+        let index = CBackend::new(&mut output).symbol_index(&ir)?;
+        let entry = index.iter().find(|entry| entry.symbol == "int8_enum_t").unwrap();
+        assert!(entry.location.filename.ends_with("enums.test.fidl"));
+        assert_eq!(entry.location.line, 6);
+        Ok(())
+    }
 }
 
 mod rust {
+    use {
+        fidl_ir_lib::fidl::FidlIr,
+        fidlgen_banjo_lib::backends::{Backend, CodegenOptions, NewlineStyle, RustBackend},
+    };
+
     macro_rules! rust_test {
         ( $id:ident ) => {
             codegen_test!($id, RustBackend, concat!("rust/", stringify!($id), ".rs"));
@@ -119,6 +638,547 @@ mod rust {
     rust_test!(tables);
     rust_test!(types);
     rust_test!(view);
+
+    /// Parses `ir` and generates its Rust bindings with `options`.
+    fn codegen(ir: &str, options: CodegenOptions) -> Result<String, anyhow::Error> {
+        codegen_ir(serde_json::from_str(ir)?, options)
+    }
+
+    /// Generates the Rust bindings of an IR that a test has already parsed and adjusted.
+    fn codegen_ir(mut ir: FidlIr, options: CodegenOptions) -> Result<String, anyhow::Error> {
+        ir.build()?;
+        let mut output = vec![];
+        RustBackend::with_options(&mut output, options).codegen(ir)?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    #[test]
+    fn enums_is_known() -> Result<(), anyhow::Error> {
+        use super::enums_golden::{int8_enum, uint8_enum};
+        use fidl_ir_lib::fidl::{Constant, Literal};
+
+        assert!(uint8_enum::KTHREE.is_known());
+        assert!(!uint8_enum(42).is_known());
+        assert!(int8_enum(-1).is_known());
+        assert!(!int8_enum(0).is_known());
+
+        // Make kTwo an alias of kOne, which is covered by kOne's arm.
+        let mut ir: FidlIr = serde_json::from_str(test_irs::enums::IR)?;
+        let decl = ir
+            .enum_declarations
+            .iter_mut()
+            .find(|decl| decl.name.get_name() == "uint8_enum")
+            .expect("uint8_enum is declared");
+        decl.members[1].value = Constant::Literal {
+            literal: Literal::Numeric { value: "1".to_string(), expression: "1".to_string() },
+            value: "1".to_string(),
+            expression: "1".to_string(),
+        };
+        let output = codegen_ir(ir, CodegenOptions::default())?;
+        assert!(output.contains("    pub const KTWO: Self = Self(1);\n"));
+        assert!(output.contains(
+            "    pub const KFIVE: Self = Self(5);\n\n    \
+             pub fn is_known(&self) -> bool {\n        \
+             match *self {\n            \
+             Self::KONE => true,\n            \
+             Self::KTHREE => true,\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn zircon_path() -> Result<(), anyhow::Error> {
+        let options =
+            CodegenOptions { zircon_path: "crate::zx::sys".to_string(), ..Default::default() };
+        let output = codegen(test_irs::tables::IR, options)?;
+
+        assert!(output.contains("    pub baz: crate::zx::sys::zx_handle_t,\n"));
+        assert!(!output.contains("zircon_types"));
+        Ok(())
+    }
+
+    #[test]
+    fn zircon_wrapper_path() -> Result<(), anyhow::Error> {
+        let options = CodegenOptions {
+            zircon_wrapper_path: "crate::zx".to_string(),
+            rust_protocols: true,
+            status_results: true,
+            typed_handles: true,
+            async_wrappers_feature: Some("async".to_string()),
+            ..Default::default()
+        };
+        let output = codegen(test_irs::simple::IR, options)?;
+
+        assert!(output.contains("commands: crate::zx::Vmo"));
+        assert!(output.contains(
+            "-> Result<(), crate::zx::Status> {\n        \
+             crate::zx::Status::ok(((*self.ops).draw_array)(self.ctx, points))\n"
+        ));
+        assert!(output.contains("-> Result<Point, crate::zx::Status> {\n"));
+        assert!(!output.contains("zircon::"));
+        Ok(())
+    }
+
+    #[test]
+    fn library_version() -> Result<(), anyhow::Error> {
+        let output =
+            codegen_ir(super::versioned_constants_ir(Some("1.2.0"))?, CodegenOptions::default())?;
+        assert!(output.contains("pub const LIBRARY_VERSION: &str = \"1.2.0\";\n"));
+
+        let output = codegen_ir(super::versioned_constants_ir(None)?, CodegenOptions::default())?;
+        assert!(!output.contains("LIBRARY_VERSION"));
+        Ok(())
+    }
+
+    #[test]
+    fn enum_value_out_of_range() -> Result<(), anyhow::Error> {
+        let err =
+            codegen_ir(super::out_of_range_enum_ir()?, CodegenOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Value 300 of member kOne of enum banjo.examples.enums/uint8_enum doesn't fit in uint8"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rust_enum_style() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{Constant, Literal};
+        use fidlgen_banjo_lib::backends::RustEnumStyle;
+
+        let options = |rust_enum_style| CodegenOptions { rust_enum_style, ..Default::default() };
+
+        let output = codegen(test_irs::enums::IR, options(RustEnumStyle::Newtype))?;
+        assert!(output.contains("pub struct int8_enum(pub i8);\n"));
+        assert!(output.contains("    pub const KNEGATIVEONE: Self = Self(-1);\n"));
+
+        let output = codegen(test_irs::enums::IR, options(RustEnumStyle::NativeEnum))?;
+        assert!(output.contains(
+            "#[repr(i8)]\n#[derive(Copy, Clone, Debug, PartialEq, Eq)]\npub enum int8_enum {\n    \
+             KNEGATIVEONE = -1,\n    KONE = 1,\n}\n"
+        ));
+        assert!(!output.contains("pub struct int8_enum"));
+
+        // Make kTwo an alias of kOne.
+        let mut ir: FidlIr = serde_json::from_str(test_irs::enums::IR)?;
+        let decl = ir
+            .enum_declarations
+            .iter_mut()
+            .find(|decl| decl.name.get_name() == "uint8_enum")
+            .expect("uint8_enum is declared");
+        decl.members[1].value = Constant::Literal {
+            literal: Literal::Numeric { value: "1".to_string(), expression: "1".to_string() },
+            value: "1".to_string(),
+            expression: "1".to_string(),
+        };
+        assert_eq!(
+            codegen_ir(ir.clone(), options(RustEnumStyle::NativeEnum)).unwrap_err().to_string(),
+            "Members kOne and kTwo of enum banjo.examples.enums/uint8_enum share the value 1, \
+             which a native Rust enum can't represent"
+        );
+        assert!(codegen_ir(ir, options(RustEnumStyle::Newtype)).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn one_method_protocol() -> Result<(), anyhow::Error> {
+        let mut ir: FidlIr = serde_json::from_str(test_irs::simple::IR)?;
+        ir.interface_declarations[0].methods.retain(|method| method.name.0 == "Draw");
+        let options = CodegenOptions { rust_protocols: true, ..Default::default() };
+        let output = codegen_ir(ir, options)?;
+
+        assert!(output.contains(
+            "#[repr(C)]\n\
+             pub struct Drawing_ops_t {\n    \
+             pub draw: unsafe extern \"C\" fn(ctx: *mut u8, p: *const Point, d: Direction),\n\
+             }\n"
+        ));
+        assert!(output.contains(
+            "#[repr(C)]\n\
+             pub struct DrawingProtocol {\n    \
+             ops: *mut Drawing_ops_t,\n    \
+             ctx: *mut u8,\n\
+             }\n"
+        ));
+        assert!(output.contains(
+            "    pub unsafe fn draw(&self, p: *const Point, d: Direction) {\n        \
+             ((*self.ops).draw)(self.ctx, p, d)\n    }"
+        ));
+        assert!(!output.contains("draw_lots"));
+        Ok(())
+    }
+
+    #[test]
+    fn status_results() -> Result<(), anyhow::Error> {
+        let options = |status_results| CodegenOptions {
+            rust_protocols: true,
+            status_results,
+            ..Default::default()
+        };
+
+        let output = codegen(test_irs::simple::IR, options(true))?;
+        assert!(output.contains(
+            "    pub draw_array: unsafe extern \"C\" fn(ctx: *mut u8, points: *const Point) \
+             -> zircon_types::zx_status_t,\n"
+        ));
+        assert!(output.contains(
+            "    pub unsafe fn draw_array(&self, points: *const Point) \
+             -> Result<(), zircon::Status> {\n        \
+             zircon::Status::ok(((*self.ops).draw_array)(self.ctx, points))\n"
+        ));
+
+        let output = codegen(test_irs::simple::IR, options(false))?;
+        assert!(output.contains(
+            "    pub unsafe fn draw_array(&self, points: *const Point) \
+             -> zircon_types::zx_status_t {\n"
+        ));
+        assert!(!output.contains("Result<(), zircon::Status> {\n        zircon::Status::ok"));
+        Ok(())
+    }
+
+    #[test]
+    fn declaration_ordinals() -> Result<(), anyhow::Error> {
+        let options = || CodegenOptions { declaration_ordinals: true, ..Default::default() };
+
+        let output = codegen(test_irs::point::IR, options())?;
+        assert!(output.contains("// ordinal: 0x92c61db09d17e2a3\n#[repr(C)]\n"));
+        assert_eq!(output, codegen(test_irs::point::IR, options())?);
+        Ok(())
+    }
+
+    #[test]
+    fn safe_wrappers() -> Result<(), anyhow::Error> {
+        let options = |safe_wrappers_feature| CodegenOptions {
+            rust_protocols: true,
+            safe_wrappers_feature,
+            ..Default::default()
+        };
+
+        let output = codegen(test_irs::simple::IR, options(Some("safe".to_string())))?;
+        assert!(output.contains(
+            "    #[cfg(feature = \"safe\")]\n    \
+             pub fn draw_array_safe(&self, points: &[Point; 4]) -> zircon_types::zx_status_t {\n        \
+             unsafe { self.draw_array(points.as_ptr()) }\n    }\n"
+        ));
+
+        let output = codegen(test_irs::simple::IR, options(None))?;
+        assert!(!output.contains("draw_array_safe"));
+        Ok(())
+    }
+
+    #[test]
+    fn serde_feature() -> Result<(), anyhow::Error> {
+        let options = |serde_feature| CodegenOptions { serde_feature, ..Default::default() };
+
+        let output = codegen(test_irs::point::IR, options(Some("serde".to_string())))?;
+        assert!(output.contains(
+            "#[derive(Copy, Clone, Debug, PartialEq)]\n\
+             #[cfg_attr(feature = \"serde\", derive(serde::Serialize, serde::Deserialize))]\n\
+             pub struct Point {\n"
+        ));
+
+        let output = codegen(test_irs::point::IR, options(None))?;
+        assert!(!output.contains("serde"));
+        Ok(())
+    }
+
+    #[test]
+    fn async_wrappers() -> Result<(), anyhow::Error> {
+        let options = |async_wrappers_feature| CodegenOptions {
+            rust_protocols: true,
+            async_wrappers_feature,
+            ..Default::default()
+        };
+
+        let output = codegen(test_irs::simple::IR, options(Some("async".to_string())))?;
+        assert!(output.contains(
+            "    #[cfg(feature = \"async\")]\n    \
+             pub async fn draw_async(&self, p: Point, d: Direction) {\n        \
+             let proto = Self { ops: self.ops, ctx: self.ctx };\n        \
+             fuchsia_async::unblock(move || unsafe { proto.draw(&p, d) }).await\n    }\n"
+        ));
+        assert!(output.contains(
+            "    pub async fn draw_lots_async(&self, commands: zircon_types::zx_handle_t) \
+             -> (zircon_types::zx_status_t, Point) {\n"
+        ));
+        // Strings can't be handed to another thread without copying them.
+        assert!(!output.contains("describe_async"));
+
+        let output = codegen(test_irs::simple::IR, options(None))?;
+        assert!(!output.contains("draw_async"));
+        Ok(())
+    }
+
+    #[test]
+    fn enum_field() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{CompoundIdentifier, Type};
+
+        let mut ir: FidlIr = serde_json::from_str(test_irs::simple::IR)?;
+        let point = ir
+            .struct_declarations
+            .iter_mut()
+            .find(|decl| decl.name.get_name() == "Point")
+            .expect("Point is declared");
+        point.members[0]._type = Type::Identifier {
+            identifier: CompoundIdentifier("banjo.examples.simple/Direction".to_string()),
+            nullable: false,
+        };
+        let output = codegen_ir(ir, CodegenOptions::default())?;
+
+        assert!(output.contains("pub struct Point {\n    pub x: Direction,\n"), "{}", output);
+        assert!(!output.contains("c_void"));
+        Ok(())
+    }
+
+    #[test]
+    fn evaluate_binary_operators() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{
+            CompoundIdentifier, Const, Constant, Declaration, PrimitiveSubtype, Type,
+        };
+
+        let codegen = |evaluate_binary_operators| -> Result<String, anyhow::Error> {
+            let mut ir: FidlIr = serde_json::from_str(test_irs::enums::IR)?;
+            let name = CompoundIdentifier("banjo.examples.enums/SHIFTED".to_string());
+            ir.const_declarations.push(Const {
+                maybe_attributes: None,
+                name: name.clone(),
+                location: None,
+                _type: Type::Primitive { subtype: PrimitiveSubtype::Uint32 },
+                value: Constant::BinaryOperator {
+                    value: "16".to_string(),
+                    expression: "1 << 4".to_string(),
+                },
+            });
+            ir.declarations.0.insert(name.clone(), Declaration::Const);
+            ir.declaration_order.push(name);
+            let options = CodegenOptions { evaluate_binary_operators, ..Default::default() };
+            codegen_ir(ir, options)
+        };
+
+        assert!(codegen(true)?.contains("pub const SHIFTED: u32 = 16;\n"));
+        assert!(codegen(false)?.contains("pub const SHIFTED: u32 = 1 << 4;\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn integer_constant_range() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{
+            CompoundIdentifier, Const, Constant, Declaration, Literal, PrimitiveSubtype, Type,
+        };
+
+        let codegen = |value: &str| -> Result<String, anyhow::Error> {
+            let mut ir: FidlIr = serde_json::from_str(test_irs::enums::IR)?;
+            let name = CompoundIdentifier("banjo.examples.enums/MAX_BYTE".to_string());
+            ir.const_declarations.push(Const {
+                maybe_attributes: None,
+                name: name.clone(),
+                location: None,
+                _type: Type::Primitive { subtype: PrimitiveSubtype::Uint8 },
+                value: Constant::Literal {
+                    literal: Literal::Numeric {
+                        value: value.to_string(),
+                        expression: value.to_string(),
+                    },
+                    value: value.to_string(),
+                    expression: value.to_string(),
+                },
+            });
+            ir.declarations.0.insert(name.clone(), Declaration::Const);
+            ir.declaration_order.push(name);
+            codegen_ir(ir, CodegenOptions::default())
+        };
+
+        assert!(codegen("255")?.contains("pub const MAX_BYTE: u8 = 255;\n"));
+        assert_eq!(
+            codegen("256").unwrap_err().to_string(),
+            "Constant MAX_BYTE has value 256, which doesn't fit in uint8"
+        );
+        assert!(codegen("-1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn sized_string_and_vector_fields() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{Count, PrimitiveSubtype, Type};
+
+        let mut ir: FidlIr = serde_json::from_str(test_irs::point::IR)?;
+        let point = &mut ir.struct_declarations[0];
+        point.members[0]._type = Type::Vector {
+            element_type: Box::new(Type::Primitive { subtype: PrimitiveSubtype::Uint8 }),
+            maybe_element_count: Some(Count(16)),
+            nullable: false,
+        };
+        point.members[1]._type =
+            Type::Str { maybe_element_count: Some(Count(32)), nullable: false };
+        let output = codegen_ir(ir, CodegenOptions::default())?;
+
+        // Vectors are passed by pointer whatever their bound, as in the C backend.
+        assert!(output.contains(
+            "    pub x_list: *const u8,\n    \
+             pub x_count: usize,\n    \
+             pub y: [u8; 32],\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn nested_array_fields() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{Count, PrimitiveSubtype, Type};
+
+        let mut ir: FidlIr = serde_json::from_str(test_irs::point::IR)?;
+        ir.struct_declarations[0].members[0]._type = Type::Array {
+            element_type: Box::new(Type::Array {
+                element_type: Box::new(Type::Primitive { subtype: PrimitiveSubtype::Uint8 }),
+                element_count: Count(4),
+            }),
+            element_count: Count(8),
+        };
+        let output = codegen_ir(ir, CodegenOptions::default())?;
+
+        assert!(output.contains("    pub x: [[u8; 4]; 8],\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn handle_subtypes() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{HandleSubtype, Type};
+
+        let codegen = |typed_handles| -> Result<String, anyhow::Error> {
+            let mut ir: FidlIr = serde_json::from_str(test_irs::point::IR)?;
+            let point = &mut ir.struct_declarations[0];
+            point.members[0]._type =
+                Type::Handle { subtype: HandleSubtype::Channel, rights: 0, nullable: false };
+            point.members[1]._type =
+                Type::Handle { subtype: HandleSubtype::Vmo, rights: 0, nullable: true };
+            let options = CodegenOptions { typed_handles, ..Default::default() };
+            codegen_ir(ir, options)
+        };
+
+        assert!(codegen(false)?.contains(
+            "#[derive(Copy, Clone, Debug, PartialEq)]\n\
+             pub struct Point {\n    \
+             /// Handle subtype: channel.\n    \
+             pub x: zircon_types::zx_handle_t,\n    \
+             /// Handle subtype: vmo.\n    \
+             pub y: zircon_types::zx_handle_t,\n"
+        ));
+        // Typed handles own what they refer to, so the struct can't be copied.
+        assert!(codegen(true)?.contains(
+            "#[derive(Debug, PartialEq)]\n\
+             pub struct Point {\n    \
+             pub x: zircon::Channel,\n    \
+             pub y: zircon::Vmo,\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_type() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{CompoundIdentifier, Type};
+
+        let mut ir: FidlIr = serde_json::from_str(test_irs::point::IR)?;
+        let request = Type::Request {
+            subtype: CompoundIdentifier("banjo.examples.point/Point".to_string()),
+            nullable: false,
+        };
+        ir.struct_declarations[0].members[0]._type = request.clone();
+        let error = codegen_ir(ir, CodegenOptions::default()).unwrap_err();
+        assert!(error.to_string().contains(&format!("{:?}", request)));
+        Ok(())
+    }
+
+    #[test]
+    fn packed_structs() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::{Attribute, Count, PrimitiveSubtype, Type};
+
+        let codegen = |y: Option<Type>| -> Result<String, anyhow::Error> {
+            let mut ir: FidlIr = serde_json::from_str(test_irs::point::IR)?;
+            let point = &mut ir.struct_declarations[0];
+            point.maybe_attributes =
+                Some(vec![Attribute { name: "packed".to_string(), arguments: vec![] }]);
+            if let Some(y) = y {
+                point.members[1]._type = y;
+            }
+            codegen_ir(ir, CodegenOptions::default())
+        };
+
+        assert!(codegen(None)?.contains(
+            "#[repr(C, packed)]\n#[derive(Copy, Clone, Debug, PartialEq)]\npub struct Point {\n"
+        ));
+        assert_eq!(
+            codegen(Some(Type::Vector {
+                element_type: Box::new(Type::Primitive { subtype: PrimitiveSubtype::Uint8 }),
+                maybe_element_count: None,
+                nullable: false,
+            }))
+            .unwrap_err()
+            .to_string(),
+            "Packed declaration Point can't hold pointer field y"
+        );
+        // Bounded strings are held inline, but unbounded ones are pointers.
+        assert!(codegen(Some(Type::Str { maybe_element_count: Some(Count(8)), nullable: false }))
+            .is_ok());
+        assert!(codegen(Some(Type::Str { maybe_element_count: None, nullable: false })).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn single_trailing_newline() -> Result<(), anyhow::Error> {
+        for ir in &[test_irs::enums::IR, test_irs::point::IR, test_irs::simple::IR] {
+            for rust_protocols in [false, true] {
+                let options = CodegenOptions { rust_protocols, ..Default::default() };
+                let output = codegen(ir, options)?;
+                assert!(output.ends_with('\n') && !output.ends_with("\n\n"));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn split_files() -> Result<(), anyhow::Error> {
+        use std::fs;
+
+        // The directory is removed when it goes out of scope, even if an assertion fails.
+        let temp_dir = tempfile::tempdir()?;
+        let dir = temp_dir.path();
+        let mut ir: FidlIr = serde_json::from_str(test_irs::simple::IR)?;
+        ir.build()?;
+        let options = CodegenOptions {
+            rust_protocols: true,
+            newline_style: NewlineStyle::CrLf,
+            ..Default::default()
+        };
+        RustBackend::with_options(&mut std::io::sink(), options).codegen_split(ir, dir)?;
+
+        let mut files = fs::read_dir(dir)?
+            .map(|entry| Ok(entry?.file_name().into_string().unwrap()))
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+        files.sort();
+        // The library declares no constants.
+        assert_eq!(files, ["enums.rs", "mod.rs", "protocols.rs", "structs.rs"]);
+
+        let read_to_string = |file| -> Result<String, std::io::Error> {
+            let contents = fs::read_to_string(dir.join(file))?;
+            // Every file uses the requested line endings.
+            assert_eq!(contents.matches('\n').count(), contents.matches("\r\n").count());
+            Ok(contents.replace("\r\n", "\n"))
+        };
+        let mod_rs = read_to_string("mod.rs")?;
+        assert!(mod_rs.ends_with(
+            "\nmod enums;\npub use enums::*;\n\
+             \nmod structs;\npub use structs::*;\n\
+             \nmod protocols;\npub use protocols::*;\n"
+        ));
+        let enums = read_to_string("enums.rs")?;
+        assert!(enums.contains("use super::*;"));
+        assert!(enums.contains("pub struct Direction("));
+        assert!(!enums.contains("pub struct Point"));
+        let structs = read_to_string("structs.rs")?;
+        assert!(structs.contains("pub struct Point {"));
+        let protocols = read_to_string("protocols.rs")?;
+        assert!(protocols.contains("pub struct DrawingProtocol"));
+        Ok(())
+    }
 }
 
 mod cpp {
@@ -189,3 +1249,42 @@ mod cpp_mock {
     cpp_mock_test!(protocolprimitive);
     cpp_mock_test!(protocolvector);
 }
+
+mod ir_dump {
+    #[test]
+    fn declaration_order() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::FidlIr;
+        use fidlgen_banjo_lib::backends::{Backend, IrDumpBackend};
+
+        let mut ir: FidlIr = serde_json::from_str(test_irs::enums::IR)?;
+        ir.build()?;
+        let names = ir.declaration_order.iter().map(|id| id.0.clone()).collect::<Vec<_>>();
+        let mut output = vec![];
+        IrDumpBackend::new(&mut output).codegen(ir)?;
+
+        let dump: serde_json::Value = serde_json::from_slice(&output)?;
+        assert_eq!(dump["name"], "banjo.examples.enums");
+        assert_eq!(dump["declaration_order"], serde_json::json!(names));
+        assert!(names.contains(&"banjo.examples.enums/uint8_enum".to_string()));
+        assert_eq!(dump["declarations"]["banjo.examples.enums/uint8_enum"], "enum");
+        assert_eq!(dump["enum_declarations"].as_array().map(Vec::len), Some(8));
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip() -> Result<(), anyhow::Error> {
+        use fidl_ir_lib::fidl::FidlIr;
+        use fidlgen_banjo_lib::backends::{Backend, IrDumpBackend};
+
+        let mut ir: FidlIr = serde_json::from_str(test_irs::enums::IR)?;
+        ir.build()?;
+        let expected = ir.clone();
+        let mut output = vec![];
+        IrDumpBackend::new(&mut output).codegen(ir)?;
+
+        let mut parsed: FidlIr = serde_json::from_slice(&output)?;
+        parsed.build()?;
+        assert_eq!(parsed, expected);
+        Ok(())
+    }
+}
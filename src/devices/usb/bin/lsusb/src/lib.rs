@@ -13,23 +13,31 @@ use {
     fuchsia_async::TimeoutExt,
     fuchsia_zircon_status as zx,
     futures::future::{BoxFuture, FutureExt},
-    std::sync::Mutex,
+    std::{io::Write, sync::Mutex},
 };
 
 // This isn't actually unused, but rustc can't seem to tell otherwise.
 #[allow(unused_imports)]
 use zerocopy::{AsBytes, LayoutVerified};
 
-pub async fn lsusb(device_watcher: DeviceWatcherProxy, args: Args) -> Result<()> {
+pub async fn lsusb(
+    device_watcher: DeviceWatcherProxy,
+    args: Args,
+    writer: &mut (impl Write + Send),
+) -> Result<()> {
     if args.tree {
-        list_tree(&device_watcher, &args).await
+        list_tree(&device_watcher, &args, writer).await
     } else {
-        list_devices(&device_watcher, &args).await
+        list_devices(&device_watcher, &args, writer).await
     }
 }
 
-async fn list_devices(device_watcher: &DeviceWatcherProxy, args: &Args) -> Result<()> {
-    println!("ID    VID:PID   SPEED  MANUFACTURER PRODUCT");
+async fn list_devices(
+    device_watcher: &DeviceWatcherProxy,
+    args: &Args,
+    writer: &mut impl Write,
+) -> Result<()> {
+    writeln!(writer, "ID    VID:PID   SPEED  MANUFACTURER PRODUCT")?;
 
     let mut idx = 0;
     while let Ok(device) = device_watcher
@@ -42,7 +50,7 @@ async fn list_devices(device_watcher: &DeviceWatcherProxy, args: &Args) -> Resul
         let channel = fidl::AsyncChannel::from_channel(device)?;
         let device = fidl_fuchsia_hardware_usb_device::DeviceProxy::new(channel);
 
-        match list_device(&device, idx, 0, 0, &args).await {
+        match list_device(&device, idx, 0, 0, &args, writer).await {
             Ok(()) => {}
             Err(e) => eprintln!("Error: {:?}", e),
         }
@@ -57,6 +65,7 @@ async fn list_device(
     depth: usize,
     max_depth: usize,
     args: &Args,
+    writer: &mut impl Write,
 ) -> Result<()> {
     let devname = &format!("/dev/class/usb-device/{:03}", devnum);
 
@@ -93,7 +102,8 @@ async fn list_device(
     let left_pad = depth * 4;
     let right_pad = (max_depth - depth) * 4;
 
-    println!(
+    writeln!(
+        writer,
         "{0:left_pad$}{1:03}  {0:right_pad$}{2:04X}:{3:04X}  {4:<5}  {5} {6}",
         "",
         devnum,
@@ -104,34 +114,49 @@ async fn list_device(
         string_prod_desc,
         left_pad = left_pad,
         right_pad = right_pad,
-    );
+    )?;
 
     if args.verbose {
-        println!("Device Descriptor:");
-        println!("  {:<33}{}", "bLength", device_desc.b_length);
-        println!("  {:<33}{}", "bDescriptorType", device_desc.b_descriptor_type);
-        println!("  {:<33}{}.{}", "bcdUSB", device_desc.bcd_usb >> 8, device_desc.bcd_usb & 0xFF);
-        println!("  {:<33}{}", "bDeviceClass", device_desc.b_device_class);
-        println!("  {:<33}{}", "bDeviceSubClass", device_desc.b_device_sub_class);
-        println!("  {:<33}{}", "bDeviceProtocol", device_desc.b_device_protocol);
-        println!("  {:<33}{}", "bMaxPacketSize0", device_desc.b_max_packet_size0);
-        println!("  {:<33}{:#06X}", "idVendor", { device_desc.id_vendor });
-        println!("  {:<33}{:#06X}", "idProduct", { device_desc.id_product });
-        println!(
+        writeln!(writer, "Device Descriptor:")?;
+        writeln!(writer, "  {:<33}{}", "bLength", device_desc.b_length)?;
+        writeln!(writer, "  {:<33}{}", "bDescriptorType", device_desc.b_descriptor_type)?;
+        writeln!(
+            writer,
+            "  {:<33}{}.{}",
+            "bcdUSB",
+            device_desc.bcd_usb >> 8,
+            device_desc.bcd_usb & 0xFF
+        )?;
+        writeln!(writer, "  {:<33}{}", "bDeviceClass", device_desc.b_device_class)?;
+        writeln!(writer, "  {:<33}{}", "bDeviceSubClass", device_desc.b_device_sub_class)?;
+        writeln!(writer, "  {:<33}{}", "bDeviceProtocol", device_desc.b_device_protocol)?;
+        writeln!(writer, "  {:<33}{}", "bMaxPacketSize0", device_desc.b_max_packet_size0)?;
+        writeln!(writer, "  {:<33}{:#06X}", "idVendor", { device_desc.id_vendor })?;
+        writeln!(writer, "  {:<33}{:#06X}", "idProduct", { device_desc.id_product })?;
+        writeln!(
+            writer,
             "  {:<33}{}.{}",
             "bcdDevice",
             device_desc.bcd_device >> 8,
             device_desc.bcd_device & 0xFF
-        );
-        println!("  {:<33}{} {}", "iManufacturer", device_desc.i_manufacturer, string_manu_desc);
-        println!("  {:<33}{} {}", "iProduct", device_desc.i_product, string_prod_desc);
+        )?;
+        writeln!(
+            writer,
+            "  {:<33}{} {}",
+            "iManufacturer", device_desc.i_manufacturer, string_manu_desc
+        )?;
+        writeln!(writer, "  {:<33}{} {}", "iProduct", device_desc.i_product, string_prod_desc)?;
 
         let serial_number = get_string_descriptor(device, device_desc.i_serial_number)
             .await
             .context(format!("DeviceGetStringDescriptor failed for {}", devname))?;
 
-        println!("  {:<33}{} {}", "iSerialNumber", device_desc.i_serial_number, serial_number);
-        println!("  {:<33}{}", "bNumConfigurations", device_desc.b_num_configurations);
+        writeln!(
+            writer,
+            "  {:<33}{} {}",
+            "iSerialNumber", device_desc.i_serial_number, serial_number
+        )?;
+        writeln!(writer, "  {:<33}{}", "bNumConfigurations", device_desc.b_num_configurations)?;
 
         let mut config = args.configuration;
         if config.is_none() {
@@ -154,104 +179,215 @@ async fn list_device(
         for descriptor in DescriptorIterator::new(&config_desc_data) {
             match descriptor {
                 Descriptor::Config(config_desc) => {
-                    println!("{:>2}Configuration Descriptor:", "");
-                    println!("{:>4}{:<31}{}", "", "bLength", config_desc.b_length);
-                    println!("{:>4}{:<31}{}", "", "bDescriptorType", config_desc.b_descriptor_type);
-                    println!("{:>4}{:<31}{}", "", "wTotalLength", { config_desc.w_total_length });
-                    println!("{:>4}{:<31}{}", "", "bNumInterfaces", config_desc.b_num_interfaces);
-                    println!(
+                    writeln!(writer, "{:>2}Configuration Descriptor:", "")?;
+                    writeln!(writer, "{:>4}{:<31}{}", "", "bLength", config_desc.b_length)?;
+                    writeln!(
+                        writer,
+                        "{:>4}{:<31}{}",
+                        "", "bDescriptorType", config_desc.b_descriptor_type
+                    )?;
+                    writeln!(writer, "{:>4}{:<31}{}", "", "wTotalLength", {
+                        config_desc.w_total_length
+                    })?;
+                    writeln!(
+                        writer,
+                        "{:>4}{:<31}{}",
+                        "", "bNumInterfaces", config_desc.b_num_interfaces
+                    )?;
+                    writeln!(
+                        writer,
                         "{:>4}{:<31}{}",
                         "", "bConfigurationValue", config_desc.b_configuration_value
-                    );
+                    )?;
                     let config_str = get_string_descriptor(device, config_desc.i_configuration)
                         .await
                         .context(format!("DeviceGetStringDescriptor failed for {}", devname))?;
-                    println!(
+                    writeln!(
+                        writer,
                         "{:>4}{:<31}{} {}",
                         "", "iConfiguration", config_desc.i_configuration, config_str
-                    );
-                    println!("{:>4}{:<31}{:#04X}", "", "bmAttributes", config_desc.bm_attributes);
-                    println!("{:>4}{:<31}{}", "", "bMaxPower", config_desc.b_max_power);
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>4}{:<31}{:#04X}",
+                        "", "bmAttributes", config_desc.bm_attributes
+                    )?;
+                    writeln!(writer, "{:>4}{:<31}{}", "", "bMaxPower", config_desc.b_max_power)?;
                 }
                 Descriptor::Interface(info) => {
-                    println!("{:>4}Interface Descriptor:", "");
-                    println!("{:>6}{:<29}{}", "", "bLength", info.b_length);
-                    println!("{:>6}{:<29}{}", "", "bDescriptorType", info.b_descriptor_type);
-                    println!("{:>6}{:<29}{}", "", "bInterfaceNumber", info.b_interface_number);
-                    println!("{:>6}{:<29}{}", "", "bAlternateSetting", info.b_alternate_setting);
-                    println!("{:>6}{:<29}{}", "", "bNumEndpoints", info.b_num_endpoints);
-                    println!("{:>6}{:<29}{}", "", "bInterfaceClass", info.b_interface_class);
-                    println!("{:>6}{:<29}{}", "", "bInterfaceSubClass", info.b_interface_sub_class);
-                    println!("{:>6}{:<29}{}", "", "bInterfaceProtocol", info.b_interface_protocol);
+                    writeln!(writer, "{:>4}Interface Descriptor:", "")?;
+                    writeln!(writer, "{:>6}{:<29}{}", "", "bLength", info.b_length)?;
+                    writeln!(
+                        writer,
+                        "{:>6}{:<29}{}",
+                        "", "bDescriptorType", info.b_descriptor_type
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>6}{:<29}{}",
+                        "", "bInterfaceNumber", info.b_interface_number
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>6}{:<29}{}",
+                        "", "bAlternateSetting", info.b_alternate_setting
+                    )?;
+                    writeln!(writer, "{:>6}{:<29}{}", "", "bNumEndpoints", info.b_num_endpoints)?;
+                    writeln!(
+                        writer,
+                        "{:>6}{:<29}{}",
+                        "", "bInterfaceClass", info.b_interface_class
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>6}{:<29}{}",
+                        "", "bInterfaceSubClass", info.b_interface_sub_class
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>6}{:<29}{}",
+                        "", "bInterfaceProtocol", info.b_interface_protocol
+                    )?;
 
                     let interface_str = get_string_descriptor(device, info.i_interface)
                         .await
                         .context(format!("DeviceGetStringDescriptor failed for {}", devname))?;
-                    println!("{:>6}{:<29}{}{}", "", "iInterface", info.i_interface, interface_str);
+                    writeln!(
+                        writer,
+                        "{:>6}{:<29}{}{}",
+                        "", "iInterface", info.i_interface, interface_str
+                    )?;
                 }
                 Descriptor::Endpoint(info) => {
-                    println!("{:>6}Endpoint Descriptor:", "");
-                    println!("{:>8}{:<27}{}", "", "bLength", info.b_length);
-                    println!("{:>8}{:<27}{}", "", "bDescriptorType", info.b_descriptor_type);
-                    println!("{:>8}{:<27}{:#04X}", "", "bEndpointAddress", info.b_endpoint_address);
-                    println!("{:>8}{:<27}{:#04X}", "", "bmAttributes", info.bm_attributes);
-                    println!("{:>8}{:<27}{}", "", "wMaxPacketSize", { info.w_max_packet_size });
-                    println!("{:>8}{:<27}{}", "", "bInterval", info.b_interval);
+                    writeln!(writer, "{:>6}Endpoint Descriptor:", "")?;
+                    writeln!(writer, "{:>8}{:<27}{}", "", "bLength", info.b_length)?;
+                    writeln!(
+                        writer,
+                        "{:>8}{:<27}{}",
+                        "", "bDescriptorType", info.b_descriptor_type
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>8}{:<27}{:#04X}",
+                        "", "bEndpointAddress", info.b_endpoint_address
+                    )?;
+                    writeln!(writer, "{:>8}{:<27}{:#04X}", "", "bmAttributes", info.bm_attributes)?;
+                    writeln!(writer, "{:>8}{:<27}{}", "", "wMaxPacketSize", {
+                        info.w_max_packet_size
+                    })?;
+                    writeln!(writer, "{:>8}{:<27}{}", "", "bInterval", info.b_interval)?;
                 }
                 Descriptor::Hid(descriptor) => {
                     let info = descriptor.get();
-                    println!("{:>6}HID Descriptor:", "");
-                    println!("{:>8}{:<27}{}", "", "bLength", info.b_length);
-                    println!("{:>8}{:<27}{}", "", "bDescriptorType", info.b_descriptor_type);
-                    println!(
+                    writeln!(writer, "{:>6}HID Descriptor:", "")?;
+                    writeln!(writer, "{:>8}{:<27}{}", "", "bLength", info.b_length)?;
+                    writeln!(
+                        writer,
+                        "{:>8}{:<27}{}",
+                        "", "bDescriptorType", info.b_descriptor_type
+                    )?;
+                    writeln!(
+                        writer,
                         "{:>8}{:<27}{}{}",
                         "",
                         "bcdHID",
                         info.bcd_hid >> 8,
                         info.bcd_hid & 0xFF
-                    );
-                    println!("{:>8}{:<27}{}", "", "bCountryCode", info.b_country_code);
-                    println!("{:>8}{:<27}{}", "", "bNumDescriptors", info.b_num_descriptors);
+                    )?;
+                    writeln!(writer, "{:>8}{:<27}{}", "", "bCountryCode", info.b_country_code)?;
+                    writeln!(
+                        writer,
+                        "{:>8}{:<27}{}",
+                        "", "bNumDescriptors", info.b_num_descriptors
+                    )?;
                     for entry in descriptor {
-                        println!("{:>10}{:<25}{}", "", "bDescriptorType", entry.b_descriptor_type);
-                        println!("{:>10}{:<25}{}", "", "wDescriptorLength", {
+                        writeln!(
+                            writer,
+                            "{:>10}{:<25}{}",
+                            "", "bDescriptorType", entry.b_descriptor_type
+                        )?;
+                        writeln!(writer, "{:>10}{:<25}{}", "", "wDescriptorLength", {
                             entry.w_descriptor_length
-                        });
+                        })?;
                     }
                 }
                 Descriptor::SsEpCompanion(info) => {
-                    println!("{:>8}SuperSpeed Endpoint Companion Descriptor:", "");
-                    println!("{:>10}{:<25}{}", "", "bLength", info.b_length);
-                    println!("{:>10}{:<25}{}", "", "bDescriptorType", info.b_descriptor_type);
-                    println!("{:>10}{:<25}{:#04X}", "", "bMaxBurst", info.b_max_burst);
-                    println!("{:>10}{:<25}{:#04X}", "", "bmAttributes", info.bm_attributes);
-                    println!("{:>10}{:<25}{}", "", "wBytesPerInterval", info.w_bytes_per_interval);
+                    writeln!(writer, "{:>8}SuperSpeed Endpoint Companion Descriptor:", "")?;
+                    writeln!(writer, "{:>10}{:<25}{}", "", "bLength", info.b_length)?;
+                    writeln!(
+                        writer,
+                        "{:>10}{:<25}{}",
+                        "", "bDescriptorType", info.b_descriptor_type
+                    )?;
+                    writeln!(writer, "{:>10}{:<25}{:#04X}", "", "bMaxBurst", info.b_max_burst)?;
+                    writeln!(
+                        writer,
+                        "{:>10}{:<25}{:#04X}",
+                        "", "bmAttributes", info.bm_attributes
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>10}{:<25}{}",
+                        "", "wBytesPerInterval", info.w_bytes_per_interval
+                    )?;
                 }
                 Descriptor::SsIsochEpCompanion(info) => {
-                    println!("{:>10}SuperSpeed Isochronous Endpoint Companion Descriptor:", "");
-                    println!("{:>12}{:<23}{}", "", "bLength", info.b_length);
-                    println!("{:>12}{:<23}{}", "", "bDescriptorType", info.b_descriptor_type);
-                    println!("{:>12}{:<23}{}", "", "wReserved", { info.w_reserved });
-                    println!("{:>12}{:<23}{}", "", "dwBytesPerInterval", {
+                    writeln!(
+                        writer,
+                        "{:>10}SuperSpeed Isochronous Endpoint Companion Descriptor:",
+                        ""
+                    )?;
+                    writeln!(writer, "{:>12}{:<23}{}", "", "bLength", info.b_length)?;
+                    writeln!(
+                        writer,
+                        "{:>12}{:<23}{}",
+                        "", "bDescriptorType", info.b_descriptor_type
+                    )?;
+                    writeln!(writer, "{:>12}{:<23}{}", "", "wReserved", { info.w_reserved })?;
+                    writeln!(writer, "{:>12}{:<23}{}", "", "dwBytesPerInterval", {
                         info.dw_bytes_per_interval
-                    });
+                    })?;
                 }
                 Descriptor::InterfaceAssociation(info) => {
-                    println!("{:>12}Interface Association Descriptor:", "");
-                    println!("{:>14}{:<21}{}", "", "bLength", info.b_length);
-                    println!("{:>14}{:<21}{}", "", "bDescriptorType", info.b_descriptor_type);
-                    println!("{:>14}{:<21}{}", "", "bFirstInterface", info.b_first_interface);
-                    println!("{:>14}{:<21}{}", "", "bInterfaceCount", info.b_interface_count);
-                    println!("{:>14}{:<21}{}", "", "bFunctionClass", info.b_function_class);
-                    println!("{:>14}{:<21}{}", "", "bFunctionSubClass", info.b_function_sub_class);
-                    println!("{:>14}{:<21}{}", "", "bFunctionProtocol", info.b_function_protocol);
-                    println!("{:>14}{:<21}{}", "", "iFunction", info.i_function);
+                    writeln!(writer, "{:>12}Interface Association Descriptor:", "")?;
+                    writeln!(writer, "{:>14}{:<21}{}", "", "bLength", info.b_length)?;
+                    writeln!(
+                        writer,
+                        "{:>14}{:<21}{}",
+                        "", "bDescriptorType", info.b_descriptor_type
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>14}{:<21}{}",
+                        "", "bFirstInterface", info.b_first_interface
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>14}{:<21}{}",
+                        "", "bInterfaceCount", info.b_interface_count
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>14}{:<21}{}",
+                        "", "bFunctionClass", info.b_function_class
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>14}{:<21}{}",
+                        "", "bFunctionSubClass", info.b_function_sub_class
+                    )?;
+                    writeln!(
+                        writer,
+                        "{:>14}{:<21}{}",
+                        "", "bFunctionProtocol", info.b_function_protocol
+                    )?;
+                    writeln!(writer, "{:>14}{:<21}{}", "", "iFunction", info.i_function)?;
                 }
                 Descriptor::Unknown(buffer) => {
-                    println!("Unknown Descriptor:");
-                    println!("  {:<33}{}", "bLength", buffer[0]);
-                    println!("  {:<33}{}", "bDescriptorType", buffer[1]);
-                    println!("  {:X?}", buffer);
+                    writeln!(writer, "Unknown Descriptor:")?;
+                    writeln!(writer, "  {:<33}{}", "bLength", buffer[0])?;
+                    writeln!(writer, "  {:<33}{}", "bDescriptorType", buffer[1])?;
+                    writeln!(writer, "  {:X?}", buffer)?;
                 }
             }
         }
@@ -286,7 +422,11 @@ impl DeviceNode {
     }
 }
 
-async fn list_tree(device_watcher: &DeviceWatcherProxy, args: &Args) -> Result<()> {
+async fn list_tree(
+    device_watcher: &DeviceWatcherProxy,
+    args: &Args,
+    writer: &mut (impl Write + Send),
+) -> Result<()> {
     let mut devices = Vec::new();
     while let Ok(device) = device_watcher
         .next_device()
@@ -311,30 +451,33 @@ async fn list_tree(device_watcher: &DeviceWatcherProxy, args: &Args) -> Result<(
         .filter_map(|device| device.depth.lock().unwrap().clone())
         .fold(0, std::cmp::max::<usize>);
 
-    print!("ID   ");
+    write!(writer, "ID   ")?;
     for _ in 0..max_depth {
-        print!("    ");
+        write!(writer, "    ")?;
     }
-    println!(" VID:PID   SPEED  MANUFACTURER PRODUCT");
+    writeln!(writer, " VID:PID   SPEED  MANUFACTURER PRODUCT")?;
 
-    do_list_tree(&devices, 0, max_depth, args).await
+    do_list_tree(&devices, 0, max_depth, args, writer).await
 }
 
-fn do_list_tree<'a>(
+fn do_list_tree<'a, W: Write + Send>(
     devices: &'a [DeviceNode],
     hub_id: u32,
     max_depth: usize,
     args: &'a Args,
+    writer: &'a mut W,
 ) -> BoxFuture<'a, Result<()>> {
     async move {
         for device in devices.iter() {
             if device.hub_id == hub_id {
                 let depth = device.depth.lock().unwrap().unwrap().clone();
-                match list_device(&device.device, device.devnum, depth, max_depth, args).await {
+                match list_device(&device.device, device.devnum, depth, max_depth, args, writer)
+                    .await
+                {
                     Ok(()) => {}
                     Err(e) => eprintln!("Error: {:?}", e),
                 }
-                do_list_tree(devices, device.device_id, max_depth, args).await?;
+                do_list_tree(devices, device.device_id, max_depth, args, writer).await?;
             }
         }
         Ok(())
@@ -492,8 +635,9 @@ mod test {
         let server_task = run_usb_server(stream).fuse();
         let test_task = async move {
             let args = Args { tree: false, verbose: true, configuration: None, device: None };
-            println!("ID    VID:PID   SPEED  MANUFACTURER PRODUCT");
-            list_device(&device, 0, 0, 0, &args).await.unwrap();
+            let mut writer = std::io::stdout();
+            writeln!(writer, "ID    VID:PID   SPEED  MANUFACTURER PRODUCT").unwrap();
+            list_device(&device, 0, 0, 0, &args, &mut writer).await.unwrap();
         }
         .fuse();
         futures::pin_mut!(server_task, test_task);
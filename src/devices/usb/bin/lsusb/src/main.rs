@@ -13,5 +13,5 @@ async fn main() -> Result<()> {
     let (proxy, server) = fidl::endpoints::create_proxy::<DeviceWatcherMarker>()?;
 
     fdio::service_connect("/svc/fuchsia.hardware.usb.DeviceWatcher", server.into_channel())?;
-    lsusb::lsusb(proxy, args).await
+    lsusb::lsusb(proxy, args, &mut std::io::stdout()).await
 }